@@ -0,0 +1,76 @@
+//! Engine throughput benchmarks
+//!
+//! Run with `cargo bench --bench engine_bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gonhanh_core::engine::shortcut::Shortcut;
+use gonhanh_core::engine::validation::{is_valid, is_valid_for_transform, ValidationCache};
+use gonhanh_core::engine::Engine;
+use gonhanh_core::utils::{keys_from_str, type_word};
+
+/// Long words with shortcuts defined: words longer than any trigger skip matching
+fn shortcut_long_words(c: &mut Criterion) {
+    let input = "nghieengs truowngf nguowif khuyeenr vn ".repeat(200);
+    c.bench_function("shortcut_long_words", |b| {
+        b.iter(|| {
+            let mut e = Engine::new();
+            e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+            e.shortcuts_mut().add(Shortcut::new("hcm", "Hồ Chí Minh"));
+            type_word(&mut e, black_box(&input))
+        })
+    });
+}
+
+/// Long plain words: letters with no modifier role take the fast path
+fn plain_long_words(c: &mut Criterion) {
+    let input = "nghiengthuongchung khuynhhuong chuyenngang ".repeat(200);
+    c.bench_function("plain_long_words", |b| {
+        b.iter(|| type_word(&mut Engine::new(), black_box(&input)))
+    });
+}
+
+/// 30+ letter run-on words: every modifier key validates the whole buffer
+fn long_run_on_words(c: &mut Criterion) {
+    let input = "supercalifragilisticexpialidocious antidisestablishmentarianism ".repeat(200);
+    c.bench_function("long_run_on_words", |b| {
+        b.iter(|| type_word(&mut Engine::new(), black_box(&input)))
+    });
+}
+
+/// Per-keystroke checks on a growing 34-letter word, direct vs `ValidationCache`
+fn validation_cache(c: &mut Criterion) {
+    let word = keys_from_str("supercalifragilisticexpialidocious");
+    let mut group = c.benchmark_group("validation_per_keystroke");
+    group.bench_function("direct", |b| {
+        b.iter(|| {
+            for len in 1..=word.len() {
+                let keys = black_box(&word[..len]);
+                // try_tone, try_mark and the delayed-stroke check on one buffer
+                black_box(is_valid_for_transform(keys));
+                black_box(is_valid_for_transform(keys));
+                black_box(is_valid(keys));
+            }
+        })
+    });
+    group.bench_function("cached", |b| {
+        let mut cache = ValidationCache::default();
+        b.iter(|| {
+            for len in 1..=word.len() {
+                let keys = black_box(&word[..len]);
+                black_box(cache.is_valid_for_transform(keys));
+                black_box(cache.is_valid_for_transform(keys));
+                black_box(cache.is_valid(keys));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    shortcut_long_words,
+    plain_long_words,
+    long_run_on_words,
+    validation_cache
+);
+criterion_main!(benches);
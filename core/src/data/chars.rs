@@ -50,6 +50,22 @@ const VOWEL_TABLE: [(char, [char; 5]); 12] = [
     ('y', ['ý', 'ỳ', 'ỷ', 'ỹ', 'ỵ']),
 ];
 
+/// Uppercase mirror of `VOWEL_TABLE` (same order, for table-based casing)
+const VOWEL_TABLE_UPPER: [(char, [char; 5]); 12] = [
+    ('A', ['Á', 'À', 'Ả', 'Ã', 'Ạ']),
+    ('Ă', ['Ắ', 'Ằ', 'Ẳ', 'Ẵ', 'Ặ']),
+    ('Â', ['Ấ', 'Ầ', 'Ẩ', 'Ẫ', 'Ậ']),
+    ('E', ['É', 'È', 'Ẻ', 'Ẽ', 'Ẹ']),
+    ('Ê', ['Ế', 'Ề', 'Ể', 'Ễ', 'Ệ']),
+    ('I', ['Í', 'Ì', 'Ỉ', 'Ĩ', 'Ị']),
+    ('O', ['Ó', 'Ò', 'Ỏ', 'Õ', 'Ọ']),
+    ('Ô', ['Ố', 'Ồ', 'Ổ', 'Ỗ', 'Ộ']),
+    ('Ơ', ['Ớ', 'Ờ', 'Ở', 'Ỡ', 'Ợ']),
+    ('U', ['Ú', 'Ù', 'Ủ', 'Ũ', 'Ụ']),
+    ('Ư', ['Ứ', 'Ừ', 'Ử', 'Ữ', 'Ự']),
+    ('Y', ['Ý', 'Ỳ', 'Ỷ', 'Ỹ', 'Ỵ']),
+];
+
 /// Get base character from key + tone modifier
 ///
 /// # Arguments
@@ -103,12 +119,50 @@ fn apply_mark(base: char, m: u8) -> char {
         .unwrap_or(base)
 }
 
-/// Convert to uppercase using Rust's Unicode-aware method
+/// Map a Vietnamese letter between the two vowel tables (same row and column)
+fn map_case(
+    ch: char,
+    from: &[(char, [char; 5]); 12],
+    to: &[(char, [char; 5]); 12],
+) -> Option<char> {
+    from.iter()
+        .zip(to.iter())
+        .find_map(|((base, marks), (to_base, to_marks))| {
+            if *base == ch {
+                Some(*to_base)
+            } else {
+                marks.iter().position(|&m| m == ch).map(|i| to_marks[i])
+            }
+        })
+}
+
+/// Uppercase a character via a dedicated table for Vietnamese letters
 ///
-/// This handles all Vietnamese characters correctly without
-/// explicit character mapping.
-fn to_upper(ch: char) -> char {
-    ch.to_uppercase().next().unwrap_or(ch)
+/// All 72 vowel forms and đ are mapped explicitly, so casing never depends
+/// on the host's Unicode tables. Other chars fall back to `char::to_uppercase`.
+pub fn to_upper(ch: char) -> char {
+    if ch.is_ascii() {
+        return ch.to_ascii_uppercase();
+    }
+    if ch == 'đ' {
+        return 'Đ';
+    }
+    map_case(ch, &VOWEL_TABLE, &VOWEL_TABLE_UPPER)
+        .unwrap_or_else(|| ch.to_uppercase().next().unwrap_or(ch))
+}
+
+/// Lowercase a character via a dedicated table for Vietnamese letters
+///
+/// Inverse of `to_upper`. Other chars fall back to `char::to_lowercase`.
+pub fn to_lower(ch: char) -> char {
+    if ch.is_ascii() {
+        return ch.to_ascii_lowercase();
+    }
+    if ch == 'Đ' {
+        return 'đ';
+    }
+    map_case(ch, &VOWEL_TABLE_UPPER, &VOWEL_TABLE)
+        .unwrap_or_else(|| ch.to_lowercase().next().unwrap_or(ch))
 }
 
 /// Convert key + modifiers to Vietnamese character
@@ -129,6 +183,43 @@ pub fn to_char(key: u16, caps: bool, tone: u8, mark: u8) -> Option<char> {
     Some(if caps { to_upper(marked) } else { marked })
 }
 
+/// Append the canonical decomposition (NFD) of `ch` to `out`
+///
+/// Vietnamese vowels split into the base letter plus combining marks in
+/// canonical order: horn, dot below, then circumflex/breve and the other marks
+/// ("ệ" → e + U+0323 + U+0302, "ợ" → o + U+031B + U+0323).
+/// Everything else (đ included, it has no decomposition) is pushed unchanged.
+pub fn decompose(ch: char, out: &mut Vec<char>) {
+    let Some(p) = parse_char(ch).filter(|p| !p.stroke && (p.tone | p.mark) != 0) else {
+        out.push(ch);
+        return;
+    };
+    let Some(base) = to_char(p.key, p.caps, tone::NONE, mark::NONE) else {
+        out.push(ch);
+        return;
+    };
+    out.push(base);
+    let horn_or_breve = p.tone == tone::HORN;
+    if horn_or_breve && p.key != keys::A {
+        out.push('\u{031B}');
+    }
+    if p.mark == mark::NANG {
+        out.push('\u{0323}');
+    }
+    if p.tone == tone::CIRCUMFLEX {
+        out.push('\u{0302}');
+    } else if horn_or_breve && p.key == keys::A {
+        out.push('\u{0306}');
+    }
+    match p.mark {
+        mark::SAC => out.push('\u{0301}'),
+        mark::HUYEN => out.push('\u{0300}'),
+        mark::HOI => out.push('\u{0309}'),
+        mark::NGA => out.push('\u{0303}'),
+        _ => {}
+    }
+}
+
 /// Get đ/Đ character
 pub fn get_d(caps: bool) -> char {
     if caps {
@@ -408,6 +499,42 @@ pub fn parse_char(c: char) -> Option<ParsedChar> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_case_round_trip_full_alphabet() {
+        let lower = VOWEL_TABLE
+            .iter()
+            .flat_map(|(base, marks)| std::iter::once(*base).chain(marks.iter().copied()))
+            .chain("bcdđghklmnpqrstvx".chars());
+        for ch in lower {
+            let upper = to_upper(ch);
+            assert_ne!(upper, ch, "'{}' should have an uppercase form", ch);
+            assert_eq!(upper, ch.to_uppercase().next().unwrap(), "'{}'", ch);
+            assert_eq!(to_lower(upper), ch, "'{}' round trip", ch);
+            // Already-cased input is unchanged
+            assert_eq!(to_upper(upper), upper);
+            assert_eq!(to_lower(ch), ch);
+        }
+        assert_eq!(to_upper('1'), '1');
+        assert_eq!(to_lower('-'), '-');
+    }
+
+    #[test]
+    fn test_decompose() {
+        let nfd = |ch: char| {
+            let mut out = Vec::new();
+            decompose(ch, &mut out);
+            out.into_iter().collect::<String>()
+        };
+        assert_eq!(nfd('ệ'), "e\u{0323}\u{0302}");
+        assert_eq!(nfd('ế'), "e\u{0302}\u{0301}");
+        assert_eq!(nfd('Ợ'), "O\u{031B}\u{0323}");
+        assert_eq!(nfd('ẳ'), "a\u{0306}\u{0309}");
+        assert_eq!(nfd('ư'), "u\u{031B}");
+        assert_eq!(nfd('ỹ'), "y\u{0303}");
+        assert_eq!(nfd('đ'), "đ");
+        assert_eq!(nfd('t'), "t");
+    }
+
     #[test]
     fn test_basic_vowels() {
         // Basic vowels without modifiers
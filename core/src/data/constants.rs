@@ -147,6 +147,23 @@ pub const V2_CIRCUMFLEX_REQUIRED: &[[u16; 2]] = &[
     [keys::Y, keys::E], // yê: E (V2) must have circumflex
 ];
 
+// =============================================================================
+// I/Y VARIANTS
+// =============================================================================
+
+/// Initials where a final i/y nucleus has accepted variants in both spellings
+/// (lí/lý, kĩ/kỹ, hi/hy, mĩ/mỹ, sĩ/sỹ, tí/tý, vĩ/vỹ)
+/// Others take one spelling only: "bi", "chi", "quy" (u+y, not a variant)
+pub const IY_VARIANT_INITIALS: &[u16] = &[
+    keys::H,
+    keys::K,
+    keys::L,
+    keys::M,
+    keys::S,
+    keys::T,
+    keys::V,
+];
+
 // =============================================================================
 // SPELLING RULES
 // =============================================================================
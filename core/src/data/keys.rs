@@ -1,4 +1,7 @@
 //! macOS virtual keycodes
+//!
+//! The engine works on macOS codes internally. Hosts on other platforms
+//! select a layout and their codes are translated via `KeyCode`.
 
 // Letters
 pub const A: u16 = 0;
@@ -40,6 +43,18 @@ pub const N8: u16 = 28;
 pub const N9: u16 = 25;
 pub const N0: u16 = 29;
 
+// Numeric keypad
+pub const KP0: u16 = 82;
+pub const KP1: u16 = 83;
+pub const KP2: u16 = 84;
+pub const KP3: u16 = 85;
+pub const KP4: u16 = 86;
+pub const KP5: u16 = 87;
+pub const KP6: u16 = 88;
+pub const KP7: u16 = 89;
+pub const KP8: u16 = 91;
+pub const KP9: u16 = 92;
+
 // Special
 pub const SPACE: u16 = 49;
 pub const DELETE: u16 = 51;
@@ -151,3 +166,404 @@ pub fn is_consonant(key: u16) -> bool {
 pub fn is_number(key: u16) -> bool {
     matches!(key, N0 | N1 | N2 | N3 | N4 | N5 | N6 | N7 | N8 | N9)
 }
+
+/// Map a numeric keypad key to the top-row digit key (KP1 → N1)
+pub fn numpad_to_digit(key: u16) -> Option<u16> {
+    Some(match key {
+        KP0 => N0,
+        KP1 => N1,
+        KP2 => N2,
+        KP3 => N3,
+        KP4 => N4,
+        KP5 => N5,
+        KP6 => N6,
+        KP7 => N7,
+        KP8 => N8,
+        KP9 => N9,
+        _ => return None,
+    })
+}
+
+// =============================================================================
+// PLATFORM LAYOUTS
+// =============================================================================
+
+/// Keycode layout of the host platform (see `Engine::set_layout`)
+pub const LAYOUT_MACOS: u8 = 0;
+pub const LAYOUT_WINDOWS: u8 = 1;
+pub const LAYOUT_LINUX: u8 = 2;
+
+/// Internal code for keys that have no mapping in the active layout
+pub const UNKNOWN: u16 = u16::MAX;
+
+/// Platform-neutral logical key
+///
+/// Decouples the physical key from any platform's numbering.
+/// Engine internals still work on macOS codes (`to_internal`), so every
+/// layout funnels through this enum before `process`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCode {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    N0,
+    N1,
+    N2,
+    N3,
+    N4,
+    N5,
+    N6,
+    N7,
+    N8,
+    N9,
+    Kp0,
+    Kp1,
+    Kp2,
+    Kp3,
+    Kp4,
+    Kp5,
+    Kp6,
+    Kp7,
+    Kp8,
+    Kp9,
+    Space,
+    Delete,
+    Tab,
+    Return,
+    Enter,
+    Esc,
+    Left,
+    Right,
+    Down,
+    Up,
+    Dot,
+    Comma,
+    Slash,
+    Semicolon,
+    Quote,
+    LBracket,
+    RBracket,
+    Backslash,
+    Minus,
+    Equal,
+    Backquote,
+}
+
+/// Keycode table: (logical key, [macOS virtual key, Windows VK, Linux evdev])
+///
+/// Windows has no separate keypad Enter VK, so it shares VK_RETURN.
+const LAYOUT_TABLE: &[(KeyCode, [u16; 3])] = &[
+    (KeyCode::A, [A, 0x41, 30]),
+    (KeyCode::B, [B, 0x42, 48]),
+    (KeyCode::C, [C, 0x43, 46]),
+    (KeyCode::D, [D, 0x44, 32]),
+    (KeyCode::E, [E, 0x45, 18]),
+    (KeyCode::F, [F, 0x46, 33]),
+    (KeyCode::G, [G, 0x47, 34]),
+    (KeyCode::H, [H, 0x48, 35]),
+    (KeyCode::I, [I, 0x49, 23]),
+    (KeyCode::J, [J, 0x4A, 36]),
+    (KeyCode::K, [K, 0x4B, 37]),
+    (KeyCode::L, [L, 0x4C, 38]),
+    (KeyCode::M, [M, 0x4D, 50]),
+    (KeyCode::N, [N, 0x4E, 49]),
+    (KeyCode::O, [O, 0x4F, 24]),
+    (KeyCode::P, [P, 0x50, 25]),
+    (KeyCode::Q, [Q, 0x51, 16]),
+    (KeyCode::R, [R, 0x52, 19]),
+    (KeyCode::S, [S, 0x53, 31]),
+    (KeyCode::T, [T, 0x54, 20]),
+    (KeyCode::U, [U, 0x55, 22]),
+    (KeyCode::V, [V, 0x56, 47]),
+    (KeyCode::W, [W, 0x57, 17]),
+    (KeyCode::X, [X, 0x58, 45]),
+    (KeyCode::Y, [Y, 0x59, 21]),
+    (KeyCode::Z, [Z, 0x5A, 44]),
+    (KeyCode::N0, [N0, 0x30, 11]),
+    (KeyCode::N1, [N1, 0x31, 2]),
+    (KeyCode::N2, [N2, 0x32, 3]),
+    (KeyCode::N3, [N3, 0x33, 4]),
+    (KeyCode::N4, [N4, 0x34, 5]),
+    (KeyCode::N5, [N5, 0x35, 6]),
+    (KeyCode::N6, [N6, 0x36, 7]),
+    (KeyCode::N7, [N7, 0x37, 8]),
+    (KeyCode::N8, [N8, 0x38, 9]),
+    (KeyCode::N9, [N9, 0x39, 10]),
+    (KeyCode::Kp0, [KP0, 0x60, 82]),
+    (KeyCode::Kp1, [KP1, 0x61, 79]),
+    (KeyCode::Kp2, [KP2, 0x62, 80]),
+    (KeyCode::Kp3, [KP3, 0x63, 81]),
+    (KeyCode::Kp4, [KP4, 0x64, 75]),
+    (KeyCode::Kp5, [KP5, 0x65, 76]),
+    (KeyCode::Kp6, [KP6, 0x66, 77]),
+    (KeyCode::Kp7, [KP7, 0x67, 71]),
+    (KeyCode::Kp8, [KP8, 0x68, 72]),
+    (KeyCode::Kp9, [KP9, 0x69, 73]),
+    (KeyCode::Space, [SPACE, 0x20, 57]),
+    (KeyCode::Delete, [DELETE, 0x08, 14]),
+    (KeyCode::Tab, [TAB, 0x09, 15]),
+    (KeyCode::Return, [RETURN, 0x0D, 28]),
+    (KeyCode::Enter, [ENTER, 0x0D, 96]),
+    (KeyCode::Esc, [ESC, 0x1B, 1]),
+    (KeyCode::Left, [LEFT, 0x25, 105]),
+    (KeyCode::Right, [RIGHT, 0x27, 106]),
+    (KeyCode::Down, [DOWN, 0x28, 108]),
+    (KeyCode::Up, [UP, 0x26, 103]),
+    (KeyCode::Dot, [DOT, 0xBE, 52]),
+    (KeyCode::Comma, [COMMA, 0xBC, 51]),
+    (KeyCode::Slash, [SLASH, 0xBF, 53]),
+    (KeyCode::Semicolon, [SEMICOLON, 0xBA, 39]),
+    (KeyCode::Quote, [QUOTE, 0xDE, 40]),
+    (KeyCode::LBracket, [LBRACKET, 0xDB, 26]),
+    (KeyCode::RBracket, [RBRACKET, 0xDD, 27]),
+    (KeyCode::Backslash, [BACKSLASH, 0xDC, 43]),
+    (KeyCode::Minus, [MINUS, 0xBD, 12]),
+    (KeyCode::Equal, [EQUAL, 0xBB, 13]),
+    (KeyCode::Backquote, [BACKQUOTE, 0xC0, 41]),
+];
+
+impl KeyCode {
+    /// Look up logical key from a platform keycode
+    pub fn from_platform(layout: u8, code: u16) -> Option<KeyCode> {
+        let col = layout as usize;
+        if col > LAYOUT_LINUX as usize {
+            return None;
+        }
+        LAYOUT_TABLE
+            .iter()
+            .find(|(_, codes)| codes[col] == code)
+            .map(|&(k, _)| k)
+    }
+
+    /// Platform keycode for this logical key
+    pub fn to_platform(self, layout: u8) -> Option<u16> {
+        let col = layout as usize;
+        if col > LAYOUT_LINUX as usize {
+            return None;
+        }
+        LAYOUT_TABLE
+            .iter()
+            .find(|&&(k, _)| k == self)
+            .map(|(_, codes)| codes[col])
+    }
+
+    /// Internal keycode used by the engine
+    pub fn to_internal(self) -> u16 {
+        self.to_platform(LAYOUT_MACOS).unwrap_or(UNKNOWN)
+    }
+}
+
+/// Translate a platform keycode to the internal keycode
+///
+/// macOS codes pass through untouched. Unmapped codes from other
+/// layouts become `UNKNOWN` so they can't alias a macOS key.
+pub fn translate(layout: u8, code: u16) -> u16 {
+    if layout == LAYOUT_MACOS {
+        return code;
+    }
+    KeyCode::from_platform(layout, code)
+        .map(KeyCode::to_internal)
+        .unwrap_or(UNKNOWN)
+}
+
+/// Map a typed character to the internal (macOS) keycode that produces it
+///
+/// For hosts without a keycode layer. Letters map regardless of case, and
+/// shifted symbols map to their base key (e.g., '@' → N2, '"' → QUOTE).
+/// Control chars cover the editing keys: '\x08'/'\x7f' → DELETE,
+/// '\t' → TAB, '\n'/'\r' → RETURN, '\x1b' → ESC.
+pub fn from_char(ch: char) -> Option<u16> {
+    let key = match ch.to_ascii_lowercase() {
+        'a' => A,
+        'b' => B,
+        'c' => C,
+        'd' => D,
+        'e' => E,
+        'f' => F,
+        'g' => G,
+        'h' => H,
+        'i' => I,
+        'j' => J,
+        'k' => K,
+        'l' => L,
+        'm' => M,
+        'n' => N,
+        'o' => O,
+        'p' => P,
+        'q' => Q,
+        'r' => R,
+        's' => S,
+        't' => T,
+        'u' => U,
+        'v' => V,
+        'w' => W,
+        'x' => X,
+        'y' => Y,
+        'z' => Z,
+        '0' | ')' => N0,
+        '1' | '!' => N1,
+        '2' | '@' => N2,
+        '3' | '#' => N3,
+        '4' | '$' => N4,
+        '5' | '%' => N5,
+        '6' | '^' => N6,
+        '7' | '&' => N7,
+        '8' | '*' => N8,
+        '9' | '(' => N9,
+        ' ' => SPACE,
+        '.' | '>' => DOT,
+        ',' | '<' => COMMA,
+        '/' | '?' => SLASH,
+        ';' | ':' => SEMICOLON,
+        '\'' | '"' => QUOTE,
+        '[' | '{' => LBRACKET,
+        ']' | '}' => RBRACKET,
+        '\\' | '|' => BACKSLASH,
+        '-' | '_' => MINUS,
+        '=' | '+' => EQUAL,
+        '`' | '~' => BACKQUOTE,
+        '\x08' | '\x7f' => DELETE,
+        '\t' => TAB,
+        '\n' | '\r' => RETURN,
+        '\x1b' => ESC,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Check if a character is the shifted form of its key (e.g., '@', ':', '"')
+pub fn is_shifted_char(ch: char) -> bool {
+    matches!(
+        ch,
+        ')' | '!'
+            | '@'
+            | '#'
+            | '$'
+            | '%'
+            | '^'
+            | '&'
+            | '*'
+            | '('
+            | '>'
+            | '<'
+            | '?'
+            | ':'
+            | '"'
+            | '{'
+            | '}'
+            | '|'
+            | '_'
+            | '+'
+            | '~'
+    )
+}
+
+/// Map a portable `keyboard_types::Code` to the internal (macOS) keycode
+///
+/// Lets Linux/Windows hosts feed W3C key codes instead of maintaining
+/// their own translation tables. Returns None for keys the engine doesn't use.
+/// The result is already internal: feed it with `Engine::on_key_code`, or with
+/// `on_key` only while the layout is `LAYOUT_MACOS` (other layouts would
+/// translate it a second time).
+#[cfg(feature = "keyboard-types")]
+pub fn from_key_code(code: keyboard_types::Code) -> Option<u16> {
+    use keyboard_types::Code;
+    let key = match code {
+        // Letters
+        Code::KeyA => A,
+        Code::KeyB => B,
+        Code::KeyC => C,
+        Code::KeyD => D,
+        Code::KeyE => E,
+        Code::KeyF => F,
+        Code::KeyG => G,
+        Code::KeyH => H,
+        Code::KeyI => I,
+        Code::KeyJ => J,
+        Code::KeyK => K,
+        Code::KeyL => L,
+        Code::KeyM => M,
+        Code::KeyN => N,
+        Code::KeyO => O,
+        Code::KeyP => P,
+        Code::KeyQ => Q,
+        Code::KeyR => R,
+        Code::KeyS => S,
+        Code::KeyT => T,
+        Code::KeyU => U,
+        Code::KeyV => V,
+        Code::KeyW => W,
+        Code::KeyX => X,
+        Code::KeyY => Y,
+        Code::KeyZ => Z,
+        // Numbers
+        Code::Digit0 => N0,
+        Code::Digit1 => N1,
+        Code::Digit2 => N2,
+        Code::Digit3 => N3,
+        Code::Digit4 => N4,
+        Code::Digit5 => N5,
+        Code::Digit6 => N6,
+        Code::Digit7 => N7,
+        Code::Digit8 => N8,
+        Code::Digit9 => N9,
+        Code::Numpad0 => KP0,
+        Code::Numpad1 => KP1,
+        Code::Numpad2 => KP2,
+        Code::Numpad3 => KP3,
+        Code::Numpad4 => KP4,
+        Code::Numpad5 => KP5,
+        Code::Numpad6 => KP6,
+        Code::Numpad7 => KP7,
+        Code::Numpad8 => KP8,
+        Code::Numpad9 => KP9,
+        // Special
+        Code::Space => SPACE,
+        Code::Backspace => DELETE,
+        Code::Tab => TAB,
+        Code::Enter => RETURN,
+        Code::NumpadEnter => ENTER,
+        Code::Escape => ESC,
+        Code::ArrowLeft => LEFT,
+        Code::ArrowRight => RIGHT,
+        Code::ArrowDown => DOWN,
+        Code::ArrowUp => UP,
+        // Punctuation
+        Code::Period => DOT,
+        Code::Comma => COMMA,
+        Code::Slash => SLASH,
+        Code::Semicolon => SEMICOLON,
+        Code::Quote => QUOTE,
+        Code::BracketLeft => LBRACKET,
+        Code::BracketRight => RBRACKET,
+        Code::Backslash => BACKSLASH,
+        Code::Minus => MINUS,
+        Code::Equal => EQUAL,
+        Code::Backquote => BACKQUOTE,
+        _ => return None,
+    };
+    Some(key)
+}
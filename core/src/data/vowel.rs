@@ -272,6 +272,30 @@ impl Phonology {
         modern: bool,
         has_qu_initial: bool,
         has_gi_initial: bool,
+    ) -> usize {
+        Self::find_tone_position_split(
+            vowels,
+            has_final_consonant,
+            modern,
+            modern,
+            has_qu_initial,
+            has_gi_initial,
+        )
+    }
+
+    /// Same as `find_tone_position`, with the two modern/traditional decisions split
+    ///
+    /// - `oa_oe_modern`: open oa/oe (hoà vs hòa, khoẻ vs khỏe)
+    /// - `uy_modern`: uy without qu-initial (thuý vs thúy)
+    ///
+    /// Closed syllables (toàn, hoạch) are rule-driven and ignore both.
+    pub fn find_tone_position_split(
+        vowels: &[Vowel],
+        has_final_consonant: bool,
+        oa_oe_modern: bool,
+        uy_modern: bool,
+        has_qu_initial: bool,
+        has_gi_initial: bool,
     ) -> usize {
         // Handle gi-initial: first vowel 'i' is part of consonant, use remaining vowels
         // Example: "giàu" → vowels [i, a, u], but with gi-initial, treat as [a, u] diphthong
@@ -283,7 +307,8 @@ impl Phonology {
                 2 => Self::find_diphthong_position(
                     remaining,
                     has_final_consonant,
-                    modern,
+                    oa_oe_modern,
+                    uy_modern,
                     false,
                     false,
                 ),
@@ -301,7 +326,8 @@ impl Phonology {
                 2 => Self::find_diphthong_position(
                     remaining,
                     has_final_consonant,
-                    modern,
+                    oa_oe_modern,
+                    uy_modern,
                     false, // No longer qu-initial for remaining vowels
                     false,
                 ),
@@ -315,7 +341,8 @@ impl Phonology {
             2 => Self::find_diphthong_position(
                 vowels,
                 has_final_consonant,
-                modern,
+                oa_oe_modern,
+                uy_modern,
                 has_qu_initial,
                 has_gi_initial,
             ),
@@ -343,7 +370,8 @@ impl Phonology {
     fn find_diphthong_position(
         vowels: &[Vowel],
         has_final_consonant: bool,
-        modern: bool,
+        oa_oe_modern: bool,
+        uy_modern: bool,
         has_qu_initial: bool,
         has_gi_initial: bool,
     ) -> usize {
@@ -408,12 +436,14 @@ impl Phonology {
             .any(|p| p[0] == pair[0] && p[1] == pair[1])
         {
             // Only oa, oe, uy are affected by modern/traditional debate
-            let is_modern_pattern = matches!(
-                (v1.key, v2.key),
-                (keys::O, keys::A) | (keys::O, keys::E) | (keys::U, keys::Y)
-            );
-            if is_modern_pattern {
-                return if modern { v2.pos } else { v1.pos };
+            match (v1.key, v2.key) {
+                (keys::O, keys::A) | (keys::O, keys::E) => {
+                    return if oa_oe_modern { v2.pos } else { v1.pos };
+                }
+                (keys::U, keys::Y) => {
+                    return if uy_modern { v2.pos } else { v1.pos };
+                }
+                _ => {}
             }
             // Other patterns (uê, iê, uô): always 2nd vowel
             return v2.pos;
@@ -60,6 +60,7 @@ impl Buffer {
         }
     }
 
+    /// Append a char; ignored once the buffer holds `MAX` chars
     pub fn push(&mut self, c: Char) {
         if self.len < MAX {
             self.data[self.len] = c;
@@ -204,4 +205,13 @@ mod tests {
         buf.clear();
         assert!(buf.is_empty());
     }
+
+    #[test]
+    fn test_buffer_push_overflow_ignored() {
+        let mut buf = Buffer::new();
+        for _ in 0..MAX + 10 {
+            buf.push(Char::new(0, false));
+        }
+        assert_eq!(buf.len(), MAX);
+    }
 }
@@ -0,0 +1,105 @@
+//! Stateless Composition
+//!
+//! Turns a raw keystroke string into the text a user would see, using a
+//! throwaway engine. For batch conversion and server-side rendering, where
+//! callers shouldn't share (or lock) a long-lived engine.
+
+use super::{Action, Engine};
+use crate::data::keys;
+
+/// Engine flags for `compose` (defaults match `Engine::new`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComposeOptions {
+    /// Modern tone placement ("thuý" instead of "thúy")
+    pub modern_tone: bool,
+    /// Place tones without validating the syllable
+    pub free_tone: bool,
+    /// Restore English-looking words to raw keystrokes on word end
+    pub english_auto_restore: bool,
+    /// Telex: keep a word-initial 'w' as 'w' instead of 'ư'
+    pub skip_w_shortcut: bool,
+    /// Capitalize the first letter of each sentence
+    pub auto_capitalize: bool,
+}
+
+impl Default for ComposeOptions {
+    fn default() -> Self {
+        Self {
+            modern_tone: true,
+            free_tone: false,
+            english_auto_restore: false,
+            skip_w_shortcut: false,
+            auto_capitalize: false,
+        }
+    }
+}
+
+/// Compose raw keystrokes into text (method: 0=Telex, 1=VNI)
+///
+/// Every char of `raw` is typed in order, including spaces and punctuation,
+/// so multi-word input works: "vieetj nam" → "việt nam". No shortcuts apply.
+pub fn compose(raw: &str, method: u8, opts: ComposeOptions) -> String {
+    let mut e = Engine::new();
+    e.set_method(method);
+    e.set_modern_tone(opts.modern_tone);
+    e.set_free_tone(opts.free_tone);
+    e.set_english_auto_restore(opts.english_auto_restore);
+    e.set_skip_w_shortcut(opts.skip_w_shortcut);
+    e.set_auto_capitalize(opts.auto_capitalize);
+
+    let mut screen: Vec<char> = Vec::with_capacity(raw.len());
+    for ch in raw.chars() {
+        let r = e.on_char(ch, false, false, false);
+        if r.action == Action::None as u8 {
+            // Passed through: the host types the char itself
+            screen.push(ch);
+            continue;
+        }
+
+        screen.truncate(screen.len().saturating_sub(r.backspace as usize));
+        screen.extend(
+            r.chars[..r.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c)),
+        );
+        // Break keys (punctuation) follow the edit unless the engine consumed them
+        let key = keys::from_char(ch).unwrap_or(keys::UNKNOWN);
+        if key != keys::SPACE
+            && keys::is_break_ext(key, keys::is_shifted_char(ch))
+            && !r.key_consumed()
+        {
+            screen.push(ch);
+        }
+    }
+    screen.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose() {
+        let opts = ComposeOptions::default();
+        assert_eq!(compose("vieetj nam", 0, opts), "việt nam");
+        assert_eq!(compose("vie65t nam", 1, opts), "việt nam");
+        assert_eq!(compose("dduowngf, ddi!", 0, opts), "đường, đi!");
+        assert_eq!(compose("", 0, opts), "");
+    }
+
+    #[test]
+    fn test_compose_options() {
+        let classic = ComposeOptions {
+            modern_tone: false,
+            ..Default::default()
+        };
+        assert_eq!(compose("thuys", 0, ComposeOptions::default()), "thuý");
+        assert_eq!(compose("thuys", 0, classic), "thúy");
+
+        let restore = ComposeOptions {
+            english_auto_restore: true,
+            ..Default::default()
+        };
+        assert_eq!(compose("user ", 0, restore), "user ");
+    }
+}
@@ -11,7 +11,9 @@
 //! 4. **Longest-Match-First**: For diacritic placement
 
 pub mod buffer;
+mod compose;
 pub mod shortcut;
+mod snapshot;
 pub mod syllable;
 pub mod transform;
 pub mod validation;
@@ -21,18 +23,36 @@ use crate::data::{
     constants, keys,
     vowel::{Phonology, Vowel},
 };
-use crate::input::{self, ToneType};
+use crate::input::{self, t9, ModifierKeys, ToneType};
 use crate::utils;
 use buffer::{Buffer, Char, MAX};
-use shortcut::{InputMethod, ShortcutTable};
-use validation::{is_foreign_word_pattern, is_valid, is_valid_for_transform, is_valid_with_tones};
+use shortcut::{InputMethod, ShortcutTable, TriggerCondition};
+use std::collections::{HashSet, VecDeque};
+use validation::{is_foreign_word_pattern, is_valid, is_valid_with_tones, ValidationCache};
+
+pub use compose::{compose, ComposeOptions};
+
+/// Record an engine decision in the trace (`trace` feature; no code otherwise)
+macro_rules! trace {
+    ($engine:expr, $($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        $engine.record_trace(format!($($arg)*));
+    };
+}
+
+/// Trace entries kept between `take_trace` calls (oldest dropped first)
+#[cfg(feature = "trace")]
+const TRACE_LIMIT: usize = 512;
 
 /// Engine action result
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Action {
+    /// Pass the key through unchanged
     None = 0,
+    /// Delete `backspace` chars, then insert `chars`
     Send = 1,
+    /// Same as Send, but the word went back to raw keystrokes (ESC, auto-restore)
     Restore = 2,
 }
 
@@ -46,12 +66,239 @@ pub struct Result {
     /// Flags byte:
     /// - bit 0 (0x01): key_consumed - if set, the trigger key should NOT be passed through
     ///   Used for shortcuts where the trigger key is part of the replacement
+    /// - bit 1 (0x02): overflow - shortcut output too long for `chars` (`OVERFLOW_ERROR`)
+    /// - bit 2 (0x04): shortcut_cancelled - key typed past a complete shortcut trigger
+    /// - bit 3 (0x08): auto_capitalized - uppercase was forced by auto-capitalize
+    /// - bit 4 (0x10): rejected - modifier key was invalid and typed as a letter
+    /// - bit 5 (0x20): shortcut_pending - word is a proper prefix of a shortcut trigger
+    /// - bit 6 (0x40): composition_reset - the composing word was dropped (ctrl, break, disabled)
     pub flags: u8,
+    /// Index into `chars` of the vowel carrying the diacritic just applied
+    /// (mark, or ^/horn/breve when the word has no mark), for UI highlighting.
+    /// `NO_TONE_INDEX` (0xFF) if the result is not a mark/tone transform.
+    pub tone_index: u8,
 }
 
+/// `Result::tone_index` value when no output char carries a new diacritic
+pub const NO_TONE_INDEX: u8 = 0xFF;
+
 /// Flag: key was consumed by shortcut, don't pass through
 pub const FLAG_KEY_CONSUMED: u8 = 0x01;
 
+/// Flag: shortcut output was longer than `MAX` with `OVERFLOW_ERROR`; not expanded
+pub const FLAG_OVERFLOW: u8 = 0x02;
+
+/// Flag: the key typed past a complete word shortcut trigger ("vn" + 'x'),
+/// declining its expansion; hosts may dismiss an expansion hint
+pub const FLAG_SHORTCUT_CANCELLED: u8 = 0x04;
+
+/// Flag: letter was uppercased by auto-capitalize (host may offer undo)
+pub const FLAG_AUTO_CAPITALIZED: u8 = 0x08;
+
+/// Flag: key was a modifier rejected as invalid Vietnamese and passed through
+/// as a plain letter (e.g., "kw" can't become "kư"); hosts may give feedback
+pub const FLAG_REJECTED: u8 = 0x10;
+
+/// Flag: the word so far is a proper prefix of a shortcut trigger, so it may
+/// still expand (for "expanding..." hints); doesn't change the output
+pub const FLAG_SHORTCUT_PENDING: u8 = 0x20;
+
+/// Flag: the key ended the composing word as a side effect (ctrl chord, break
+/// key, IME disabled); later results won't edit that word again
+pub const FLAG_COMPOSITION_RESET: u8 = 0x40;
+
+/// Auto-capitalize trigger: '.'
+pub const CAPITALIZE_PERIOD: u8 = 0x01;
+/// Auto-capitalize trigger: '!'
+pub const CAPITALIZE_EXCLAIM: u8 = 0x02;
+/// Auto-capitalize trigger: '?'
+pub const CAPITALIZE_QUESTION: u8 = 0x04;
+/// Auto-capitalize trigger: Enter/Return
+pub const CAPITALIZE_ENTER: u8 = 0x08;
+/// Auto-capitalize trigger: ':'
+pub const CAPITALIZE_COLON: u8 = 0x10;
+/// Default auto-capitalize triggers: . ! ? Enter
+pub const CAPITALIZE_DEFAULT: u8 =
+    CAPITALIZE_PERIOD | CAPITALIZE_EXCLAIM | CAPITALIZE_QUESTION | CAPITALIZE_ENTER;
+
+/// Feature bit 0: Telex: word-initial 'w' stays 'w'
+pub const FEATURE_SKIP_W_SHORTCUT: u32 = 1 << 0;
+/// Feature bit 1: ESC restores raw keystrokes
+pub const FEATURE_ESC_RESTORE: u32 = 1 << 1;
+/// Feature bit 2: Place tones without spelling validation
+pub const FEATURE_FREE_TONE: u32 = 1 << 2;
+/// Feature bit 3: Modern tone placement ("hoà")
+pub const FEATURE_MODERN_TONE: u32 = 1 << 3;
+/// Feature bit 4: Restore English words on word end
+pub const FEATURE_ENGLISH_AUTO_RESTORE: u32 = 1 << 4;
+/// Feature bit 5: Capitalize the first letter of a sentence
+pub const FEATURE_AUTO_CAPITALIZE: u32 = 1 << 5;
+/// Feature bit 6: Backtick code spans suppress transforms
+pub const FEATURE_CODE_SPAN: u32 = 1 << 6;
+/// Feature bit 7: Minimize backspaces
+pub const FEATURE_DIFF_OUTPUT: u32 = 1 << 7;
+/// Feature bit 8: Every edit resends the whole word
+pub const FEATURE_FULL_REPLACE: u32 = 1 << 8;
+/// Feature bit 9: Send each word once, on commit
+pub const FEATURE_DEFERRED_EMIT: u32 = 1 << 9;
+/// Feature bit 10: Apostrophe between letters stays in the word
+pub const FEATURE_APOSTROPHE_IN_WORD: u32 = 1 << 10;
+/// Feature bit 11: Hyphen between letters keeps the word open
+pub const FEATURE_HYPHEN_IN_WORD: u32 = 1 << 11;
+/// Feature bit 12: Invalid Vietnamese reverts on word end
+pub const FEATURE_REVERT_INVALID: u32 = 1 << 12;
+/// Feature bit 13: Backspace strips one diacritic at a time
+pub const FEATURE_SMART_BACKSPACE: u32 = 1 << 13;
+/// Feature bit 14: Backspace after space reopens the word
+pub const FEATURE_BACKSPACE_RESTORE: u32 = 1 << 14;
+/// Feature bit 15: Restore raw as soon as a consonant makes an invalid final
+pub const FEATURE_STRICT_FINALS: u32 = 1 << 15;
+/// Feature bit 16: Defer breve in open syllables until a final ("aw" stays "aw")
+pub const FEATURE_DEFER_BREVE: u32 = 1 << 16;
+/// Feature bit 17: Wrap committed words in bidi isolates
+pub const FEATURE_BIDI_ISOLATION: u32 = 1 << 17;
+/// Feature bit 18: Horn both u and o of "uo" at once ("huow" → "hươ")
+pub const FEATURE_EAGER_UO_HORN: u32 = 1 << 18;
+/// Feature bit 19: No expansion after typing past a trigger
+pub const FEATURE_SHORTCUT_EXACT_BOUNDARY: u32 = 1 << 19;
+/// Feature bit 20: Simple mode (no tone reposition, no short-pattern stroke)
+pub const FEATURE_SIMPLE_MODE: u32 = 1 << 20;
+/// Feature bit 21: Output in NFD (decomposed) form
+pub const FEATURE_NFD_OUTPUT: u32 = 1 << 21;
+/// Feature bit 22: No-echo field (keys pass through literally)
+pub const FEATURE_NO_ECHO: u32 = 1 << 22;
+/// Feature bit 23: VNI modifier digits on the numeric keypad
+pub const FEATURE_VNI_NUMPAD: u32 = 1 << 23;
+
+/// Feature bit with its getter and setter
+type Feature = (u32, fn(&Engine) -> bool, fn(&mut Engine, bool));
+
+/// Every `FEATURE_*` bit, for `Engine::feature_flags`
+const FEATURES: &[Feature] = &[
+    (
+        FEATURE_SKIP_W_SHORTCUT,
+        |e| e.skip_w_shortcut,
+        Engine::set_skip_w_shortcut,
+    ),
+    (
+        FEATURE_ESC_RESTORE,
+        |e| e.esc_restore_enabled,
+        Engine::set_esc_restore,
+    ),
+    (
+        FEATURE_FREE_TONE,
+        |e| e.free_tone_enabled,
+        Engine::set_free_tone,
+    ),
+    (
+        FEATURE_MODERN_TONE,
+        |e| e.modern_tone,
+        Engine::set_modern_tone,
+    ),
+    (
+        FEATURE_ENGLISH_AUTO_RESTORE,
+        |e| e.english_auto_restore,
+        Engine::set_english_auto_restore,
+    ),
+    (
+        FEATURE_AUTO_CAPITALIZE,
+        |e| e.auto_capitalize,
+        Engine::set_auto_capitalize,
+    ),
+    (FEATURE_CODE_SPAN, |e| e.code_span, Engine::set_code_span),
+    (
+        FEATURE_DIFF_OUTPUT,
+        |e| e.diff_output,
+        Engine::set_diff_output,
+    ),
+    (
+        FEATURE_FULL_REPLACE,
+        |e| e.full_replace,
+        Engine::set_full_replace,
+    ),
+    (
+        FEATURE_DEFERRED_EMIT,
+        |e| e.deferred_emit,
+        Engine::set_deferred_emit,
+    ),
+    (
+        FEATURE_APOSTROPHE_IN_WORD,
+        |e| e.apostrophe_in_word,
+        Engine::set_apostrophe_in_word,
+    ),
+    (
+        FEATURE_HYPHEN_IN_WORD,
+        |e| e.hyphen_in_word,
+        Engine::set_hyphen_in_word,
+    ),
+    (
+        FEATURE_REVERT_INVALID,
+        |e| e.revert_invalid,
+        Engine::set_revert_invalid,
+    ),
+    (
+        FEATURE_SMART_BACKSPACE,
+        |e| e.smart_backspace,
+        Engine::set_smart_backspace,
+    ),
+    (
+        FEATURE_BACKSPACE_RESTORE,
+        |e| e.backspace_restore,
+        Engine::set_backspace_restore,
+    ),
+    (
+        FEATURE_STRICT_FINALS,
+        |e| e.strict_finals,
+        Engine::set_strict_finals,
+    ),
+    (
+        FEATURE_DEFER_BREVE,
+        |e| e.defer_breve,
+        Engine::set_defer_breve,
+    ),
+    (
+        FEATURE_BIDI_ISOLATION,
+        |e| e.bidi_isolation,
+        Engine::set_bidi_isolation,
+    ),
+    (
+        FEATURE_EAGER_UO_HORN,
+        |e| e.eager_uo_horn,
+        Engine::set_eager_uo_horn,
+    ),
+    (
+        FEATURE_SHORTCUT_EXACT_BOUNDARY,
+        |e| e.shortcut_exact_boundary,
+        Engine::set_shortcut_require_exact_boundary,
+    ),
+    (
+        FEATURE_SIMPLE_MODE,
+        |e| e.simple_mode,
+        Engine::set_simple_mode,
+    ),
+    (FEATURE_NFD_OUTPUT, |e| e.nfd_output, Engine::set_nfd_output),
+    (FEATURE_NO_ECHO, |e| e.no_echo, Engine::set_no_echo),
+    (FEATURE_VNI_NUMPAD, |e| e.vni_numpad, Engine::set_vni_numpad),
+];
+
+/// Left-to-right isolate, opens a bidi-isolated committed word
+pub const BIDI_LRI: char = '\u{2066}';
+/// Pop directional isolate, closes a bidi-isolated committed word
+pub const BIDI_PDI: char = '\u{2069}';
+
+/// Strong right-to-left letter (Hebrew, Arabic, Syriac, Thaana, N'Ko and their
+/// presentation forms): text the host reports around a committed word
+fn is_rtl_char(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+}
+
+/// Output longer than `MAX` is cut to the first `MAX` chars (default)
+pub const OVERFLOW_TRUNCATE: u8 = 0;
+/// Output longer than `MAX` is split; the rest is queued for `Engine::next_result`
+pub const OVERFLOW_SPLIT: u8 = 1;
+/// Output longer than `MAX` is dropped and the result carries `FLAG_OVERFLOW`
+pub const OVERFLOW_ERROR: u8 = 2;
+
 impl Result {
     pub fn none() -> Self {
         Self {
@@ -60,6 +307,7 @@ impl Result {
             backspace: 0,
             count: 0,
             flags: 0,
+            tone_index: NO_TONE_INDEX,
         }
     }
 
@@ -70,6 +318,7 @@ impl Result {
             backspace,
             count: chars.len().min(MAX) as u8,
             flags: 0,
+            tone_index: NO_TONE_INDEX,
         };
         for (i, &c) in chars.iter().take(MAX).enumerate() {
             result.chars[i] = c as u32;
@@ -77,6 +326,14 @@ impl Result {
         result
     }
 
+    /// Replace with raw keystrokes: same contract as `send`, but tells the host the
+    /// word was restored (ESC, auto-restore) rather than transformed
+    pub fn restore(backspace: u8, chars: &[char]) -> Self {
+        let mut result = Self::send(backspace, chars);
+        result.action = Action::Restore as u8;
+        result
+    }
+
     /// Send with key_consumed flag set (shortcut consumed the trigger key)
     pub fn send_consumed(backspace: u8, chars: &[char]) -> Self {
         let mut result = Self::send(backspace, chars);
@@ -88,6 +345,208 @@ impl Result {
     pub fn key_consumed(&self) -> bool {
         self.flags & FLAG_KEY_CONSUMED != 0
     }
+
+    /// Check if the host should swallow the physical key
+    ///
+    /// True when the engine produced an edit or consumed the key. A break key
+    /// (punctuation, not space) that isn't consumed must then be typed by the
+    /// host after the edit: "tẽt" + ',' → "text" + ','.
+    pub fn should_suppress_key(&self) -> bool {
+        self.action != Action::None as u8 || self.key_consumed()
+    }
+
+    /// Check if output was dropped for not fitting (`OVERFLOW_ERROR`)
+    pub fn overflowed(&self) -> bool {
+        self.flags & FLAG_OVERFLOW != 0
+    }
+
+    /// Check if uppercase was forced by auto-capitalize
+    pub fn auto_capitalized(&self) -> bool {
+        self.flags & FLAG_AUTO_CAPITALIZED != 0
+    }
+
+    /// Check if a modifier key was rejected as invalid Vietnamese
+    pub fn rejected(&self) -> bool {
+        self.flags & FLAG_REJECTED != 0
+    }
+
+    /// Check if the key typed past a complete shortcut trigger (declining it)
+    pub fn shortcut_cancelled(&self) -> bool {
+        self.flags & FLAG_SHORTCUT_CANCELLED != 0
+    }
+
+    /// Check if the word may still grow into a shortcut trigger
+    pub fn shortcut_pending(&self) -> bool {
+        self.flags & FLAG_SHORTCUT_PENDING != 0
+    }
+
+    /// Check if the key dropped the composing word (no further edits to it)
+    pub fn composition_reset(&self) -> bool {
+        self.flags & FLAG_COMPOSITION_RESET != 0
+    }
+
+    /// Point `tone_index` at buffer position `pos` of a result rebuilt from `from`
+    fn with_tone_index(mut self, pos: usize, from: usize) -> Self {
+        self.tone_index = match pos.checked_sub(from) {
+            Some(i) if i < self.count as usize => i as u8,
+            _ => NO_TONE_INDEX,
+        };
+        self
+    }
+
+    /// Skip the leading chars that already match what's on screen
+    ///
+    /// `displayed` is the text before the cursor this result edits. Chars at the
+    /// start of the replacement that equal the deleted text are kept on screen,
+    /// reducing both backspace and output count.
+    /// Example: screen "việt", replace 4 with "vieejt" → replace 2 with "eejt"
+    fn trim_unchanged_prefix(&mut self, displayed: &[char]) {
+        let backspace = self.backspace as usize;
+        if self.action == Action::None as u8 || backspace == 0 || backspace > displayed.len() {
+            return;
+        }
+
+        let deleted = &displayed[displayed.len() - backspace..];
+        let count = self.count as usize;
+        let common = deleted
+            .iter()
+            .zip(&self.chars[..count])
+            .take_while(|(&d, &c)| d as u32 == c)
+            .count();
+        if common == 0 {
+            return;
+        }
+
+        self.chars.copy_within(common..count, 0);
+        for c in &mut self.chars[count - common..count] {
+            *c = 0;
+        }
+        self.backspace -= common as u8;
+        self.count -= common as u8;
+        if self.tone_index != NO_TONE_INDEX {
+            self.tone_index = self
+                .tone_index
+                .checked_sub(common as u8)
+                .unwrap_or(NO_TONE_INDEX);
+        }
+    }
+
+    /// Grow the edit to replace the whole word on screen
+    ///
+    /// `displayed` is the text before the cursor this result edits. The kept start
+    /// of the word is deleted and re-sent, so the host replaces the full word.
+    /// Example: screen "viet", replace 2 with "ệt" → replace 4 with "việt"
+    /// Left unchanged if the word wouldn't fit in `chars`.
+    fn widen_to_full_word(&mut self, displayed: &[char]) {
+        let backspace = self.backspace as usize;
+        if self.action == Action::None as u8 || backspace > displayed.len() {
+            return;
+        }
+
+        let kept = &displayed[..displayed.len() - backspace];
+        let count = self.count as usize;
+        if kept.is_empty() || kept.len() + count > MAX {
+            return;
+        }
+
+        self.chars.copy_within(0..count, kept.len());
+        for (slot, &c) in self.chars.iter_mut().zip(kept) {
+            *slot = c as u32;
+        }
+        self.backspace += kept.len() as u8;
+        self.count += kept.len() as u8;
+        if self.tone_index != NO_TONE_INDEX {
+            self.tone_index += kept.len() as u8;
+        }
+    }
+
+    /// Rewrite the edit in NFD (decomposed) form
+    ///
+    /// `displayed` is the NFC text before the cursor this result edits. The host
+    /// shows it decomposed too, so `backspace` becomes the number of codepoints
+    /// in the deleted chars; anything deleted before the word (a space) counts one.
+    /// Example: screen "việt", replace 2 "ệt" with "ết" → replace 4 with e+^+´+t
+    /// Left in NFC if the decomposed edit wouldn't fit in `chars`.
+    fn decompose(&mut self, displayed: &[char]) {
+        if self.action == Action::None as u8 {
+            return;
+        }
+
+        let mut output = Vec::with_capacity(MAX);
+        let mut tone_index = NO_TONE_INDEX;
+        for (i, &c) in self.chars[..self.count as usize].iter().enumerate() {
+            if i == self.tone_index as usize {
+                tone_index = output.len().min(u8::MAX as usize) as u8;
+            }
+            match char::from_u32(c) {
+                Some(ch) => chars::decompose(ch, &mut output),
+                None => continue,
+            }
+        }
+        if output.len() > MAX {
+            return;
+        }
+
+        let backspace = self.backspace as usize;
+        let deleted = &displayed[displayed.len().saturating_sub(backspace)..];
+        let mut scratch = Vec::with_capacity(3);
+        let mut codepoints = backspace - deleted.len();
+        for &c in deleted {
+            scratch.clear();
+            chars::decompose(c, &mut scratch);
+            codepoints += scratch.len();
+        }
+
+        let decomposed = Result::send(0, &output);
+        self.chars = decomposed.chars;
+        self.count = decomposed.count;
+        self.backspace = codepoints.min(u8::MAX as usize) as u8;
+        self.tone_index = tone_index;
+    }
+}
+
+/// Result for FFI with UTF-16 output (Windows, JVM, .NET hosts)
+///
+/// Same fields as `Result`; `chars` holds UTF-16 code units, with surrogate
+/// pairs for chars outside the BMP, so it's twice as long.
+#[repr(C)]
+pub struct ResultU16 {
+    pub chars: [u16; MAX * 2],
+    pub action: u8,
+    /// Number of characters (not code units) to delete
+    pub backspace: u8,
+    /// Number of valid code units in `chars`
+    pub count: u8,
+    /// Same bits as `Result::flags`
+    pub flags: u8,
+    /// Code unit index into `chars` of the changed vowel, or `NO_TONE_INDEX`
+    pub tone_index: u8,
+}
+
+impl From<&Result> for ResultU16 {
+    fn from(r: &Result) -> Self {
+        let mut out = Self {
+            chars: [0; MAX * 2],
+            action: r.action,
+            backspace: r.backspace,
+            count: 0,
+            flags: r.flags,
+            tone_index: NO_TONE_INDEX,
+        };
+        let mut len = 0;
+        for (i, c) in r.chars[..r.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .enumerate()
+        {
+            if i == r.tone_index as usize {
+                out.tone_index = len as u8;
+            }
+            len += c.encode_utf16(&mut out.chars[len..]).len();
+        }
+        out.count = len as u8;
+        out
+    }
 }
 
 /// Transform type for revert tracking
@@ -117,6 +576,9 @@ struct WordHistory {
     data: [Buffer; HISTORY_CAPACITY],
     head: usize,
     len: usize,
+    /// Number of most recent entries separated by exactly one space
+    /// (for multi-word shortcut triggers like "on the fly")
+    chain: usize,
 }
 
 impl WordHistory {
@@ -125,6 +587,7 @@ impl WordHistory {
             data: std::array::from_fn(|_| Buffer::new()),
             head: 0,
             len: 0,
+            chain: 0,
         }
     }
 
@@ -135,6 +598,14 @@ impl WordHistory {
         if self.len < HISTORY_CAPACITY {
             self.len += 1;
         }
+        self.chain = 1;
+    }
+
+    /// Push a word typed one space after the previous entry, extending the chain
+    fn push_joined(&mut self, buf: Buffer) {
+        let chain = self.chain;
+        self.push(buf);
+        self.chain = (chain + 1).min(self.len);
     }
 
     /// Pop most recent buffer from history
@@ -144,24 +615,38 @@ impl WordHistory {
         }
         self.head = (self.head + HISTORY_CAPACITY - 1) % HISTORY_CAPACITY;
         self.len -= 1;
+        self.chain = self.chain.saturating_sub(1);
         Some(self.data[self.head].clone())
     }
 
+    /// Get the n-th most recent buffer (0 = last pushed) without popping
+    fn recent(&self, n: usize) -> Option<&Buffer> {
+        if n >= self.len {
+            return None;
+        }
+        Some(&self.data[(self.head + HISTORY_CAPACITY - 1 - n) % HISTORY_CAPACITY])
+    }
+
     fn clear(&mut self) {
         self.len = 0;
         self.head = 0;
+        self.chain = 0;
     }
 }
 
 /// Check if key is sentence-ending punctuation (triggers auto-capitalize)
-/// Triggers: . ! ? Enter
+/// `triggers` is a set of `CAPITALIZE_*` bits (default: . ! ? Enter)
 #[inline]
-fn is_sentence_ending(key: u16, shift: bool) -> bool {
-    key == keys::RETURN
-        || key == keys::ENTER
-        || key == keys::DOT
-        || (shift && key == keys::N1) // !
-        || (shift && key == keys::SLASH) // ?
+fn is_sentence_ending(key: u16, shift: bool, triggers: u8) -> bool {
+    let bit = match key {
+        keys::RETURN | keys::ENTER => CAPITALIZE_ENTER,
+        keys::DOT => CAPITALIZE_PERIOD,
+        keys::N1 if shift => CAPITALIZE_EXCLAIM,
+        keys::SLASH if shift => CAPITALIZE_QUESTION,
+        keys::SEMICOLON if shift => CAPITALIZE_COLON,
+        _ => return false,
+    };
+    triggers & bit != 0
 }
 
 /// Check if a break key should reset pending_capitalize
@@ -191,6 +676,16 @@ fn should_reset_pending_capitalize(key: u16, shift: bool) -> bool {
     !is_neutral
 }
 
+/// Character a break key stands for in `ShortcutTable` terminators
+/// Enter → '\n', Tab → '\t', punctuation via `break_key_to_char`
+fn terminator_char(key: u16, shift: bool) -> Option<char> {
+    match key {
+        keys::RETURN | keys::ENTER => Some('\n'),
+        keys::TAB => Some('\t'),
+        _ => break_key_to_char(key, shift),
+    }
+}
+
 /// Convert break key to its character representation
 /// Handles both shifted and unshifted break characters for shortcut matching.
 /// Examples: MINUS → '-', Shift+DOT → '>', Shift+MINUS → '_'
@@ -240,10 +735,61 @@ fn break_key_to_char(key: u16, shift: bool) -> Option<char> {
     }
 }
 
+/// Combine `first` with `next` (the result for `key`) as if the host applied both
+///
+/// Used when one key event does two things, e.g. a T9 commit followed by the
+/// key that triggered it. A passthrough `next` becomes explicit output, since
+/// the host blocks letters and backspace once `first` is a Send.
+fn chain_results(first: Result, next: Result, key: u16, caps: bool, shift: bool) -> Result {
+    if first.action == Action::None as u8 {
+        return next;
+    }
+
+    let mut screen: Vec<char> = first.chars[..first.count as usize]
+        .iter()
+        .filter_map(|&c| char::from_u32(c))
+        .collect();
+    let mut backspace = first.backspace;
+    let (next_backspace, next_chars, flags) = if next.action != Action::None as u8 {
+        let chars = next.chars[..next.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        (next.backspace, chars, next.flags)
+    } else if key == keys::SPACE {
+        // A Send on space carries the space itself
+        (0, vec![' '], 0)
+    } else if keys::is_break_ext(key, shift) {
+        // Host still types a break key after a Send
+        let mut result = first;
+        result.flags &= !FLAG_KEY_CONSUMED;
+        return result;
+    } else if key == keys::DELETE {
+        (1, Vec::new(), 0)
+    } else {
+        match utils::key_to_char_ext(key, caps, shift) {
+            Some(ch) => (0, vec![ch], 0),
+            None => return first,
+        }
+    };
+
+    for _ in 0..next_backspace {
+        if screen.pop().is_none() {
+            backspace = backspace.saturating_add(1);
+        }
+    }
+    screen.extend(next_chars);
+    let mut result = Result::send(backspace, &screen);
+    result.flags = flags;
+    result
+}
+
 /// Main Vietnamese IME engine
 pub struct Engine {
     buf: Buffer,
     method: u8,
+    /// VNI: keypad digits act like top-row digits (tone/mark keys)
+    vni_numpad: bool,
     enabled: bool,
     last_transform: Option<Transform>,
     shortcuts: ShortcutTable,
@@ -255,20 +801,32 @@ pub struct Engine {
     /// Skip w→ư shortcut in Telex mode (user preference)
     /// When true, typing 'w' at word start stays as 'w' instead of converting to 'ư'
     skip_w_shortcut: bool,
+    /// Initials allowed before a standalone 'w' (Cw → Cư), matched on the first letter
+    /// None = validation decides (default)
+    w_valid_initials: Option<Vec<u16>>,
+    /// Initials after which Telex "oo" stays literal ("coo" → "coo", not "cô")
+    /// Matched on the first letter; empty = always circumflex (default)
+    double_o_literal_initials: Vec<u16>,
     /// Enable ESC key to restore raw ASCII (undo Vietnamese transforms)
     /// When false, ESC key is passed through without restoration
     esc_restore_enabled: bool,
     /// Enable free tone placement (skip validation)
     /// When true, allows placing diacritics anywhere without spelling validation
     free_tone_enabled: bool,
-    /// Use modern orthography for tone placement (hoà vs hòa)
-    /// When true: oà, uý (tone on second vowel)
-    /// When false: òa, úy (tone on first vowel - traditional)
+    /// Use modern orthography for tone placement on uy (thuý vs thúy)
+    /// When true: uý (tone on second vowel)
+    /// When false: úy (tone on first vowel - traditional)
     modern_tone: bool,
+    /// Tone placement for open oa/oe syllables (hoà vs hòa)
+    /// 0 = traditional (òa, tone on first vowel), 1 = modern (oà, tone on second vowel)
+    /// Closed syllables (toàn) stay rule-driven
+    oa_oe_style: u8,
     /// Enable English auto-restore (experimental)
     /// When true, automatically restores English words that were transformed
     /// e.g., "tẽt" → "text", "ễpct" → "expect"
     english_auto_restore: bool,
+    /// Words with fewer keystrokes than this never auto-restore (0 = no minimum)
+    min_restore_len: usize,
     /// Word history for backspace-after-space feature
     word_history: WordHistory,
     /// Number of spaces typed after committing a word (for backspace tracking)
@@ -287,6 +845,10 @@ pub struct Engine {
     /// When true, subsequent 'd' keys are treated as normal letters, not stroke triggers
     /// This prevents "ddddd" from oscillating between đ and dd states
     stroke_reverted: bool,
+    /// Key whose transform was just reverted ("aaa" → "aa"), while it keeps repeating
+    /// The key stays literal until another key breaks the run, so "aaaaa" doesn't
+    /// oscillate between â and aa
+    reverted_run_key: Option<u16>,
     /// Tracks if a mark was reverted in current word
     /// Used by auto-restore to detect words like "issue", "bass" that need restoration
     had_mark_revert: bool,
@@ -308,17 +870,129 @@ pub struct Engine {
     /// so shortcuts like "#fne" can match even though # is normally a break char
     /// Extended: Now accumulates multiple break chars for shortcuts like "->" → "→"
     shortcut_prefix: String,
+    /// The current word was typed past a complete word shortcut trigger
+    shortcut_declined: bool,
+    /// Once declined, the word never expands, even if backspaced to the trigger
+    shortcut_exact_boundary: bool,
     /// Buffer was just restored from DELETE - clear on next letter input
     /// This prevents typing after restore from appending to old buffer
     restored_pending_clear: bool,
     /// Auto-capitalize first letter after sentence-ending punctuation
     /// Triggers: . ! ? Enter → next letter becomes uppercase
     auto_capitalize: bool,
+    /// Which punctuation triggers auto-capitalize (`CAPITALIZE_*` bits)
+    capitalize_triggers: u8,
     /// Pending capitalize state: set after sentence-ending punctuation
     pending_capitalize: bool,
     /// Tracks if auto-capitalize was just used on the current word
     /// Used to restore pending_capitalize when user deletes the capitalized letter
     auto_capitalize_used: bool,
+    /// Keycode layout of incoming keys (keys::LAYOUT_*)
+    layout: u8,
+    /// Words that never get Vietnamese transforms (brand names, technical terms)
+    /// Stored lowercase. While raw input is a prefix of one, letters stay raw
+    protected_words: HashSet<String>,
+    /// Idle time (ms) after which `tick` commits the current word (0 = disabled)
+    idle_commit_ms: u32,
+    /// Time (ms) accumulated by `tick` since the last key event
+    idle_ms: u32,
+    /// Backtick toggles a code span (markdown `code`) where transforms are off
+    code_span: bool,
+    /// Currently inside a backtick code span (transforms suppressed, shortcuts still work)
+    in_code_span: bool,
+    /// Minimize backspaces by keeping the unchanged start of each replacement on screen
+    diff_output: bool,
+    /// Every edit replaces the whole composing word (takes precedence over diff_output)
+    full_replace: bool,
+    /// Keys of a word pass through as typed; the composed word replaces them in
+    /// one edit when the word ends (takes precedence over full_replace)
+    deferred_emit: bool,
+    /// Keys typed (and shown raw) in the current word under deferred_emit
+    deferred_keys: Vec<(u16, bool, bool)>,
+    /// Apostrophe between letters stays in the word ("don't") instead of breaking it
+    apostrophe_in_word: bool,
+    /// Hyphen between letters keeps the word open ("việt-trường", "well-known")
+    hyphen_in_word: bool,
+    /// Earlier parts of the current hyphenated word, hyphens included: text as it
+    /// should end up (English parts restored), displayed length, and whether any
+    /// part still needs its restore
+    hyphen_text: Vec<char>,
+    hyphen_shown: usize,
+    hyphen_restore: bool,
+    /// The stem before an in-word apostrophe had transforms that auto-restore
+    /// would keep ("đi" in "đi's"), so a clitic after it doesn't restore the word
+    apostrophe_stem_kept: bool,
+    /// What a Ctrl/Cmd chord does to the current word: 0=clear, 1=ignore (keep composing)
+    ctrl_behavior: u8,
+    /// Revert to raw keystrokes on word end when the buffer is structurally
+    /// invalid Vietnamese (no English detection, unlike english_auto_restore)
+    revert_invalid: bool,
+    /// What ESC does: 0=restore raw (if esc_restore enabled), 1=commit and keep, 2=clear only
+    esc_behavior: u8,
+    /// Backspace peels one diacritic layer before deleting the letter (ế → ê → e)
+    smart_backspace: bool,
+    /// Backspacing the spaces after a word reopens it for editing
+    backspace_restore: bool,
+    /// Spelling of i/y variant words on commit: 0=as typed, 1=prefer y, 2=prefer i
+    iy_preference: u8,
+    /// Minimal predictable Telex: a placed tone mark stays put and "did" stays
+    /// "did" (see `set_simple_mode` for the flags it also turns off)
+    simple_mode: bool,
+    /// Output decomposed (NFD) text; backspace counts decomposed codepoints
+    nfd_output: bool,
+    /// Field doesn't echo input (password): every key passes through literally
+    no_echo: bool,
+    /// Host hook run on the chars of every Send result (see `set_output_filter`)
+    output_filter: Option<fn(&mut Vec<char>)>,
+    /// Current key was a modifier that failed validation (set by try_*, read in process)
+    rejected: bool,
+    /// One-shot: keep the current word raw (no transforms) until the next word
+    raw_current_word: bool,
+    /// T9 keypad front-end over Telex (method 4): digits 2-9 cycle letters
+    t9: bool,
+    /// Keypad letter being selected (shown on screen, not yet in the buffer)
+    t9_cycle: Option<t9::Cycle>,
+    /// Time (ms) after which a pending keypad letter commits on its own
+    t9_timeout_ms: u32,
+    /// Time (ms) accumulated by `tick` since the last keypad press
+    t9_idle_ms: u32,
+    /// Drop a letter/number repeating the previous key within this time (0 = off)
+    /// Only applies to `on_key_timed`
+    repeat_filter_ms: u32,
+    /// A mark key repeated within this time keeps the mark instead of reverting it
+    /// (0 = off). Only applies to `on_key_timed`
+    mark_revert_gap_ms: u32,
+    /// Previous key and host timestamp (ms) seen by `on_key_timed`
+    last_timed_key: Option<(u16, u32)>,
+    /// Edit (backspace count, inserted chars) of the last `on_key_ext` result
+    last_edit: (u8, Vec<char>),
+    /// Restore raw as soon as a consonant makes an invalid final ("việt" + b → "vieetb"),
+    /// instead of waiting for the word boundary
+    strict_finals: bool,
+    /// Defer breve in open syllables until a final is typed (Issue #44):
+    /// "aw" stays "aw", "trawm" → "trăm"
+    defer_breve: bool,
+    /// Wrap words committed by space in U+2066/U+2069 for right-to-left contexts
+    bidi_isolation: bool,
+    /// Last strong text before the current word is right-to-left, so a committed
+    /// word crosses a script boundary (set by `on_char`/`set_cursor_context`)
+    rtl_context: bool,
+    /// What to do with shortcut output longer than `MAX` (OVERFLOW_*)
+    overflow_mode: u8,
+    /// Rest of a split output, drained by `next_result`
+    overflow_queue: VecDeque<Result>,
+    /// Shortcut output was dropped during this key (`OVERFLOW_ERROR`)
+    overflowed: bool,
+    /// Composing word was dropped during this key (`FLAG_COMPOSITION_RESET`)
+    composition_reset: bool,
+    /// Decisions recorded since the last `take_trace`
+    #[cfg(feature = "trace")]
+    trace: Vec<String>,
+    /// Horn on both 'u' and 'o' at once for "uo" + w ("huow" → "hươ"),
+    /// instead of deferring the 'u' horn until a final (Issue #133)
+    eager_uo_horn: bool,
+    /// Last buffer validation, shared by the checks of one keystroke
+    validation_cache: ValidationCache,
 }
 
 impl Default for Engine {
@@ -332,35 +1006,123 @@ impl Engine {
         Self {
             buf: Buffer::new(),
             method: 0,
+            vni_numpad: false, // Default: OFF (keypad stays numeric entry)
             enabled: true,
             last_transform: None,
             shortcuts: ShortcutTable::with_defaults(),
             raw_input: Vec::with_capacity(64),
             has_non_letter_prefix: false,
             skip_w_shortcut: false,
+            w_valid_initials: None, // Default: validation-driven
+            double_o_literal_initials: Vec::new(), // Default: "oo" → "ô" everywhere
             esc_restore_enabled: false, // Default: OFF (user request)
             free_tone_enabled: false,
-            modern_tone: true,           // Default: modern style (hoà, thuý)
+            modern_tone: true,           // Default: modern style (thuý)
+            oa_oe_style: 1,              // Default: modern style (hoà)
             english_auto_restore: false, // Default: OFF (experimental feature)
+            min_restore_len: 0,          // Default: no minimum
             word_history: WordHistory::new(),
             spaces_after_commit: 0,
             pending_breve_pos: None,
             pending_u_horn_pos: None,
             stroke_reverted: false,
+            reverted_run_key: None,
             had_mark_revert: false,
             pending_mark_revert_pop: false,
             had_any_transform: false,
             had_vowel_triggered_circumflex: false,
             shortcut_prefix: String::new(),
+            shortcut_declined: false,
+            shortcut_exact_boundary: false, // Default: "vnx<" + space still expands
             restored_pending_clear: false,
             auto_capitalize: false, // Default: OFF
+            capitalize_triggers: CAPITALIZE_DEFAULT,
             pending_capitalize: false,
             auto_capitalize_used: false,
+            layout: keys::LAYOUT_MACOS, // Default: macOS virtual keycodes
+            protected_words: HashSet::new(),
+            idle_commit_ms: 0, // Default: OFF
+            idle_ms: 0,
+            code_span: false, // Default: OFF
+            in_code_span: false,
+            diff_output: false,   // Default: OFF
+            full_replace: false,  // Default: OFF (incremental edits)
+            deferred_emit: false, // Default: OFF (edit as you type)
+            deferred_keys: Vec::new(),
+            apostrophe_in_word: false, // Default: OFF
+            hyphen_in_word: false,     // Default: OFF (hyphen breaks the word)
+            hyphen_text: Vec::new(),
+            hyphen_shown: 0,
+            hyphen_restore: false,
+            apostrophe_stem_kept: false,
+            ctrl_behavior: 0,        // Default: clear
+            revert_invalid: false,   // Default: OFF
+            esc_behavior: 0,         // Default: restore
+            smart_backspace: false,  // Default: OFF
+            backspace_restore: true, // Default: ON
+            iy_preference: 0,        // Default: as typed
+            simple_mode: false,      // Default: OFF
+            nfd_output: false,       // Default: OFF (precomposed NFC)
+            no_echo: false,          // Default: OFF
+            output_filter: None,
+            rejected: false,
+            raw_current_word: false,
+            t9: false,
+            t9_cycle: None,
+            t9_timeout_ms: t9::DEFAULT_TIMEOUT_MS,
+            t9_idle_ms: 0,
+            repeat_filter_ms: 0,   // Default: OFF
+            mark_revert_gap_ms: 0, // Default: OFF (any repeat reverts)
+            last_timed_key: None,
+            last_edit: (0, Vec::new()),
+            strict_finals: false,  // Default: OFF (checked at word boundary)
+            defer_breve: false,    // Default: immediate breve ("aw" → "ă")
+            bidi_isolation: false, // Default: OFF
+            rtl_context: false,
+            overflow_mode: OVERFLOW_TRUNCATE,
+            overflow_queue: VecDeque::new(),
+            overflowed: false,
+            composition_reset: false,
+            #[cfg(feature = "trace")]
+            trace: Vec::new(),
+            eager_uo_horn: false, // Default: deferred (huơ)
+            validation_cache: ValidationCache::default(),
         }
     }
 
+    /// Set input method (0=Telex, 1=VNI, 4=T9 keypad over Telex)
     pub fn set_method(&mut self, method: u8) {
-        self.method = method;
+        self.t9 = method == t9::METHOD_ID;
+        self.t9_cycle = None;
+        self.method = if self.t9 { 0 } else { method };
+    }
+
+    /// Set whether VNI reads tone/mark digits from the numeric keypad too
+    ///
+    /// Off by default so keypad number entry never transforms a word.
+    pub fn set_vni_numpad(&mut self, enabled: bool) {
+        self.vni_numpad = enabled;
+    }
+
+    /// Get current input method (0=Telex, 1=VNI, 4=T9)
+    pub fn method(&self) -> u8 {
+        if self.t9 {
+            t9::METHOD_ID
+        } else {
+            self.method
+        }
+    }
+
+    /// Get the modifier keys of the active method, as codes of the active layout
+    ///
+    /// T9 reports the Telex keys its keypad letters feed into.
+    pub fn modifier_keys(&self) -> ModifierKeys {
+        ModifierKeys::of(input::get(self.method)).to_layout(self.layout)
+    }
+
+    /// Set how long (ms) a T9 keypad letter stays selectable before `tick` commits it
+    pub fn set_t9_timeout_ms(&mut self, ms: u32) {
+        self.t9_timeout_ms = ms;
     }
 
     pub fn set_enabled(&mut self, enabled: bool) {
@@ -377,6 +1139,64 @@ impl Engine {
         self.skip_w_shortcut = skip;
     }
 
+    /// Set which initial consonants allow a standalone 'w' to become ư ("Cw" → "Cư")
+    pub fn set_w_valid_initials(&mut self, initials: &[u16]) {
+        self.w_valid_initials = Some(initials.to_vec());
+    }
+
+    /// Go back to the validation-driven initial set for standalone 'w'
+    pub fn reset_w_valid_initials(&mut self) {
+        self.w_valid_initials = None;
+    }
+
+    /// Set which initial consonants keep Telex "oo" literal (loanwords like "cool")
+    pub fn set_double_o_literal_initials(&mut self, initials: &[u16]) {
+        self.double_o_literal_initials = initials.to_vec();
+    }
+
+    /// Set whether breve waits for a final consonant in open syllables ("aw" stays "aw")
+    ///
+    /// Off by default: breve applies immediately ("raw" → "ră"), as it always has.
+    pub fn set_defer_breve(&mut self, enabled: bool) {
+        self.defer_breve = enabled;
+    }
+
+    /// Set whether words committed by space are wrapped in bidi isolates
+    ///
+    /// For hosts editing right-to-left text (Arabic, Hebrew): a word committed
+    /// after right-to-left text ("سلام việt" + space) is replaced by U+2066 "việt"
+    /// U+2069 " ". Words after left-to-right text are left alone. The preceding
+    /// script comes from RTL chars typed through `on_char` or the `before` text
+    /// given to `set_cursor_context`. The two markers are real codepoints
+    /// on screen that the engine doesn't track, so each isolated word takes 2 more
+    /// backspaces to delete and can't be reopened by backspace-after-space.
+    pub fn set_bidi_isolation(&mut self, enabled: bool) {
+        self.bidi_isolation = enabled;
+    }
+
+    /// Set whether an invalid final restores raw immediately while typing
+    pub fn set_strict_finals(&mut self, enabled: bool) {
+        self.strict_finals = enabled;
+    }
+
+    /// Set what happens to shortcut output longer than one result (OVERFLOW_*)
+    ///
+    /// With `OVERFLOW_SPLIT`, the key's result holds the first `MAX` chars and
+    /// the host drains the rest with `next_result`.
+    pub fn set_overflow_mode(&mut self, mode: u8) {
+        self.overflow_mode = mode;
+        self.shortcuts
+            .set_truncate_output(mode == OVERFLOW_TRUNCATE);
+        self.overflow_queue.clear();
+    }
+
+    /// Next queued chunk of a split output (`OVERFLOW_SPLIT`), in order
+    ///
+    /// Each chunk is a Send with 0 backspace. The queue is dropped on the next key.
+    pub fn next_result(&mut self) -> Option<Result> {
+        self.overflow_queue.pop_front()
+    }
+
     /// Set whether ESC key restores raw ASCII
     pub fn set_esc_restore(&mut self, enabled: bool) {
         self.esc_restore_enabled = enabled;
@@ -387,9 +1207,15 @@ impl Engine {
         self.free_tone_enabled = enabled;
     }
 
-    /// Set whether to use modern orthography for tone placement
+    /// Set whether to use modern orthography for tone placement (oa/oe and uy)
     pub fn set_modern_tone(&mut self, modern: bool) {
         self.modern_tone = modern;
+        self.oa_oe_style = modern as u8;
+    }
+
+    /// Set tone placement for open oa/oe only (0=traditional, 1=modern)
+    pub fn set_oa_oe_style(&mut self, style: u8) {
+        self.oa_oe_style = style;
     }
 
     /// Set whether to enable English auto-restore (experimental)
@@ -397,6 +1223,14 @@ impl Engine {
         self.english_auto_restore = enabled;
     }
 
+    /// Set the minimum word length (keystrokes) for English auto-restore
+    ///
+    /// Shorter words keep their Vietnamese form: with 4, "sax" stays "sã" while
+    /// "user" still restores. 0 (default) restores any length.
+    pub fn set_min_restore_len(&mut self, len: usize) {
+        self.min_restore_len = len;
+    }
+
     /// Set whether to enable auto-capitalize after sentence-ending punctuation
     pub fn set_auto_capitalize(&mut self, enabled: bool) {
         self.auto_capitalize = enabled;
@@ -405,6 +1239,238 @@ impl Engine {
         }
     }
 
+    /// Set which punctuation triggers auto-capitalize (`CAPITALIZE_*` bits)
+    pub fn set_capitalize_triggers(&mut self, triggers: u8) {
+        self.capitalize_triggers = triggers;
+    }
+
+    /// Set whether a word typed past a shortcut trigger stays unexpanded even
+    /// after backspacing to the trigger ("vnx" + backspace + space → "vn ")
+    pub fn set_shortcut_require_exact_boundary(&mut self, enabled: bool) {
+        self.shortcut_exact_boundary = enabled;
+    }
+
+    /// Set keycode layout of incoming keys (0=macOS, 1=Windows, 2=Linux)
+    pub fn set_layout(&mut self, layout: u8) {
+        self.layout = layout;
+    }
+
+    /// Add a word that should never be transformed (case-insensitive)
+    pub fn add_protected_word(&mut self, word: &str) {
+        let word = word.trim().to_lowercase();
+        if !word.is_empty() {
+            self.protected_words.insert(word);
+        }
+    }
+
+    /// Remove all protected words
+    pub fn clear_protected_words(&mut self) {
+        self.protected_words.clear();
+    }
+
+    /// Check if `raw` keystrokes are a prefix of a protected word
+    fn is_protected_prefix(&self, raw: &[(u16, bool, bool)]) -> bool {
+        if self.protected_words.is_empty() || raw.is_empty() {
+            return false;
+        }
+        let typed: String = raw
+            .iter()
+            .filter_map(|&(k, _, _)| utils::key_to_char(k, false))
+            .collect();
+        self.protected_words.iter().any(|w| w.starts_with(&typed))
+    }
+
+    /// Replay the word (current key included) with transforms once its raw
+    /// input stops being a protected prefix
+    ///
+    /// Everything before the key is on screen as typed, so the result replaces
+    /// it with the recomposed word; `none` when recomposing changes nothing.
+    fn recompose_diverged_word(&mut self) -> Result {
+        let displayed = self.buf.len();
+        let typed = std::mem::take(&mut self.raw_input);
+        let raw: String = typed
+            .iter()
+            .filter_map(|&(k, c, s)| utils::key_to_char_ext(k, c, s))
+            .collect();
+        self.buf.clear();
+        self.last_transform = None;
+
+        let protected = std::mem::take(&mut self.protected_words);
+        for &(k, c, s) in &typed {
+            self.handle_key(k, c, false, s);
+        }
+        self.protected_words = protected;
+
+        let word: Vec<char> = self.buf.to_full_string().chars().collect();
+        if word.iter().collect::<String>() == raw {
+            return Result::none();
+        }
+        Result::send(displayed as u8, &word)
+    }
+
+    /// Set whether backtick toggles a code span with Vietnamese transforms off
+    pub fn set_code_span(&mut self, enabled: bool) {
+        self.code_span = enabled;
+        if !enabled {
+            self.in_code_span = false;
+        }
+    }
+
+    /// Set whether to minimize backspaces (skip unchanged leading chars of replacements)
+    pub fn set_diff_output(&mut self, enabled: bool) {
+        self.diff_output = enabled;
+    }
+
+    /// Set whether every edit resends the whole composing word (for hosts that can't
+    /// apply partial edits reliably)
+    pub fn set_full_replace(&mut self, enabled: bool) {
+        self.full_replace = enabled;
+    }
+
+    /// Set whether output is decomposed (NFD): "ệ" is sent as e + U+0323 + U+0302
+    pub fn set_nfd_output(&mut self, enabled: bool) {
+        self.nfd_output = enabled;
+    }
+
+    /// Set no-echo mode for fields that don't show what's typed (password fields)
+    ///
+    /// The engine can't replace text it can't see, so every key passes through
+    /// as typed: no transforms, shortcuts or backspaces. The buffer still tracks
+    /// the literal word. Toggling starts a new word.
+    pub fn set_no_echo(&mut self, enabled: bool) {
+        self.no_echo = enabled;
+        self.clear();
+    }
+
+    /// Set whether output is held back until the word ends, then sent in one edit
+    /// (for hosts that can't handle backspacing mid-word)
+    pub fn set_deferred_emit(&mut self, enabled: bool) {
+        self.deferred_emit = enabled;
+        self.deferred_keys.clear();
+    }
+
+    /// Set whether structurally invalid Vietnamese reverts to raw keystrokes on word end
+    pub fn set_revert_invalid(&mut self, enabled: bool) {
+        self.revert_invalid = enabled;
+    }
+
+    /// Set what Ctrl/Cmd chords do to the current word (0=clear, 1=ignore)
+    pub fn set_ctrl_behavior(&mut self, mode: u8) {
+        self.ctrl_behavior = mode;
+    }
+
+    /// Set what ESC does (0=restore if esc_restore enabled, 1=commit and keep, 2=clear only)
+    pub fn set_esc_behavior(&mut self, mode: u8) {
+        self.esc_behavior = mode;
+    }
+
+    /// Set whether backspace strips mark, then tone, then the letter (UniKey style)
+    pub fn set_smart_backspace(&mut self, enabled: bool) {
+        self.smart_backspace = enabled;
+    }
+
+    /// Set whether backspacing the spaces after a word reopens it for editing
+    pub fn set_backspace_restore(&mut self, enabled: bool) {
+        self.backspace_restore = enabled;
+    }
+
+    /// Set i/y spelling for variant words like "lí/lý" on commit
+    /// (0=as typed, 1=prefer y, 2=prefer i)
+    pub fn set_iy_preference(&mut self, mode: u8) {
+        self.iy_preference = mode;
+    }
+
+    /// Set simple mode: a minimal, predictable Telex for beginners
+    ///
+    /// Turning it on disables:
+    /// - breve deferral (`set_defer_breve(false)`): "aw" → "ă" right away
+    /// - English auto-restore (`set_english_auto_restore(false)`)
+    /// - moving a placed tone mark when later letters change the vowel group
+    ///   ("muas" + 'n' stays "múan" instead of "muán")
+    /// - short-pattern stroke: "did" stays "did" (type "ddi" for "đi")
+    ///
+    /// Turning it off restores the last two; the flags stay off until set again.
+    pub fn set_simple_mode(&mut self, enabled: bool) {
+        self.simple_mode = enabled;
+        if enabled {
+            self.set_defer_breve(false);
+            self.set_english_auto_restore(false);
+        }
+    }
+
+    /// On/off features as a `FEATURE_*` bitmask
+    pub fn feature_flags(&self) -> u32 {
+        FEATURES
+            .iter()
+            .filter(|(_, get, _)| get(self))
+            .fold(0, |flags, (bit, _, _)| flags | bit)
+    }
+
+    /// Turn every `FEATURE_*` feature on or off from a bitmask (unknown bits ignored)
+    ///
+    /// Only features whose bit changes go through their setter, so side effects
+    /// like `set_modern_tone` resetting the oa/oe style don't hit the others.
+    pub fn set_feature_flags(&mut self, flags: u32) {
+        for &(bit, get, set) in FEATURES {
+            let on = flags & bit != 0;
+            if get(self) != on {
+                set(self, on);
+            }
+        }
+    }
+
+    /// Set a last-chance filter on the chars of every Send result (None removes it)
+    ///
+    /// Runs in `on_key_ext`/`on_char` after all other output options, so hosts can
+    /// normalize or block text without forking. Restores (raw keystrokes) are left
+    /// alone. The engine keeps tracking the unfiltered word: a filter that changes
+    /// the char count mid-word throws off the backspace count of the next edit,
+    /// so keep the length, or only change results that end a word (trailing space).
+    pub fn set_output_filter(&mut self, filter: Option<fn(&mut Vec<char>)>) {
+        self.output_filter = filter;
+    }
+
+    /// Set whether an apostrophe between letters keeps the word together ("don't")
+    pub fn set_apostrophe_in_word(&mut self, enabled: bool) {
+        self.apostrophe_in_word = enabled;
+    }
+
+    /// Set whether a hyphen between letters keeps the word open ("việt-trường")
+    pub fn set_hyphen_in_word(&mut self, enabled: bool) {
+        self.hyphen_in_word = enabled;
+    }
+
+    /// Set idle time (ms) after which `tick` commits the current word (0 = disabled)
+    pub fn set_idle_commit_ms(&mut self, ms: u32) {
+        self.idle_commit_ms = ms;
+        self.idle_ms = 0;
+    }
+
+    /// Set whether "uo" + horn marks both vowels immediately ("huow" → "hươ")
+    pub fn set_eager_uo_horn(&mut self, enabled: bool) {
+        self.eager_uo_horn = enabled;
+    }
+
+    /// Set time (ms) within which `on_key_timed` drops a repeated key (0 = disabled)
+    pub fn set_repeat_filter_ms(&mut self, ms: u32) {
+        self.repeat_filter_ms = ms;
+    }
+
+    /// Set time (ms) within which `on_key_timed` ignores a repeated mark key
+    /// instead of reverting the mark (0 = disabled)
+    pub fn set_mark_revert_gap_ms(&mut self, ms: u32) {
+        self.mark_revert_gap_ms = ms;
+    }
+
+    /// Set whether Vietnamese symbol shortcuts are enabled ("(dong)" → "₫", "(do)" → "°")
+    pub fn set_symbol_shortcuts(&mut self, enabled: bool) {
+        if enabled {
+            self.shortcuts.add_symbol_defaults();
+        } else {
+            self.shortcuts.remove_symbol_defaults();
+        }
+    }
+
     pub fn shortcuts(&self) -> &ShortcutTable {
         &self.shortcuts
     }
@@ -455,24 +1521,343 @@ impl Engine {
     /// Handle key event with extended parameters
     ///
     /// # Arguments
-    /// * `key` - macOS virtual keycode
+    /// * `key` - keycode in the active layout (macOS by default, see `set_layout`)
     /// * `caps` - true if Caps Lock is active (for uppercase letters)
     /// * `ctrl` - true if Cmd/Ctrl/Alt is pressed (bypasses IME)
     /// * `shift` - true if Shift key is pressed (for symbols like @, #, $)
     pub fn on_key_ext(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        // Translate platform keycode to internal (macOS) keycode
+        let key = keys::translate(self.layout, key);
+        self.on_translated_key(key, caps, ctrl, shift)
+    }
+
+    /// Handle a portable `keyboard_types::Code` key event
+    ///
+    /// The code is mapped with `keys::from_key_code`, which already yields internal
+    /// keycodes, so the active layout is not used (unlike passing that result to
+    /// `on_key`, which only works with `LAYOUT_MACOS`). Unmapped codes act as
+    /// unknown keys.
+    #[cfg(feature = "keyboard-types")]
+    pub fn on_key_code(
+        &mut self,
+        code: keyboard_types::Code,
+        caps: bool,
+        ctrl: bool,
+        shift: bool,
+    ) -> Result {
+        let key = keys::from_key_code(code).unwrap_or(keys::UNKNOWN);
+        self.on_translated_key(key, caps, ctrl, shift)
+    }
+
+    /// Shared tail of `on_key_ext` and `on_key_code` once `key` is internal
+    fn on_translated_key(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        trace!(self, "key {key} caps={caps} ctrl={ctrl} shift={shift}");
+        // VNI numpad: keypad digits compose exactly like the top row
+        let key = match keys::numpad_to_digit(key) {
+            Some(digit) if self.vni_numpad && self.method == 1 && !self.t9 => digit,
+            _ => key,
+        };
+        self.process_key(key, caps, ctrl, shift)
+    }
+
+    /// Handle key event with a host timestamp, filtering held-key repeats
+    ///
+    /// With `set_repeat_filter_ms` > 0, a letter or number that repeats the previous
+    /// key within the threshold is dropped (consumed, nothing sent), so a briefly
+    /// held 'a' doesn't become "â". Other keys (e.g., held backspace) always go through.
+    ///
+    /// With `set_mark_revert_gap_ms` > 0, a mark key typed again within the gap is
+    /// an accidental double tap: it is consumed and the mark stays ("cas" + 's'
+    /// quickly → "cá", not "cas"). Past the gap it reverts as usual.
+    /// `timestamp_ms` may wrap around.
+    pub fn on_key_timed(
+        &mut self,
+        key: u16,
+        caps: bool,
+        ctrl: bool,
+        shift: bool,
+        timestamp_ms: u32,
+    ) -> Result {
+        let last = self.last_timed_key.replace((key, timestamp_ms));
+        if let (Some((last_key, last_ms)), false) = (last, ctrl) {
+            let internal = keys::translate(self.layout, key);
+            let gap = timestamp_ms.wrapping_sub(last_ms);
+            let composes = keys::is_letter(internal) || keys::is_number(internal);
+            if composes && last_key == key && gap < self.repeat_filter_ms {
+                return Result::send_consumed(0, &[]);
+            }
+            // Double-tapped mark key: keep the mark the first tap applied
+            let mark_repeat =
+                matches!(self.last_transform, Some(Transform::Mark(k, _)) if k == internal);
+            if mark_repeat && last_key == key && gap < self.mark_revert_gap_ms {
+                return Result::send_consumed(0, &[]);
+            }
+        }
+        self.on_key_ext(key, caps, ctrl, shift)
+    }
+
+    /// Handle a typed character instead of a keycode
+    ///
+    /// For hosts without a keycode layer (the active layout is not used).
+    /// Uppercase letters imply `caps` and shifted symbols ('@', ':') imply `shift`.
+    /// Characters with no key (e.g., already-composed "ệ") act as unknown keys.
+    pub fn on_char(&mut self, ch: char, caps: bool, ctrl: bool, shift: bool) -> Result {
+        let key = keys::from_char(ch).unwrap_or(keys::UNKNOWN);
+        let caps = caps || ch.is_uppercase();
+        let shift = shift || keys::is_shifted_char(ch);
+        let result = self.process_key(key, caps, ctrl, shift);
+        if is_rtl_char(ch) {
+            self.rtl_context = true;
+        }
+        result
+    }
+
+    /// Run one internal key with the per-key bookkeeping shared by `on_key_ext`
+    /// and `on_char`: a fresh overflow queue, one-shot result flags, output filter
+    /// and the `last_edit` record
+    fn process_key(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        self.overflow_queue.clear();
+        let mut result = self.on_internal_key(key, caps, ctrl, shift);
+        if std::mem::take(&mut self.overflowed) {
+            result.flags |= FLAG_OVERFLOW;
+        }
+        if std::mem::take(&mut self.composition_reset) {
+            result.flags |= FLAG_COMPOSITION_RESET;
+        }
+        self.filter_output(&mut result);
+        self.last_edit = if result.action == Action::None as u8 {
+            (0, Vec::new())
+        } else {
+            let inserted = result.chars[..result.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c))
+                .collect();
+            (result.backspace, inserted)
+        };
+        result
+    }
+
+    /// Run the host output filter on a Send result
+    fn filter_output(&self, result: &mut Result) {
+        let Some(filter) = self.output_filter else {
+            return;
+        };
+        if result.action != Action::Send as u8 {
+            return;
+        }
+        let mut chars: Vec<char> = result.chars[..result.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        filter(&mut chars);
+        let filtered = Result::send(result.backspace, &chars);
+        result.chars = filtered.chars;
+        result.count = filtered.count;
+        if result.tone_index >= result.count {
+            result.tone_index = NO_TONE_INDEX;
+        }
+    }
+
+    /// Handle key event with internal (macOS) keycode, applying output options
+    fn on_internal_key(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        if self.t9 && self.enabled && !ctrl {
+            return self.handle_t9_key(key, caps, shift);
+        }
+        if self.no_echo && self.enabled {
+            // Keep tracking the word, but never edit the screen
+            self.handle_key(key, caps, ctrl, shift);
+            return Result::none();
+        }
+        if self.deferred_emit && self.enabled {
+            return self.handle_key_deferred(key, caps, ctrl, shift);
+        }
+        if !self.diff_output && !self.full_replace && !self.nfd_output {
+            return self.handle_key(key, caps, ctrl, shift);
+        }
+
+        // The buffer before this key is what's on screen for the current word
+        let displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let mut result = self.handle_key(key, caps, ctrl, shift);
+        if self.full_replace {
+            result.widen_to_full_word(&displayed);
+        } else if self.diff_output {
+            result.trim_unchanged_prefix(&displayed);
+        }
+        self.nfd_edit(result, &displayed)
+    }
+
+    /// Rewrite an edit of `displayed` (the word on screen before it) in NFD when
+    /// `nfd_output` is on; shared by the key path and the word-editing APIs
+    fn nfd_edit(&self, mut result: Result, displayed: &[char]) -> Result {
+        if self.nfd_output {
+            result.decompose(displayed);
+        }
+        result
+    }
+
+    /// Handle a key with deferred emit
+    ///
+    /// Keys that continue the word pass through, so the screen shows them raw
+    /// ("vieetj"). When the word ends, one edit deletes them and sends the final
+    /// text ("việt "). Backspace drops the last key and replays the rest.
+    fn handle_key_deferred(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        let mut typed = std::mem::take(&mut self.deferred_keys);
+
+        if key == keys::DELETE && !ctrl && typed.pop().is_some() {
+            self.clear();
+            for &(k, c, s) in &typed {
+                self.handle_key(k, c, false, s);
+            }
+            self.deferred_keys = typed;
+            return Result::none();
+        }
+
+        // Word on screen wasn't typed in this mode (backspace-after-space): edit directly
+        if typed.is_empty() && !self.buf.is_empty() {
+            return self.handle_key(key, caps, ctrl, shift);
+        }
+
+        let typed_char = utils::key_to_char_ext(key, caps, shift);
+        let word_key = !ctrl && typed_char.is_some() && !keys::is_break_ext(key, shift);
+        let displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let mut result = self.handle_key(key, caps, ctrl, shift);
+
+        if word_key && !result.key_consumed() && !self.buf.is_empty() {
+            typed.push((key, caps, shift));
+            self.deferred_keys = typed;
+            return Result::none();
+        }
+        if typed.is_empty() {
+            return result;
+        }
+
+        // Word ended: replace the raw keys on screen with the final text
+        if result.action == Action::None as u8 {
+            let raw: Vec<char> = typed
+                .iter()
+                .filter_map(|&(k, c, s)| utils::key_to_char_ext(k, c, s))
+                .collect();
+            if raw == displayed {
+                return result;
+            }
+            let mut output = displayed.clone();
+            // The key itself is now part of the edit, the host won't type it
+            if key == keys::SPACE {
+                output.push(' ');
+            } else if word_key {
+                output.extend(typed_char);
+            }
+            result = Result::send(0, &output);
+            result.backspace = typed.len().min(u8::MAX as usize) as u8;
+            return result;
+        }
+        result.widen_to_full_word(&displayed);
+        let backspace = result.backspace as usize;
+        if backspace >= displayed.len() {
+            result.backspace =
+                (backspace - displayed.len() + typed.len()).min(u8::MAX as usize) as u8;
+        }
+        result
+    }
+
+    /// Handle a key in T9 mode
+    ///
+    /// Keypad digits select a letter (shown on screen right away); any other key,
+    /// or a different digit, first types the pending letter as Telex.
+    fn handle_t9_key(&mut self, key: u16, caps: bool, shift: bool) -> Result {
+        if !shift {
+            // Same digit again: swap the shown letter for the next one in the group
+            if let Some(cycle) = self.t9_cycle.as_mut() {
+                if cycle.digit == key {
+                    cycle.advance();
+                    self.t9_idle_ms = 0;
+                    let ch: Vec<char> = utils::key_to_char(cycle.key(), cycle.caps)
+                        .into_iter()
+                        .collect();
+                    return Result::send(1, &ch);
+                }
+            }
+
+            if let Some(cycle) = t9::Cycle::new(key, caps) {
+                let committed = self.commit_t9();
+                self.t9_cycle = Some(cycle);
+                self.t9_idle_ms = 0;
+                let ch: Vec<char> = utils::key_to_char(cycle.key(), caps).into_iter().collect();
+                return chain_results(committed, Result::send(0, &ch), key, caps, shift);
+            }
+        }
+
+        // Backspace while selecting drops the letter: the host deletes it on screen
+        if key == keys::DELETE && self.t9_cycle.take().is_some() {
+            return Result::none();
+        }
+
+        let committed = self.commit_t9();
+        let result = self.handle_key(key, caps, false, shift);
+        chain_results(committed, result, key, caps, shift)
+    }
+
+    /// Type the pending T9 letter into the engine
+    ///
+    /// The letter is already on screen, so a Send also deletes it.
+    fn commit_t9(&mut self) -> Result {
+        let Some(cycle) = self.t9_cycle.take() else {
+            return Result::none();
+        };
+        let mut result = self.handle_key(cycle.key(), cycle.caps, false, false);
+        if result.action != Action::None as u8 {
+            result.backspace = result.backspace.saturating_add(1);
+        }
+        result
+    }
+
+    /// Handle key event with internal (macOS) keycode
+    fn handle_key(&mut self, key: u16, caps: bool, ctrl: bool, shift: bool) -> Result {
+        // Any key resets the idle timer used by tick()
+        self.idle_ms = 0;
+
         // Issue #129: Process shortcuts even when IME is disabled
         // Only bypass completely for Ctrl/Cmd modifier keys
         if ctrl {
+            // Ignore mode: chords like Cmd+Shift+Left pass through, word stays composable
+            if self.ctrl_behavior == 1 {
+                return Result::none();
+            }
+            self.composition_reset = !self.buf.is_empty();
             self.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
             return Result::none();
         }
 
-        // When IME is disabled, process shortcuts but skip Vietnamese transforms
+        // Code span: backtick toggles markdown `code` mode (transforms off inside)
+        // The backtick itself never joins shortcut_prefix, so "`btw" still matches "btw"
+        if self.code_span && key == keys::BACKQUOTE && !shift {
+            let result = if self.in_code_span {
+                Result::none()
+            } else {
+                // Entering: finish current word like any break key
+                self.try_auto_restore_on_break()
+            };
+            self.in_code_span = !self.in_code_span;
+            self.auto_capitalize_used = false;
+            self.clear();
+            self.word_history.clear();
+            self.spaces_after_commit = 0;
+            return result;
+        }
+
+        // Newline and ESC always leave an unterminated code span
+        if self.in_code_span && matches!(key, keys::RETURN | keys::ENTER | keys::ESC) {
+            self.in_code_span = false;
+        }
+
+        // When IME is disabled (or inside a code span), process shortcuts but skip
+        // Vietnamese transforms
         // This allows both word shortcuts (btw → by the way) and symbol shortcuts (-> → →)
-        if !self.enabled {
+        if !self.enabled || self.in_code_span {
             // Clear Vietnamese state
+            self.composition_reset = !self.buf.is_empty();
             self.buf.clear();
             self.raw_input.clear();
             self.word_history.clear();
@@ -495,9 +1880,9 @@ impl Engine {
                         if key == keys::SPACE {
                             let mut output_with_space = output;
                             output_with_space.push(' ');
-                            return Result::send(backspace_count, &output_with_space);
+                            return self.expansion(backspace_count, &output_with_space, false);
                         } else {
-                            return Result::send(backspace_count, &output);
+                            return self.expansion(backspace_count, &output, false);
                         }
                     }
                 }
@@ -520,7 +1905,7 @@ impl Engine {
                         let output: Vec<char> = m.output.chars().collect();
                         let backspace_count = (m.backspace_count as u8).saturating_sub(1);
                         self.shortcut_prefix.clear();
-                        return Result::send_consumed(backspace_count, &output);
+                        return self.expansion(backspace_count, &output, true);
                     }
                     return Result::none();
                 }
@@ -532,6 +1917,18 @@ impl Engine {
             // Letter and number keys: accumulate for word shortcuts (e.g., "btw", "f1", "a1")
             if let Some(ch) = utils::key_to_char(key, caps) {
                 self.shortcut_prefix.push(ch);
+
+                // Instant shortcuts fire without waiting for a boundary
+                let input_method = self.current_input_method();
+                if let Some(m) = self
+                    .shortcuts
+                    .try_match_instant(&self.shortcut_prefix, input_method)
+                {
+                    let output: Vec<char> = m.output.chars().collect();
+                    let backspace_count = (m.backspace_count as u8).saturating_sub(1);
+                    self.shortcut_prefix.clear();
+                    return self.expansion(backspace_count, &output, true);
+                }
                 return Result::none();
             }
 
@@ -540,6 +1937,29 @@ impl Engine {
             return Result::none();
         }
 
+        // Apostrophe after a letter ("don't", "isn't"): keep composing the same word
+        // so auto-restore sees it whole. A leading apostrophe still breaks as usual.
+        if self.apostrophe_in_word
+            && key == keys::QUOTE
+            && !shift
+            && self.buf.last().is_some_and(|c| keys::is_letter(c.key))
+        {
+            self.apostrophe_stem_kept =
+                self.had_any_transform && self.should_auto_restore(true).is_none();
+            self.buf.push(Char::new(keys::QUOTE, false));
+            self.raw_input.push((keys::QUOTE, false, false));
+            self.last_transform = None;
+            return Result::none();
+        }
+
+        // Trailing apostrophe ("việt'" + space): it was a break after all
+        if self.buf.last().is_some_and(|c| c.key == keys::QUOTE)
+            && !keys::is_letter(key)
+            && key != keys::DELETE
+        {
+            return self.finish_trailing_apostrophe(key, caps, shift);
+        }
+
         // Check for word boundary shortcuts ONLY on SPACE
         // Also auto-restore invalid Vietnamese to raw English
         if key == keys::SPACE {
@@ -560,7 +1980,11 @@ impl Engine {
             }
 
             // First check for shortcut
-            let shortcut_result = self.try_word_boundary_shortcut();
+            let shortcut_result = if self.shortcuts.is_terminator(' ') {
+                self.try_word_boundary_shortcut(Some(' '))
+            } else {
+                Result::none()
+            };
             if shortcut_result.action != 0 {
                 self.clear();
                 return shortcut_result;
@@ -569,6 +1993,14 @@ impl Engine {
             // Auto-restore: if buffer has transforms but is invalid Vietnamese,
             // restore to raw English (like ESC but triggered by space)
             let restore_result = self.try_auto_restore_on_space();
+            if restore_result.action != 0 {
+                trace!(self, "auto-restore on space");
+            }
+            let restore_result = self.finish_hyphen_compound(restore_result, true);
+            let restore_result = match restore_result.action {
+                0 => self.apply_iy_preference(true).unwrap_or(restore_result),
+                _ => restore_result,
+            };
 
             // If auto-restore happened, repopulate buffer with plain chars from raw_input
             // This ensures word_history stores the correct restored word (not transformed)
@@ -581,9 +2013,26 @@ impl Engine {
                 }
             }
 
+            // Bidi isolation: re-emit the word wrapped in LRI/PDI so it stays LTR
+            // inside right-to-left text. The markers aren't tracked afterwards,
+            // so the word can't be reopened by backspace-after-space
+            if self.bidi_isolation && self.rtl_context && !self.buf.is_empty() {
+                let result = self.isolate_committed_word(restore_result);
+                self.word_history.clear();
+                self.spaces_after_commit = 0;
+                self.auto_capitalize_used = false;
+                self.clear();
+                return result;
+            }
+
             // Push buffer to history before clearing (for backspace-after-space feature)
             if !self.buf.is_empty() {
-                self.word_history.push(self.buf.clone());
+                if self.spaces_after_commit == 1 {
+                    // One space after the previous word: keep the multi-word chain
+                    self.word_history.push_joined(self.buf.clone());
+                } else {
+                    self.word_history.push(self.buf.clone());
+                }
                 self.spaces_after_commit = 1; // First space after word
             } else if self.spaces_after_commit > 0 {
                 // Additional space after commit - increment counter
@@ -597,7 +2046,17 @@ impl Engine {
         // ESC key: restore to raw ASCII (undo all Vietnamese transforms)
         // Only if esc_restore is enabled by user
         if key == keys::ESC {
-            let result = if self.esc_restore_enabled {
+            // Commit mode: keep the Vietnamese word as typed; history stays intact
+            // so backspace-after-space still works ("du " + ESC + backspace → "du")
+            if self.esc_behavior == 1 {
+                if !self.buf.is_empty() {
+                    self.word_history.push(self.buf.clone());
+                    self.spaces_after_commit = 0;
+                }
+                self.clear();
+                return Result::none();
+            }
+            let result = if self.esc_behavior == 0 && self.esc_restore_enabled {
                 self.restore_to_raw()
             } else {
                 Result::none()
@@ -612,6 +2071,77 @@ impl Engine {
         // Also trigger auto-restore for invalid Vietnamese before clearing
         // Use is_break_ext to handle shifted symbols like @, !, #, etc.
         if keys::is_break_ext(key, shift) {
+            // Hyphen between letters: keep the word open, compose the next part fresh
+            if key == keys::MINUS
+                && !shift
+                && self.hyphen_in_word
+                && !self.buf.is_empty()
+                && !self.has_non_letter_prefix
+            {
+                self.continue_hyphen_compound();
+                return Result::none();
+            }
+
+            // Break right after a hyphen ("well-,"): the hyphen was trailing
+            if self.buf.is_empty() && self.hyphen_shown > 0 {
+                let result = self.finish_hyphen_compound(Result::none(), false);
+                self.reset_hyphen_compound();
+                if result.action != 0 {
+                    self.auto_capitalize_used = false;
+                    self.clear();
+                    self.word_history.clear();
+                    self.spaces_after_commit = 0;
+                    if let Some(ch) = break_key_to_char(key, shift) {
+                        self.shortcut_prefix.push(ch);
+                    }
+                    return result;
+                }
+            }
+
+            // Immediate shortcuts ending with a break char: "(dong)" → "₫"
+            // The closing char completes a trigger spanning prefix + word + break char
+            if !self.buf.is_empty() && !self.has_non_letter_prefix {
+                if let Some(ch) = break_key_to_char(key, shift) {
+                    let trigger = format!(
+                        "{}{}{}",
+                        self.shortcut_prefix,
+                        self.buf.to_full_string(),
+                        ch
+                    );
+                    let input_method = self.current_input_method();
+                    if let Some(m) =
+                        self.shortcuts
+                            .try_match_for_method(&trigger, None, false, input_method)
+                    {
+                        // backspace_count - 1 because current key hasn't been typed yet
+                        let output: Vec<char> = m.output.chars().collect();
+                        let backspace_count = (m.backspace_count as u8).saturating_sub(1);
+                        self.clear();
+                        self.word_history.clear();
+                        self.spaces_after_commit = 0;
+                        return self.expansion(backspace_count, &output, true);
+                    }
+                }
+            }
+
+            // Word shortcuts on configured terminators other than space ("vn" + Enter)
+            if let Some(ch) = terminator_char(key, shift) {
+                if self.shortcuts.is_terminator(ch) {
+                    let result = self.try_word_boundary_shortcut(None);
+                    if result.action != 0 {
+                        if self.auto_capitalize
+                            && is_sentence_ending(key, shift, self.capitalize_triggers)
+                        {
+                            self.pending_capitalize = true;
+                        }
+                        self.clear();
+                        self.word_history.clear();
+                        self.spaces_after_commit = 0;
+                        return result;
+                    }
+                }
+            }
+
             // Issue #107 + Bug #11: When buffer is empty AND we're at true start of input
             // (no word history), accumulate break chars for shortcuts.
             // This allows shortcuts like "#fne", "->", "=>" to work.
@@ -648,11 +2178,13 @@ impl Engine {
                         let output: Vec<char> = m.output.chars().collect();
                         let backspace_count = (m.backspace_count as u8).saturating_sub(1);
                         self.shortcut_prefix.clear();
-                        return Result::send_consumed(backspace_count, &output);
+                        return self.expansion(backspace_count, &output, true);
                     }
 
                     // Auto-capitalize: set pending if sentence-ending (! or ?)
-                    if self.auto_capitalize && is_sentence_ending(key, shift) {
+                    if self.auto_capitalize
+                        && is_sentence_ending(key, shift, self.capitalize_triggers)
+                    {
                         self.pending_capitalize = true;
                     }
                     return Result::none(); // Let the char pass through, keep accumulating
@@ -660,7 +2192,7 @@ impl Engine {
             }
 
             // Auto-capitalize: set pending if sentence-ending punctuation
-            if self.auto_capitalize && is_sentence_ending(key, shift) {
+            if self.auto_capitalize && is_sentence_ending(key, shift, self.capitalize_triggers) {
                 self.pending_capitalize = true;
             } else if self.auto_capitalize && should_reset_pending_capitalize(key, shift) {
                 // Reset pending for word-breaking keys (comma, semicolon, etc.)
@@ -670,6 +2202,15 @@ impl Engine {
             self.auto_capitalize_used = false; // Reset on word boundary
 
             let restore_result = self.try_auto_restore_on_break();
+            if restore_result.action != 0 {
+                trace!(self, "auto-restore on break");
+            }
+            let restore_result = self.finish_hyphen_compound(restore_result, false);
+            let restore_result = match restore_result.action {
+                0 => self.apply_iy_preference(false).unwrap_or(restore_result),
+                _ => restore_result,
+            };
+            self.composition_reset = !self.buf.is_empty();
             self.clear();
             self.word_history.clear();
             self.spaces_after_commit = 0;
@@ -687,7 +2228,7 @@ impl Engine {
         if key == keys::DELETE {
             // Backspace-after-space feature: restore previous word when all spaces deleted
             // Track spaces typed after commit, restore word when counter reaches 0
-            if self.spaces_after_commit > 0 && self.buf.is_empty() {
+            if self.backspace_restore && self.spaces_after_commit > 0 && self.buf.is_empty() {
                 self.spaces_after_commit -= 1;
                 if self.spaces_after_commit == 0 {
                     // All spaces deleted - restore the word buffer
@@ -707,11 +2248,20 @@ impl Engine {
             // User might delete all new input and want to restore previous word.
             // Reset only happens on: break keys, ESC, ctrl, or new commit.
 
+            // Smart backspace: strip one diacritic layer from the last char first
+            if self.smart_backspace {
+                if let Some(result) = self.try_smart_backspace() {
+                    return result;
+                }
+            }
+
             // If buffer is already empty, user is deleting content from previous word
             // that we don't track. Mark this to prevent false shortcut matches.
             // e.g., "đa" + SPACE + backspace×2 + "a" should NOT match shortcut "a"
             if self.buf.is_empty() {
                 self.has_non_letter_prefix = true;
+                // Deleting the hyphen leaves the earlier parts untracked
+                self.reset_hyphen_compound();
             }
             self.buf.pop();
             self.raw_input.pop();
@@ -719,6 +2269,7 @@ impl Engine {
             // Reset stroke_reverted on backspace so user can re-trigger stroke
             // e.g., "ddddd" → "dddd", then backspace×3 → "d", then "d" → "đ"
             self.stroke_reverted = false;
+            self.reverted_run_key = None;
             // Only reset restored_pending_clear when buffer is empty
             // (user finished deleting restored word completely)
             // If buffer still has chars, user might think they cleared everything
@@ -771,22 +2322,103 @@ impl Engine {
             self.raw_input.push((key, effective_caps, shift));
         }
 
-        let result = self.process(key, effective_caps, shift);
+        // Chars on screen for this word before the key (for instant shortcuts)
+        let screen_len = self.shortcut_prefix.chars().count() + self.buf.len();
+        let was_trigger =
+            (keys::is_letter(key) || keys::is_number(key)) && self.is_word_shortcut_trigger();
+
+        let mut result = self.process(key, effective_caps, shift);
+
+        // Typing past a complete trigger declines it: "vn" + 'x' → "vnx"
+        if was_trigger && !self.is_word_shortcut_trigger() {
+            self.shortcut_declined = true;
+            result.flags |= FLAG_SHORTCUT_CANCELLED;
+        }
+
+        // Instant shortcuts fire as soon as the trigger is complete: "teh" → "the"
+        if keys::is_letter(key) || keys::is_number(key) {
+            if let Some(r) = self.try_instant_shortcut(screen_len) {
+                return r;
+            }
+        }
 
-        // If auto-capitalize triggered for first letter of a new word and process returned none,
-        // we need to send the uppercase character since the original key was lowercase
-        if was_auto_capitalized && result.action == Action::None as u8 && self.buf.len() == 1 {
-            if let Some(ch) = crate::utils::key_to_char(key, true) {
-                return Result::send(0, &[ch]);
+        if was_auto_capitalized {
+            // If auto-capitalize triggered for first letter of a new word and process returned none,
+            // we need to send the uppercase character since the original key was lowercase
+            if result.action == Action::None as u8 && self.buf.len() == 1 {
+                if let Some(ch) = crate::utils::key_to_char(key, true) {
+                    result = Result::send(0, &[ch]);
+                }
             }
+            // Let host know uppercase was forced (e.g., to offer "undo autocapitalize")
+            result.flags |= FLAG_AUTO_CAPITALIZED;
+        }
+
+        if self.is_shortcut_pending() {
+            result.flags |= FLAG_SHORTCUT_PENDING;
         }
 
         result
     }
 
+    /// Check if the current word (with shortcut prefix) is a complete word-boundary trigger
+    fn is_word_shortcut_trigger(&self) -> bool {
+        let len = self.shortcut_prefix.chars().count() + self.buf.len();
+        if len == 0 || len > self.shortcuts.max_trigger_len() || self.has_non_letter_prefix {
+            return false;
+        }
+        let word = format!("{}{}", self.shortcut_prefix, self.buf.to_full_string());
+        self.shortcuts
+            .lookup_for_method(&word, self.current_input_method())
+            .is_some_and(|(_, s)| s.condition == TriggerCondition::OnWordBoundary)
+    }
+
+    /// Check if the current word (with shortcut prefix) is a proper prefix of a trigger
+    fn is_shortcut_pending(&self) -> bool {
+        let len = self.shortcut_prefix.chars().count() + self.buf.len();
+        if len == 0 || len >= self.shortcuts.max_trigger_len() || self.has_non_letter_prefix {
+            return false;
+        }
+        let word = format!("{}{}", self.shortcut_prefix, self.buf.to_full_string());
+        self.shortcuts.has_prefix(&word)
+    }
+
     /// Main processing pipeline - pattern-based
     fn process(&mut self, key: u16, caps: bool, shift: bool) -> Result {
         let m = input::get(self.method);
+        self.rejected = false;
+
+        // Any other key ends a reverted run (see `reverted_run_key`)
+        if self.reverted_run_key != Some(key) {
+            self.reverted_run_key = None;
+        }
+
+        // Forced raw word (or no-echo field), or letters after an in-word apostrophe
+        // ("đi's": the 's' must not become a mark across it): letters and numbers
+        // go in untouched
+        let after_apostrophe = self.buf.iter().any(|c| c.key == keys::QUOTE);
+        if (self.raw_current_word || self.no_echo || after_apostrophe)
+            && (keys::is_letter(key) || keys::is_number(key))
+        {
+            self.buf.push(Char::new(key, caps));
+            self.last_transform = None;
+            return Result::none();
+        }
+
+        // Protected words: while raw input is still a prefix of a protected word,
+        // keep letters raw (no marks, tones, stroke). Once the word diverges,
+        // the letters kept raw are recomposed ("DDoS" protected: "ddi" → "đi")
+        if keys::is_letter(key) || keys::is_number(key) {
+            if self.is_protected_prefix(&self.raw_input) {
+                self.buf.push(Char::new(key, caps));
+                self.last_transform = None;
+                return Result::none();
+            }
+            let typed_before = &self.raw_input[..self.raw_input.len().saturating_sub(1)];
+            if self.is_protected_prefix(typed_before) {
+                return self.recompose_diverged_word();
+            }
+        }
 
         // Handle pending mark revert pop: if previous key was a mark revert (like "ss"),
         // and THIS key is a consonant, pop the consumed modifier from raw_input.
@@ -812,6 +2444,21 @@ impl Engine {
             }
         }
 
+        // Fast path: a letter with no modifier role in this method and nothing pending
+        // can only be a normal letter, so skip the modifier scans below
+        if keys::is_letter(key)
+            && self.last_transform.is_none()
+            && self.pending_breve_pos.is_none()
+            && self.pending_u_horn_pos.is_none()
+            && !m.stroke(key)
+            && m.tone(key).is_none()
+            && m.mark(key).is_none()
+            && !m.remove(key)
+            && !(self.method == 0 && key == keys::W)
+        {
+            return self.handle_normal_letter(key, caps);
+        }
+
         // Revert short-pattern stroke when new letter creates invalid Vietnamese
         // This handles: "ded" → "đe" (stroke applied), then 'i' → "dedi" (invalid, revert)
         // IMPORTANT: This check must happen BEFORE any modifiers (tone, mark, etc.)
@@ -877,6 +2524,7 @@ impl Engine {
         // 1. Stroke modifier (d → đ)
         if !skip_vni_modifiers && m.stroke(key) {
             if let Some(result) = self.try_stroke(key) {
+                trace!(self, "try_stroke fired");
                 return result;
             }
         }
@@ -886,6 +2534,7 @@ impl Engine {
             if let Some(tone_type) = m.tone(key) {
                 let targets = m.tone_targets(key);
                 if let Some(result) = self.try_tone(key, caps, tone_type, targets) {
+                    trace!(self, "try_tone fired ({tone_type:?})");
                     return result;
                 }
             }
@@ -895,6 +2544,7 @@ impl Engine {
         if !skip_vni_modifiers {
             if let Some(mark_val) = m.mark(key) {
                 if let Some(result) = self.try_mark(key, caps, mark_val) {
+                    trace!(self, "try_mark fired (mark {mark_val})");
                     return result;
                 }
             }
@@ -905,6 +2555,7 @@ impl Engine {
         // This allows shortcuts like "zz" to work when buffer has no marks/tones to remove
         if !skip_vni_modifiers && m.remove(key) {
             if let Some(result) = self.try_remove() {
+                trace!(self, "try_remove fired");
                 return result;
             }
         }
@@ -913,16 +2564,61 @@ impl Engine {
         // Examples: "w" → "ư", "nhw" → "như", but "kw" → "kw" (invalid)
         if self.method == 0 && key == keys::W {
             if let Some(result) = self.try_w_as_vowel(caps) {
+                trace!(self, "try_w_as_vowel fired");
                 return result;
             }
         }
 
         // Not a modifier - normal letter
-        self.handle_normal_letter(key, caps)
+        // (or a modifier that failed validation: let the host know it was rejected)
+        let mut result = self.handle_normal_letter(key, caps);
+        if self.rejected {
+            result.flags |= FLAG_REJECTED;
+        }
+        result
+    }
+
+    /// Wrap the word being committed by SPACE in bidi isolate markers
+    ///
+    /// Replaces the word on screen with LRI + word + PDI + space. `restore` is the
+    /// auto-restore result for this space, if any: its raw text is what gets wrapped.
+    fn isolate_committed_word(&self, restore: Result) -> Result {
+        let (backspace, word): (u8, Vec<char>) = if restore.action != Action::None as u8 {
+            let mut restored: Vec<char> = restore.chars[..restore.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c))
+                .collect();
+            // Only the space that triggered the commit goes after the PDI
+            if restored.last() == Some(&' ') {
+                restored.pop();
+            }
+            (restore.backspace, restored)
+        } else {
+            (
+                self.buf.len() as u8,
+                self.buf.to_full_string().chars().collect(),
+            )
+        };
+        // Markers and space must fit: too long a word is left unwrapped
+        if word.len() + 3 > MAX {
+            return restore;
+        }
+
+        let mut output = Vec::with_capacity(word.len() + 3);
+        output.push(BIDI_LRI);
+        output.extend(word);
+        output.push(BIDI_PDI);
+        output.push(' ');
+        let mut result = Result::send(backspace, &output);
+        result.action = restore.action.max(Action::Send as u8);
+        result
     }
 
     /// Try word boundary shortcuts (triggered by space, punctuation, etc.)
-    fn try_word_boundary_shortcut(&mut self) -> Result {
+    ///
+    /// `key_char` is appended to the output: `Some(' ')` for space, `None` for
+    /// other terminators, which the host types itself after the Send.
+    fn try_word_boundary_shortcut(&mut self, key_char: Option<char>) -> Result {
         // Issue #107: Allow shortcuts with special char prefix (like "#fne")
         // If shortcut_prefix is set, we still try to match even with empty buffer
         if self.buf.is_empty() && self.shortcut_prefix.is_empty() {
@@ -935,6 +2631,22 @@ impl Engine {
             return Result::none();
         }
 
+        // User typed past the trigger in this word ("vnx" + backspace)
+        if self.shortcut_exact_boundary && self.shortcut_declined {
+            return Result::none();
+        }
+
+        // Multi-word triggers ("on the fly"): previous words typed one space apart
+        if let Some(result) = self.try_multi_word_shortcut(key_char) {
+            return result;
+        }
+
+        // Word longer than any trigger can't match: skip building the trigger string
+        let trigger_len = self.shortcut_prefix.chars().count() + self.buf.len();
+        if trigger_len > self.shortcuts.max_trigger_len() {
+            return Result::none();
+        }
+
         // Build full trigger string including shortcut_prefix if present
         let full_trigger = if self.shortcut_prefix.is_empty() {
             self.buf.to_full_string()
@@ -947,16 +2659,100 @@ impl Engine {
         // Check for word boundary shortcut match
         if let Some(m) =
             self.shortcuts
-                .try_match_for_method(&full_trigger, Some(' '), true, input_method)
+                .try_match_for_method(&full_trigger, key_char, true, input_method)
         {
             let output: Vec<char> = m.output.chars().collect();
             // backspace_count = trigger.len() which already includes prefix (e.g., "#fne" = 4)
-            return Result::send(m.backspace_count as u8, &output);
+            return self.expansion(m.backspace_count as u8, &output, false);
         }
 
         Result::none()
     }
 
+    /// Try a multi-word trigger spanning committed words plus the current one
+    ///
+    /// Longest run first, so "on the fly" wins over "the fly". Backspace covers
+    /// every word of the trigger and the single spaces between them.
+    fn try_multi_word_shortcut(&mut self, key_char: Option<char>) -> Option<Result> {
+        if self.spaces_after_commit != 1 || !self.shortcut_prefix.is_empty() {
+            return None;
+        }
+
+        let max_len = self.shortcuts.max_trigger_len();
+        let mut trigger = self.buf.to_full_string();
+        let mut candidates = Vec::new();
+        for n in 0..self.word_history.chain {
+            let word = self.word_history.recent(n)?;
+            if trigger.chars().count() + 1 + word.len() > max_len {
+                break;
+            }
+            trigger = format!("{} {}", word.to_full_string(), trigger);
+            candidates.push(trigger.clone());
+        }
+
+        let input_method = self.current_input_method();
+        for trigger in candidates.iter().rev() {
+            if let Some(m) =
+                self.shortcuts
+                    .try_match_for_method(trigger, key_char, true, input_method)
+            {
+                // Earlier words are replaced on screen: drop them from history
+                self.word_history.clear();
+                self.spaces_after_commit = 0;
+                let output: Vec<char> = m.output.chars().collect();
+                return Some(self.expansion(m.backspace_count as u8, &output, false));
+            }
+        }
+        None
+    }
+
+    /// Try instant shortcut after a letter/number completes the trigger
+    ///
+    /// Only shortcuts opted in via `Shortcut::instant` match here, so a trigger
+    /// that is a prefix of a longer word never fires unless the user asked for it.
+    /// `screen_len` is the word length on screen before the current key.
+    fn try_instant_shortcut(&mut self, screen_len: usize) -> Option<Result> {
+        if self.buf.is_empty() || self.has_non_letter_prefix {
+            return None;
+        }
+        let trigger_len = self.shortcut_prefix.chars().count() + self.buf.len();
+        if trigger_len > self.shortcuts.max_trigger_len() {
+            return None;
+        }
+
+        let full_trigger = format!("{}{}", self.shortcut_prefix, self.buf.to_full_string());
+        let input_method = self.current_input_method();
+        let m = self
+            .shortcuts
+            .try_match_instant(&full_trigger, input_method)?;
+
+        let output: Vec<char> = m.output.chars().collect();
+        self.clear();
+        Some(self.expansion(screen_len as u8, &output, true))
+    }
+
+    /// Build the result of a shortcut expansion, applying `overflow_mode`
+    /// when `output` doesn't fit in one result
+    fn expansion(&mut self, backspace: u8, output: &[char], consumed: bool) -> Result {
+        let first = if output.len() <= MAX || self.overflow_mode == OVERFLOW_TRUNCATE {
+            output
+        } else if self.overflow_mode == OVERFLOW_SPLIT {
+            for chunk in output[MAX..].chunks(MAX) {
+                self.overflow_queue.push_back(Result::send(0, chunk));
+            }
+            &output[..MAX]
+        } else {
+            // Not expanded: the word goes on as typed, the key result gets FLAG_OVERFLOW
+            self.overflowed = true;
+            return Result::none();
+        };
+        if consumed {
+            Result::send_consumed(backspace, first)
+        } else {
+            Result::send(backspace, first)
+        }
+    }
+
     /// Try "w" as vowel "ư" in Telex mode
     ///
     /// Rules:
@@ -1024,9 +2820,19 @@ impl Engine {
 
         // Validate: is this valid Vietnamese?
         // Use is_valid_with_tones to check modifier requirements (e.g., E+U needs circumflex)
+        // A user-configured initial set replaces validation when only an initial precedes 'w'
         let buffer_keys: Vec<u16> = self.buf.iter().map(|c| c.key).collect();
         let buffer_tones: Vec<u8> = self.buf.iter().map(|c| c.tone).collect();
-        if is_valid_with_tones(&buffer_keys, &buffer_tones) {
+        let initial = &buffer_keys[..buffer_keys.len() - 1];
+        let valid = match &self.w_valid_initials {
+            Some(allowed)
+                if !initial.is_empty() && initial.iter().all(|&k| keys::is_consonant(k)) =>
+            {
+                allowed.contains(&initial[0])
+            }
+            _ => is_valid_with_tones(&buffer_keys, &buffer_tones),
+        };
+        if valid {
             self.last_transform = Some(Transform::WAsVowel);
             self.had_any_transform = true;
 
@@ -1038,6 +2844,7 @@ impl Engine {
 
         // Invalid - remove the U we added
         self.buf.pop();
+        self.rejected = true;
         None
     }
 
@@ -1051,9 +2858,12 @@ impl Engine {
     /// In VNI mode, '9' is always an intentional stroke command (not a letter), so
     /// delayed stroke is allowed (e.g., "duong9" → "đuong").
     fn try_stroke(&mut self, key: u16) -> Option<Result> {
-        // If stroke was already reverted in this word (ddd → dd), skip further stroke attempts
-        // This prevents "ddddd" from oscillating and ensures subsequent 'd's are just letters
-        if self.stroke_reverted && key == keys::D {
+        let m = input::get(self.method);
+
+        // If stroke was already reverted in this word (ddd → dd, d99 → d9), skip further
+        // stroke attempts. This prevents "ddddd"/"d9999" from oscillating and ensures
+        // subsequent stroke keys ('d' in Telex, '9' in VNI) are just literal keys
+        if (self.stroke_reverted && m.stroke(key)) || self.reverted_run_key == Some(key) {
             return None;
         }
 
@@ -1067,11 +2877,14 @@ impl Engine {
                     if let Some(c) = self.buf.get_mut(pos) {
                         c.stroke = false;
                     }
-                    // Add another 'd' as normal char
-                    self.buf.push(Char::new(key, false));
+                    // Add another 'd' as normal char, keeping the case it was typed in
+                    // ("DDD" → "DD", not "Dd"); raw_input's last entry is this keystroke
+                    let caps = self.raw_input.last().is_some_and(|&(_, c, _)| c);
+                    self.buf.push(Char::new(key, caps));
                     self.last_transform = None;
                     // Mark that stroke was reverted - subsequent 'd' keys will be normal letters
                     self.stroke_reverted = true;
+                    self.reverted_run_key = Some(key);
                     // Fix raw_input: "ddd" typed → raw has [d,d,d] but buffer is "dd"
                     // Remove the stroke-triggering 'd' from raw_input so restore works correctly
                     // raw_input: [d, d, d] → [d, d] (remove middle 'd' that triggered stroke)
@@ -1094,18 +2907,20 @@ impl Engine {
         // If last transform was short-pattern stroke and 'd' is pressed again, revert the stroke
         // This is similar to the ddd → dd revert above, but for delayed stroke patterns
         if let Some(Transform::ShortPatternStroke) = self.last_transform {
-            if key == keys::D {
+            if m.stroke(key) {
                 // Find the stroked 'd' to revert
                 if let Some(pos) = self.buf.iter().position(|c| c.key == keys::D && c.stroke) {
                     // Revert: un-stroke the 'd'
                     if let Some(c) = self.buf.get_mut(pos) {
                         c.stroke = false;
                     }
-                    // Add another 'd' as normal char
-                    self.buf.push(Char::new(key, false));
+                    // Add another 'd' as normal char, keeping its typed case
+                    let caps = self.raw_input.last().is_some_and(|&(_, c, _)| c);
+                    self.buf.push(Char::new(key, caps));
                     self.last_transform = None;
                     // Mark that stroke was reverted - subsequent 'd' keys will be normal letters
                     self.stroke_reverted = true;
+                    self.reverted_run_key = Some(key);
                     // Fix raw_input same as above
                     if self.raw_input.len() >= 2 {
                         let current = self.raw_input.pop();
@@ -1151,7 +2966,7 @@ impl Engine {
                 // Must form valid Vietnamese (including vowel pattern) for delayed stroke
                 // Use is_valid() instead of is_valid_for_transform() to check vowel patterns
                 // This prevents "dea" + "d" → "đea" (invalid "ea" diphthong)
-                if !is_valid(&buffer_keys) {
+                if !self.validation_cache.is_valid(&buffer_keys) {
                     return None;
                 }
 
@@ -1172,6 +2987,10 @@ impl Engine {
                 let is_d_vowels_only_pattern = key == keys::D
                     && self.buf.len() >= 2
                     && self.buf.iter().skip(1).all(|c| keys::is_vowel(c.key));
+                // Simple mode: no short-pattern stroke ("did" stays "did")
+                if self.simple_mode && is_d_vowels_only_pattern && !has_mark_applied {
+                    return None;
+                }
                 if syllable.final_c.is_empty() && !has_mark_applied && !is_d_vowels_only_pattern {
                     // Open syllable without mark, not d+vowels pattern - defer stroke decision
                     return None;
@@ -1190,6 +3009,20 @@ impl Engine {
                 .enumerate()
                 .find(|(_, c)| c.key == keys::D && !c.stroke)
                 .map(|(i, _)| i)?;
+
+            // Stroke key again while the word already has 'đ' (d9d9) reverts the
+            // stroke instead of producing a second 'đ'. Same as "ddd" → "dd" in Telex:
+            // the '9' is consumed and subsequent '9's are literal (stroke_reverted)
+            if let Some(stroked_pos) = self.buf.iter().position(|c| c.key == keys::D && c.stroke) {
+                if let Some(c) = self.buf.get_mut(stroked_pos) {
+                    c.stroke = false;
+                }
+                self.last_transform = None;
+                self.stroke_reverted = true;
+                self.reverted_run_key = Some(key);
+                return Some(self.rebuild_from(stroked_pos));
+            }
+
             (pos, false) // VNI never uses short pattern stroke
         };
 
@@ -1204,7 +3037,11 @@ impl Engine {
         // Only validate if buffer has vowels (complete syllable)
         // Allow stroke on initial consonant before vowel is typed (e.g., "dd" → "đ" then "đi")
         // Skip validation if free_tone mode is enabled
-        if !self.free_tone_enabled && has_vowel && !is_valid_for_transform(&buffer_keys) {
+        if !self.free_tone_enabled
+            && has_vowel
+            && !self.validation_cache.is_valid_for_transform(&buffer_keys)
+        {
+            self.rejected = true;
             return None;
         }
 
@@ -1224,6 +3061,17 @@ impl Engine {
     }
 
     /// Try to apply tone transformation by scanning buffer for targets
+    /// Check if the buffer is "initial + o" with an initial listed in
+    /// `double_o_literal_initials`, so the next 'o' should not add circumflex
+    fn is_double_o_literal(&self) -> bool {
+        match (self.buf.get(0), self.buf.last()) {
+            (Some(first), Some(last)) if last.key == keys::O && keys::is_consonant(first.key) => {
+                self.double_o_literal_initials.contains(&first.key)
+            }
+            _ => false,
+        }
+    }
+
     fn try_tone(
         &mut self,
         key: u16,
@@ -1231,7 +3079,7 @@ impl Engine {
         tone_type: ToneType,
         targets: &[u16],
     ) -> Option<Result> {
-        if self.buf.is_empty() {
+        if self.buf.is_empty() || self.reverted_run_key == Some(key) {
             return None;
         }
 
@@ -1258,7 +3106,9 @@ impl Engine {
         // Skip validation if free_tone mode is enabled
         let buffer_keys: Vec<u16> = self.buf.iter().map(|c| c.key).collect();
 
-        if !self.free_tone_enabled && !is_valid_for_transform(&buffer_keys) {
+        if !self.free_tone_enabled && !self.validation_cache.is_valid_for_transform(&buffer_keys) {
+            // Only a rejection if there was a vowel for the tone to land on
+            self.rejected = buffer_keys.iter().any(|k| targets.contains(k));
             return None;
         }
 
@@ -1288,7 +3138,7 @@ impl Engine {
                         let is_uo_pattern = c1.key == keys::U && c2.key == keys::O;
                         let has_final = self.buf.get(pos2 + 1).is_some();
 
-                        if is_uo_pattern && !has_final {
+                        if is_uo_pattern && !has_final && !self.eager_uo_horn {
                             // "uơ" pattern - only 'o' gets horn initially
                             // Set pending so 'u' gets horn if final consonant/vowel is added
                             target_positions.push(pos2);
@@ -1344,6 +3194,11 @@ impl Engine {
                         return None;
                     }
 
+                    // Doubled 'o' after a user-listed initial stays literal ("cool", "zoo")
+                    if key == keys::O && self.is_double_o_literal() {
+                        return None;
+                    }
+
                     // Check if buffer has multiple vowel types and any has a mark
                     // Skip circumflex if it would create invalid diphthong (like ôà, âo)
                     // But allow if circumflex creates valid pattern (like uê, iê, yê)
@@ -1641,7 +3496,10 @@ impl Engine {
                                             .iter()
                                             .filter_map(|&c| char::from_u32(c))
                                             .collect();
-                                        return Some(Result::send(result.backspace, &chars));
+                                        return Some(
+                                            Result::send(result.backspace, &chars)
+                                                .with_tone_index(i, i),
+                                        );
                                     }
 
                                     if !allow_circumflex {
@@ -1829,10 +3687,14 @@ impl Engine {
                 return None;
             }
 
-            // Issue #44 (part 2): Always apply breve for "aw" pattern immediately
+            // Issue #44 (part 2): By default, apply breve for "aw" pattern immediately
             // "aw" → "ă", "taw" → "tă", "raw" → "ră"
-            // The breve is always applied - English auto-restore handles English words separately
-            let has_breve_open_syllable = false;
+            // English auto-restore handles English words separately.
+            // With `defer_breve`, an 'a' ending the syllable waits for a final instead
+            let has_breve_open_syllable = self.defer_breve
+                && target_positions.iter().any(|&pos| {
+                    pos + 1 == self.buf.len() && self.buf.get(pos).is_some_and(|c| c.key == keys::A)
+                });
 
             if has_breve_open_syllable {
                 // Revert: clear applied tones, defer breve until final consonant
@@ -1867,12 +3729,21 @@ impl Engine {
             rebuild_pos = rebuild_pos.min(old_pos);
         }
 
-        Some(self.rebuild_from(rebuild_pos))
+        // Highlight the mark-bearing vowel if any, else the vowel that got the tone
+        let tone_pos = self
+            .buf
+            .iter()
+            .position(|c| c.mark > mark::NONE)
+            .unwrap_or(target_positions[0]);
+        Some(
+            self.rebuild_from(rebuild_pos)
+                .with_tone_index(tone_pos, rebuild_pos),
+        )
     }
 
     /// Try to apply mark transformation
     fn try_mark(&mut self, key: u16, caps: bool, mark_val: u8) -> Option<Result> {
-        if self.buf.is_empty() {
+        if self.buf.is_empty() || self.reverted_run_key == Some(key) {
             return None;
         }
 
@@ -2052,8 +3923,10 @@ impl Engine {
         if !self.free_tone_enabled
             && !has_horn_transforms
             && !has_stroke_transforms
-            && !is_valid_for_transform(&buffer_keys)
+            && !self.validation_cache.is_valid_for_transform(&buffer_keys)
         {
+            // Only a rejection if there was a vowel for the mark to land on
+            self.rejected = buffer_keys.iter().any(|&k| keys::is_vowel(k));
             return None;
         }
 
@@ -2092,8 +3965,14 @@ impl Engine {
         let has_final = self.has_final_consonant(last_vowel_pos);
         let has_qu = self.has_qu_initial();
         let has_gi = self.has_gi_initial();
-        let pos =
-            Phonology::find_tone_position(&vowels, has_final, self.modern_tone, has_qu, has_gi);
+        let pos = Phonology::find_tone_position_split(
+            &vowels,
+            has_final,
+            self.oa_oe_style == 1,
+            self.modern_tone,
+            has_qu,
+            has_gi,
+        );
 
         if let Some(c) = self.buf.get_mut(pos) {
             c.mark = mark_val;
@@ -2112,7 +3991,9 @@ impl Engine {
                     .filter_map(|&c| char::from_u32(c))
                     .collect();
                 // Add 1 to backspace for the trigger 'd' that was on screen but removed from buffer
-                return Some(Result::send(result.backspace + 1, &chars));
+                return Some(
+                    Result::send(result.backspace + 1, &chars).with_tone_index(pos, rebuild_pos),
+                );
             }
 
             // If there was pending breve, we need extra backspace
@@ -2126,7 +4007,9 @@ impl Engine {
                     .filter_map(|&c| char::from_u32(c))
                     .collect();
                 // Add 1 to backspace to account for modifier on screen
-                return Some(Result::send(result.backspace + 1, &chars));
+                return Some(
+                    Result::send(result.backspace + 1, &chars).with_tone_index(pos, rebuild_pos),
+                );
             }
 
             // If delayed circumflex was applied, rebuild from earliest vowel position
@@ -2139,10 +4022,15 @@ impl Engine {
                     .filter_map(|&c| char::from_u32(c))
                     .collect();
                 // Add 1 to backspace for the removed trigger vowel still on screen
-                return Some(Result::send(result.backspace + 1, &chars));
+                return Some(
+                    Result::send(result.backspace + 1, &chars).with_tone_index(pos, rebuild_pos),
+                );
             }
 
-            return Some(self.rebuild_from(rebuild_pos));
+            return Some(
+                self.rebuild_from(rebuild_pos)
+                    .with_tone_index(pos, rebuild_pos),
+            );
         }
 
         None
@@ -2317,8 +4205,14 @@ impl Engine {
             let has_final = self.has_final_consonant(last_vowel_pos);
             let has_qu = self.has_qu_initial();
             let has_gi = self.has_gi_initial();
-            let new_pos =
-                Phonology::find_tone_position(&vowels, has_final, self.modern_tone, has_qu, has_gi);
+            let new_pos = Phonology::find_tone_position_split(
+                &vowels,
+                has_final,
+                self.oa_oe_style == 1,
+                self.modern_tone,
+                has_qu,
+                has_gi,
+            );
 
             if new_pos != old_pos {
                 // Move tone from old position to new position
@@ -2328,6 +4222,7 @@ impl Engine {
                 if let Some(c) = self.buf.get_mut(new_pos) {
                     c.mark = tone_value;
                 }
+                trace!(self, "reposition mark {old_pos} -> {new_pos}");
                 return Some((old_pos, new_pos));
             }
         }
@@ -2362,7 +4257,9 @@ impl Engine {
 
     /// Revert tone transformation
     fn revert_tone(&mut self, key: u16, caps: bool) -> Result {
+        trace!(self, "revert tone");
         self.last_transform = None;
+        self.reverted_run_key = Some(key);
 
         for pos in self.buf.find_vowels().into_iter().rev() {
             if let Some(c) = self.buf.get_mut(pos) {
@@ -2391,7 +4288,9 @@ impl Engine {
     /// Standard behavior: "ass" → "as" (first 's' was modifier, second 's' reverts + outputs one 's')
     /// This matches standard Vietnamese IME behavior (UniKey, ibus-unikey, etc.)
     fn revert_mark(&mut self, key: u16, caps: bool) -> Result {
+        trace!(self, "revert mark");
         self.last_transform = None;
+        self.reverted_run_key = Some(key);
         self.had_mark_revert = true; // Track for auto-restore
 
         for pos in self.buf.find_vowels().into_iter().rev() {
@@ -2426,12 +4325,14 @@ impl Engine {
 
     /// Revert stroke transformation at specific position
     fn revert_stroke(&mut self, key: u16, pos: usize) -> Result {
+        trace!(self, "revert stroke at {pos}");
         self.last_transform = None;
 
         if let Some(c) = self.buf.get_mut(pos) {
             if c.key == keys::D && !c.stroke {
-                // Un-stroked d found at pos - this means we need to add another d
-                let caps = c.caps;
+                // Un-stroked d found at pos - this means we need to add another d,
+                // in the case of the reverting keystroke (raw_input's last entry)
+                let caps = self.raw_input.last().is_some_and(|&(_, c, _)| c);
                 self.buf.push(Char::new(key, caps));
                 return self.rebuild_from(pos);
             }
@@ -2444,12 +4345,19 @@ impl Engine {
     /// When None is returned, the key falls through to handle_normal_letter()
     fn try_remove(&mut self) -> Option<Result> {
         self.last_transform = None;
-        for pos in self.buf.find_vowels().into_iter().rev() {
+        let vowels = self.buf.find_vowels();
+        // Mark (sắc/huyền/...) takes priority over vowel tone (^/horn/breve),
+        // regardless of which vowel carries it: "tuố" + 0 → "tuô", not "tuó"
+        for pos in vowels.iter().rev().copied() {
             if let Some(c) = self.buf.get_mut(pos) {
                 if c.mark > mark::NONE {
                     c.mark = mark::NONE;
                     return Some(self.rebuild_from(pos));
                 }
+            }
+        }
+        for pos in vowels.iter().rev().copied() {
+            if let Some(c) = self.buf.get_mut(pos) {
                 if c.tone > tone::NONE {
                     c.tone = tone::NONE;
                     return Some(self.rebuild_from(pos));
@@ -2582,11 +4490,9 @@ impl Engine {
             // "trawm" → after "traw" (pending breve on 'a'), typing 'm' applies breve → "trăm"
             if let Some(breve_pos) = self.pending_breve_pos {
                 // Valid final consonants that make breve valid: c, k, m, n, p, t
-                // Note: k is included for ethnic minority words (Đắk Lắk)
-                if matches!(
-                    key,
-                    keys::C | keys::K | keys::M | keys::N | keys::P | keys::T
-                ) {
+                // Uses the same set as validation (VALID_FINALS_1, consonants only)
+                // so ethnic minority words like "Đắk Lắk" compose and validate alike
+                if constants::VALID_FINALS_1.contains(&key) && !keys::is_vowel(key) {
                     // Find and remove the breve modifier from buffer
                     // Telex uses 'w', VNI uses '8' - it should be right after 'a' at breve_pos
                     let modifier_pos = breve_pos + 1;
@@ -2658,6 +4564,15 @@ impl Engine {
                 return Result::send(0, &[vowel_char]);
             }
 
+            // Strict finals: a consonant that turns a valid syllable into an invalid
+            // final ("việt" + b, "óc" + h) restores raw right away, and the rest of
+            // the word stays raw
+            if self.strict_finals && keys::is_consonant(key) {
+                if let Some(result) = self.try_strict_final_restore() {
+                    return result;
+                }
+            }
+
             // Auto-correct tone position when new character changes the correct placement
             //
             // Two scenarios:
@@ -2667,7 +4582,13 @@ impl Engine {
             //    "muas" → tone on 'u' (ua open), then 'n' added → "uan" needs tone on 'a'
             //
             // Both cases need to reposition the tone mark based on Vietnamese phonology.
-            if let Some((old_pos, _new_pos)) = self.reposition_tone_if_needed() {
+            // Simple mode keeps the mark where it was placed.
+            let repositioned = if self.simple_mode {
+                None
+            } else {
+                self.reposition_tone_if_needed()
+            };
+            if let Some((old_pos, _new_pos)) = repositioned {
                 // Tone was moved - rebuild output from the old position
                 // Note: the new char was just added to buffer but NOT yet displayed
                 // So backspace = (chars from old_pos to BEFORE new char)
@@ -2750,6 +4671,57 @@ impl Engine {
         Result::none()
     }
 
+    /// Restore raw if the consonant just pushed made an invalid final
+    ///
+    /// Only fires when the syllable before it was valid Vietnamese with transforms
+    /// on screen. The consonant is in the buffer but not yet displayed.
+    fn try_strict_final_restore(&mut self) -> Option<Result> {
+        let len = self.buf.len();
+        let before = || self.buf.iter().take(len - 1);
+        if !before().any(|c| keys::is_vowel(c.key))
+            || !before().any(|c| c.tone > 0 || c.mark > 0 || c.stroke)
+        {
+            return None;
+        }
+
+        let buffer_keys: Vec<u16> = self.buf.iter().map(|c| c.key).collect();
+        let buffer_tones: Vec<u8> = self.buf.iter().map(|c| c.tone).collect();
+        if !is_valid_with_tones(&buffer_keys[..len - 1], &buffer_tones[..len - 1]) {
+            return None;
+        }
+
+        // ch/nh finals only follow a, ê, i, y (ách, ếch, ính; not "óch")
+        let palatal_final_ok = || {
+            let n = buffer_keys.len();
+            if n < 3
+                || buffer_keys[n - 1] != keys::H
+                || !matches!(buffer_keys[n - 2], keys::C | keys::N)
+            {
+                return true;
+            }
+            matches!(
+                (buffer_keys[n - 3], buffer_tones[n - 3]),
+                (keys::A, tone::NONE) | (keys::E, tone::CIRCUMFLEX) | (keys::I | keys::Y, _)
+            )
+        };
+        if is_valid_with_tones(&buffer_keys, &buffer_tones) && palatal_final_ok() {
+            return None;
+        }
+
+        let raw_chars: Vec<char> = self
+            .raw_input
+            .iter()
+            .filter_map(|&(key, caps, shift)| utils::key_to_char_ext(key, caps, shift))
+            .collect();
+        self.buf.clear();
+        for &(key, caps, _) in &self.raw_input {
+            self.buf.push(Char::new(key, caps));
+        }
+        self.last_transform = None;
+        self.raw_current_word = true;
+        Some(Result::restore((len - 1) as u8, &raw_chars))
+    }
+
     /// Check if buffer has w-as-vowel transform (standalone w→ư at start)
     /// This is different from w-as-tone which adds horn to existing vowels
     fn has_w_as_vowel_transform(&self) -> bool {
@@ -2822,6 +4794,31 @@ impl Engine {
         utils::has_gi_initial(&self.buf)
     }
 
+    /// Strip the outermost diacritic of the last char: mark, then tone, then stroke
+    ///
+    /// "ế" → "ê" → "e" → (plain delete). Returns None when the last char is plain.
+    fn try_smart_backspace(&mut self) -> Option<Result> {
+        let last = self.buf.len().checked_sub(1)?;
+        let c = self.buf.get_mut(last)?;
+        if c.mark > 0 {
+            c.mark = 0;
+        } else if c.tone > 0 {
+            c.tone = 0;
+        } else if c.stroke {
+            c.stroke = false;
+        } else {
+            return None;
+        }
+
+        // Keystrokes that built the layer are gone: raw input follows the buffer
+        let buf = self.buf.clone();
+        self.restore_raw_input_from_buffer(&buf);
+        self.last_transform = None;
+        self.stroke_reverted = false;
+        self.reverted_run_key = None;
+        Some(self.rebuild_from(last))
+    }
+
     /// Rebuild output from position
     fn rebuild_from(&self, from: usize) -> Result {
         let mut output = Vec::with_capacity(self.buf.len() - from);
@@ -2900,21 +4897,80 @@ impl Engine {
         self.pending_breve_pos = None;
         self.pending_u_horn_pos = None;
         self.stroke_reverted = false;
+        self.reverted_run_key = None;
         self.had_mark_revert = false;
         self.pending_mark_revert_pop = false;
         self.had_any_transform = false;
         self.had_vowel_triggered_circumflex = false;
         self.restored_pending_clear = false;
         self.shortcut_prefix.clear();
+        self.shortcut_declined = false;
+        self.raw_current_word = false;
+        self.apostrophe_stem_kept = false;
+        self.t9_cycle = None;
+        self.reset_hyphen_compound();
+        self.deferred_keys.clear();
+    }
+
+    /// Clear everything including word history
+    /// Used when cursor position changes (mouse click, arrow keys, etc.)
+    /// to prevent accidental restore from stale history
+    pub fn clear_all(&mut self) {
+        self.clear();
+        self.word_history.clear();
+        self.spaces_after_commit = 0;
+        self.rtl_context = false;
+    }
+
+    /// Sync after the host deleted `count` chars without sending DELETE through `on_key`
+    ///
+    /// Pops from buffer and raw input (clamped to what is tracked), like backspace
+    /// does, so the next key composes against what is actually on screen.
+    pub fn notify_external_delete(&mut self, count: usize) {
+        if count > self.buf.len() {
+            // Deleted into text we don't track (see DELETE in on_key_ext)
+            self.has_non_letter_prefix = true;
+        }
+        for _ in 0..count.min(self.buf.len()) {
+            self.buf.pop();
+            self.raw_input.pop();
+        }
+        if self.buf.is_empty() {
+            self.raw_input.clear();
+            self.restored_pending_clear = false;
+        }
+        let len = self.buf.len();
+        self.pending_breve_pos = self.pending_breve_pos.filter(|&p| p < len);
+        self.pending_u_horn_pos = self.pending_u_horn_pos.filter(|&p| p < len);
+        self.last_transform = None;
+        self.stroke_reverted = false;
+        self.reverted_run_key = None;
     }
 
-    /// Clear everything including word history
-    /// Used when cursor position changes (mouse click, arrow keys, etc.)
-    /// to prevent accidental restore from stale history
-    pub fn clear_all(&mut self) {
-        self.clear();
-        self.word_history.clear();
-        self.spaces_after_commit = 0;
+    /// Cancel a pending auto-capitalize without touching buffer or history
+    ///
+    /// Use when only the capitalization intent changed (mouse click into
+    /// mid-sentence, programmatic edit). Narrower than `clear_all`.
+    pub fn cancel_pending_capitalize(&mut self) {
+        self.pending_capitalize = false;
+        self.auto_capitalize_used = false;
+    }
+
+    /// Take the decisions recorded since the last call (`trace` feature)
+    ///
+    /// One entry per event: the key, which `try_*` fired, mark repositions,
+    /// reverts and auto-restores. Only the latest 512 entries are kept.
+    #[cfg(feature = "trace")]
+    pub fn take_trace(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.trace)
+    }
+
+    #[cfg(feature = "trace")]
+    fn record_trace(&mut self, entry: String) {
+        if self.trace.len() >= TRACE_LIMIT {
+            self.trace.remove(0);
+        }
+        self.trace.push(entry);
     }
 
     /// Get the full composed buffer as a Vietnamese string with diacritics.
@@ -2924,6 +4980,53 @@ impl Engine {
         self.buf.to_full_string()
     }
 
+    /// Tone (^/horn/breve) and mark of the current word's diacritic vowel
+    ///
+    /// The vowel is the one carrying the mark, or the last one with ^/horn/breve
+    /// when the word has no mark: "người" → (HORN, HUYEN), "viêt" → (CIRCUMFLEX, 0).
+    /// (0, 0) for an empty buffer or a word without diacritics. For UI badges.
+    pub fn current_tone_mark(&self) -> (u8, u8) {
+        self.buf
+            .iter()
+            .find(|c| c.has_mark())
+            .or_else(|| self.buf.iter().rev().find(|c| c.has_tone()))
+            .map_or((tone::NONE, mark::NONE), |c| (c.tone, c.mark))
+    }
+
+    /// How Vietnamese the current word looks, 0-100 (0 for an empty buffer)
+    ///
+    /// Combines buffer validity, whether transforms applied, and how English-like
+    /// the raw keystrokes are:
+    /// - valid with diacritics ("việt") → 100
+    /// - valid, plain letters ("ban") → 60
+    /// - invalid after transforms ("tesst" → "test") → 30
+    /// - invalid, plain English-like letters ("search") → 0
+    ///
+    /// Hosts can use it to decide when to offer English restore.
+    pub fn vietnamese_confidence(&self) -> u8 {
+        if self.buf.is_empty() {
+            return 0;
+        }
+
+        let transformed = self.had_any_transform
+            || self
+                .buf
+                .iter()
+                .any(|c| c.has_tone() || c.has_mark() || c.stroke);
+        let mut score: i32 = 50;
+        score += if self.is_buffer_invalid_vietnamese() {
+            -40
+        } else {
+            30
+        };
+        if transformed {
+            score += 20;
+        } else if self.is_raw_input_valid_english() {
+            score -= 20;
+        }
+        score.clamp(0, 100) as u8
+    }
+
     /// Debug: Check if vowel-triggered circumflex flag is set
     pub fn had_vowel_circumflex(&self) -> bool {
         self.had_vowel_triggered_circumflex
@@ -2939,13 +5042,58 @@ impl Engine {
         self.is_raw_input_valid_english()
     }
 
+    /// Debug: Get raw keystrokes of current word as string
+    /// Example: typing "vieetj" → buffer "việt", raw input "vieetj"
+    pub fn raw_input_string(&self) -> String {
+        self.raw_input
+            .iter()
+            .filter_map(|&(key, caps, shift)| utils::key_to_char_ext(key, caps, shift))
+            .collect()
+    }
+
+    /// Debug: Get the break chars accumulated for prefix shortcuts ("->", "#fne")
+    pub fn shortcut_prefix_str(&self) -> &str {
+        &self.shortcut_prefix
+    }
+
+    /// Reset the prefix shortcut accumulator without touching the current word
+    pub fn clear_shortcut_prefix(&mut self) {
+        self.shortcut_prefix.clear();
+    }
+
+    /// Debug: Edit made by the most recent `on_key_ext` result
+    ///
+    /// (backspace count, inserted chars); both empty if that key sent nothing.
+    /// Lets a host recover an edit whose `Result` it missed.
+    pub fn last_edit(&self) -> (u8, Vec<char>) {
+        self.last_edit.clone()
+    }
+
+    /// Debug: Seed raw input and replay it to rebuild the buffer
+    ///
+    /// Reproduces bug reports exactly from the keystrokes in `raw_input_string()`.
+    /// Only letters and numbers are replayed (raw input never holds break keys).
+    pub fn set_raw_and_recompose(&mut self, raw: &str) {
+        self.clear();
+        for c in raw.chars() {
+            let key = utils::char_to_key(c);
+            if keys::is_letter(key) || keys::is_number(key) {
+                self.handle_key(key, c.is_uppercase(), false, false);
+            }
+        }
+    }
+
     /// Restore buffer from a Vietnamese word string
     ///
     /// Used when native app detects cursor at word boundary and wants to edit.
     /// Parses Vietnamese characters back to buffer components.
+    /// Input is capped at buffer capacity (`MAX` chars); the rest is ignored.
     pub fn restore_word(&mut self, word: &str) {
         self.clear();
         for c in word.chars() {
+            if self.buf.len() >= MAX {
+                break;
+            }
             if let Some(parsed) = chars::parse_char(c) {
                 let mut ch = Char::new(parsed.key, parsed.caps);
                 ch.tone = parsed.tone;
@@ -2957,6 +5105,333 @@ impl Engine {
         }
     }
 
+    /// Restore buffer from a Vietnamese word and the keystrokes that typed it
+    ///
+    /// Like `restore_word`, but `raw_keys` seed the raw input as given instead
+    /// of guessing, so ESC restore gives back the real keystrokes ("việt" with
+    /// V,I,E,E,T,J → "vieetj"; with VNI keys → "vie6t5"). Keys take the case of
+    /// the letter they match in `word`; other keys are uppercase only if the
+    /// whole word is. Empty `raw_keys` is the same as `restore_word`. Keys are
+    /// capped at `MAX`, like the word.
+    pub fn restore_with_raw(&mut self, word: &str, raw_keys: &[u16]) {
+        self.restore_word(word);
+        if raw_keys.is_empty() {
+            return;
+        }
+
+        let all_caps = self.buf.len() > 1 && self.buf.iter().all(|c| c.caps);
+        let mut next = 0;
+        self.raw_input.clear();
+        for &key in raw_keys.iter().take(MAX) {
+            let caps = match self.buf.get(next) {
+                Some(c) if c.key == key => {
+                    next += 1;
+                    c.caps
+                }
+                _ => all_caps,
+            };
+            self.raw_input.push((key, caps, false));
+        }
+    }
+
+    /// Commit the word up to `index` (in chars) and keep the rest composing
+    ///
+    /// For live correction: "việtnam" split at 4 leaves "việt" as committed text
+    /// (in word history, like a word committed with space) and "nam" as a fresh
+    /// word. The rest keeps its own keystrokes when they type it on their own
+    /// (VNI "vie6t5" at 2 → "ệt" with raw "e6t5"); otherwise, e.g. when a later
+    /// mark key belongs to the committed part, its raw input is rebuilt from its
+    /// letters. Returns a Send replacing the remainder with itself, so hosts that
+    /// mark the composing text can re-anchor it; nothing visible changes.
+    /// Returns `none` if `index` is 0 or past the end (nothing to split).
+    pub fn split_at(&mut self, index: usize) -> Result {
+        if index == 0 || index >= self.buf.len() {
+            return Result::none();
+        }
+
+        let displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let rest: String = self.buf.to_full_string().chars().skip(index).collect();
+        let rest_raw = self
+            .raw_split_point(index, &rest)
+            .map(|k| self.raw_input[k..].to_vec());
+        let mut committed = self.buf.clone();
+        while committed.len() > index {
+            committed.pop();
+        }
+
+        // The committed part keeps its auto-capitalized letter
+        self.auto_capitalize_used = false;
+        self.restore_word(&rest);
+        if let Some(raw) = rest_raw {
+            self.raw_input = raw;
+        }
+        self.word_history.push(committed);
+        self.spaces_after_commit = 0;
+
+        let output: Vec<char> = rest.chars().collect();
+        self.nfd_edit(Result::send(output.len() as u8, &output), &displayed)
+    }
+
+    /// Keystroke index where buffer char `index` starts, if the keys from there
+    /// on type exactly `rest` as a word of their own
+    ///
+    /// Replays prefixes of the raw input in a scratch engine with the same
+    /// method and tone settings.
+    fn raw_split_point(&self, index: usize, rest: &str) -> Option<usize> {
+        let replay = |keys: &[(u16, bool, bool)]| {
+            let mut probe = Engine::new();
+            probe.method = self.method;
+            probe.modern_tone = self.modern_tone;
+            probe.oa_oe_style = self.oa_oe_style;
+            probe.free_tone_enabled = self.free_tone_enabled;
+            probe.skip_w_shortcut = self.skip_w_shortcut;
+            for &(key, caps, shift) in keys {
+                probe.handle_key(key, caps, false, shift);
+            }
+            probe.buf
+        };
+
+        let k = (0..=self.raw_input.len())
+            .rev()
+            .find(|&k| replay(&self.raw_input[..k]).len() == index)?;
+        (replay(&self.raw_input[k..]).to_full_string() == rest).then_some(k)
+    }
+
+    /// Sync engine state with host-reported text around the cursor
+    ///
+    /// Used when the app manages the cursor (mouse click, arrow keys, external
+    /// edits) instead of the engine assuming composition always happens at the end.
+    /// `before`/`after` are the text immediately left/right of the cursor:
+    /// - Cursor inside a word (`after` starts with a letter/digit): nothing is
+    ///   restored, so the next keys never rewrite text to the right of the cursor
+    /// - Cursor right after a word ("việt|"): the word is restored for editing
+    /// - Cursor after a word and spaces ("việt  |"): the word is kept in history,
+    ///   so backspacing the spaces restores it like after a normal commit
+    /// - Right-to-left text before the word turns on bidi isolation for its commit
+    pub fn set_cursor_context(&mut self, before: &str, after: &str) {
+        self.clear_all();
+
+        let trimmed = before.trim_end_matches(' ');
+        let spaces = before.len() - trimmed.len();
+        let word_start = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !c.is_alphabetic() || is_rtl_char(*c))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        // Script the next committed word follows: the word itself once spaced off
+        let preceding = if spaces > 0 {
+            trimmed
+        } else {
+            &trimmed[..word_start]
+        };
+        self.rtl_context = preceding
+            .chars()
+            .rev()
+            .find(|c| c.is_alphabetic())
+            .is_some_and(is_rtl_char);
+
+        if after.chars().next().is_some_and(|c| c.is_alphanumeric()) {
+            return;
+        }
+        let word = &trimmed[word_start..];
+        if word.is_empty() {
+            return;
+        }
+
+        self.restore_word(word);
+        if spaces > 0 {
+            self.word_history.push(self.buf.clone());
+            self.spaces_after_commit = spaces.min(u8::MAX as usize) as u8;
+            self.clear();
+        }
+    }
+
+    /// Record a space the host inserted itself (e.g., autocomplete)
+    ///
+    /// Same word-boundary bookkeeping as a typed SPACE, but nothing is sent:
+    /// no shortcut expansion or auto-restore, since the text is already on screen.
+    /// Keeps backspace-after-space working for spaces outside the keystroke flow.
+    pub fn notify_space(&mut self) {
+        if !self.buf.is_empty() {
+            if self.spaces_after_commit == 1 {
+                self.word_history.push_joined(self.buf.clone());
+            } else {
+                self.word_history.push(self.buf.clone());
+            }
+            self.spaces_after_commit = 1;
+        } else if self.spaces_after_commit > 0 {
+            self.spaces_after_commit = self.spaces_after_commit.saturating_add(1);
+        }
+        self.auto_capitalize_used = false;
+        self.clear();
+    }
+
+    /// Advance the idle timer (host-driven, keeps the core deterministic)
+    ///
+    /// The host calls this periodically with the time elapsed since the previous
+    /// tick. Once the idle threshold (`set_idle_commit_ms`) is reached since the
+    /// last key, the current word is committed: auto-restore runs as on a break
+    /// key, the word is pushed to history and the buffer is cleared.
+    /// With deferred emit, the raw keys on screen are replaced by the word.
+    /// In T9 mode, a pending keypad letter commits first (`set_t9_timeout_ms`).
+    pub fn tick(&mut self, elapsed_ms: u32) -> Result {
+        // A pending keypad letter commits first; the word stays open
+        if self.t9_cycle.is_some() {
+            self.t9_idle_ms = self.t9_idle_ms.saturating_add(elapsed_ms);
+            if self.t9_idle_ms < self.t9_timeout_ms {
+                return Result::none();
+            }
+            return self.commit_t9();
+        }
+
+        if self.idle_commit_ms == 0 || self.buf.is_empty() {
+            return Result::none();
+        }
+
+        self.idle_ms = self.idle_ms.saturating_add(elapsed_ms);
+        if self.idle_ms < self.idle_commit_ms {
+            return Result::none();
+        }
+        self.idle_ms = 0;
+
+        // Same as break key: restore invalid Vietnamese without adding any char
+        let typed = std::mem::take(&mut self.deferred_keys);
+        let mut displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let mut result = self.try_auto_restore_on_break();
+        if result.action != 0 {
+            self.buf.clear();
+            for &(key, caps, _) in &self.raw_input {
+                self.buf.push(Char::new(key, caps));
+            }
+        }
+
+        // Deferred emit: the raw keys are on screen, replace them with the word
+        if !typed.is_empty() {
+            displayed = typed
+                .iter()
+                .filter_map(|&(k, c, s)| utils::key_to_char_ext(k, c, s))
+                .collect();
+            let word: Vec<char> = self.buf.to_full_string().chars().collect();
+            result = if word == displayed {
+                Result::none()
+            } else {
+                Result::send(displayed.len().min(u8::MAX as usize) as u8, &word)
+            };
+        }
+
+        self.word_history.push(self.buf.clone());
+        self.spaces_after_commit = 0;
+        self.clear();
+        self.nfd_edit(result, &displayed)
+    }
+
+    /// Treat the current word as raw English until the next word boundary
+    ///
+    /// One-shot alternative to the global toggles (e.g., bound to a hotkey);
+    /// Vietnamese resumes with the next word. If the word already has transforms,
+    /// returns the replacement restoring it to raw keystrokes ("tẽt" → "text").
+    pub fn force_raw_current_word(&mut self) -> Result {
+        let displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let result = self.restore_to_raw();
+        if result.action != 0 {
+            self.buf.clear();
+            for &(key, caps, _) in &self.raw_input {
+                self.buf.push(Char::new(key, caps));
+            }
+        }
+        self.last_transform = None;
+        self.raw_current_word = true;
+        self.nfd_edit(result, &displayed)
+    }
+
+    /// Swap hỏi ↔ ngã on the current word (e.g., "mỉ" ↔ "mĩ")
+    ///
+    /// Finds the vowel carrying hỏi or ngã and flips it, returning the rebuild
+    /// from that position. Returns `none` if the word has neither mark.
+    pub fn swap_hoi_nga(&mut self) -> Result {
+        let Some(pos) = self
+            .buf
+            .iter()
+            .position(|c| c.mark == mark::HOI || c.mark == mark::NGA)
+        else {
+            return Result::none();
+        };
+
+        let displayed: Vec<char> = self.get_buffer_string().chars().collect();
+        let old_mark = self.buf.get(pos).map(|c| c.mark).unwrap_or(mark::NONE);
+        let new_mark = if old_mark == mark::HOI {
+            mark::NGA
+        } else {
+            mark::HOI
+        };
+        if let Some(c) = self.buf.get_mut(pos) {
+            c.mark = new_mark;
+        }
+
+        // Keep raw_input consistent so ESC/auto-restore reflect the swapped mark key
+        // Telex: r (hỏi) ↔ x (ngã), VNI: 3 (hỏi) ↔ 4 (ngã)
+        let (hoi_key, nga_key) = if self.method == 1 {
+            (keys::N3, keys::N4)
+        } else {
+            (keys::R, keys::X)
+        };
+        let (old_key, new_key) = if old_mark == mark::HOI {
+            (hoi_key, nga_key)
+        } else {
+            (nga_key, hoi_key)
+        };
+        if let Some(entry) = self.raw_input.iter_mut().rev().find(|e| e.0 == old_key) {
+            entry.0 = new_key;
+        }
+
+        self.last_transform = None;
+        let result = self.rebuild_from(pos);
+        self.nfd_edit(result, &displayed)
+    }
+
+    /// Apply a modifier key to an already-displayed character (e.g., 'a' + 's' → 'á')
+    ///
+    /// For "apply mark to selection" features that can't replay keystrokes.
+    /// `tone_key` is read with the given method (0=Telex, 1=VNI). Applying the
+    /// diacritic the char already has removes it, like a repeated key does.
+    /// Returns None if the key doesn't modify `ch`.
+    pub fn apply_tone_to(ch: char, tone_key: u16, method: u8) -> Option<char> {
+        let m = input::get(method);
+        let p = chars::parse_char(ch)?;
+
+        if m.stroke(tone_key) {
+            return match (p.key, p.stroke) {
+                (keys::D, false) => Some(chars::get_d(p.caps)),
+                (keys::D, true) => Some(if p.caps { 'D' } else { 'd' }),
+                _ => None,
+            };
+        }
+        if !keys::is_vowel(p.key) {
+            return None;
+        }
+
+        let (mut t, mut mk) = (p.tone, p.mark);
+        if let Some(new_mark) = m.mark(tone_key) {
+            mk = if mk == new_mark { mark::NONE } else { new_mark };
+        } else if let Some(tone_type) = m.tone(tone_key) {
+            if !m.tone_targets(tone_key).contains(&p.key) {
+                return None;
+            }
+            let value = tone_type.value();
+            t = if t == value { tone::NONE } else { value };
+        } else if m.remove(tone_key) && (mk != mark::NONE || t != tone::NONE) {
+            if mk != mark::NONE {
+                mk = mark::NONE;
+            } else {
+                t = tone::NONE;
+            }
+        } else {
+            return None;
+        }
+        chars::to_char(p.key, p.caps, t, mk)
+    }
+
     /// Check if buffer has transforms and is invalid Vietnamese
     /// Returns the raw chars if restore is needed, None otherwise
     ///
@@ -2972,6 +5447,11 @@ impl Engine {
             return None;
         }
 
+        // Short words stay Vietnamese (see `set_min_restore_len`)
+        if self.raw_input.len() < self.min_restore_len {
+            return None;
+        }
+
         // If no Vietnamese transforms were ever applied this word, nothing to restore
         // This prevents false restore for words with numbers/symbols like "nhatkha1407@gmail.com"
         // where the buffer is invalid Vietnamese but no transforms were ever attempted
@@ -2981,6 +5461,12 @@ impl Engine {
             return None;
         }
 
+        // Clitic after a stem that stayed Vietnamese ("đi's", "Việt'll"): keep it.
+        // "we'll", "isn't" and "didn't" still go through the checks below.
+        if self.apostrophe_stem_kept && self.ends_with_clitic() {
+            return None;
+        }
+
         // Check if any transforms remain in buffer
         // - Marks (sắc, huyền, hỏi, ngã, nặng): indicate Vietnamese typing intent
         // - Vowel tones (â, ê, ô, ư, ă): indicate Vietnamese typing intent
@@ -3200,6 +5686,24 @@ impl Engine {
         }
     }
 
+    /// Whether the buffer ends in an English clitic that attaches to any word
+    /// ('s, 'd, 'm, 'll, 're, 've) after its only apostrophe
+    fn ends_with_clitic(&self) -> bool {
+        let Some(quote) = self.buf.iter().position(|c| c.key == keys::QUOTE) else {
+            return false;
+        };
+        let tail: Vec<u16> = self.buf.iter().skip(quote + 1).map(|c| c.key).collect();
+        matches!(
+            tail.as_slice(),
+            [keys::S]
+                | [keys::D]
+                | [keys::M]
+                | [keys::L, keys::L]
+                | [keys::R, keys::E]
+                | [keys::V, keys::E]
+        )
+    }
+
     /// Check if buffer is NOT valid Vietnamese (for unified auto-restore logic)
     ///
     /// Uses full validation including tone requirements (circumflex for êu, etc.)
@@ -3228,10 +5732,11 @@ impl Engine {
         }
 
         // All keys must be ASCII letters (A-Z)
+        // Intra-word apostrophe ("don't") is only in raw_input when apostrophe_in_word is on
         let all_ascii_letters = self.raw_input.iter().all(|(k, _, _)| {
             // Keys are in range A-Z (from keys.rs)
             // Consonants and vowels are valid English letters
-            keys::is_consonant(*k) || keys::is_vowel(*k)
+            keys::is_consonant(*k) || keys::is_vowel(*k) || *k == keys::QUOTE
         });
 
         if !all_ascii_letters {
@@ -3420,7 +5925,7 @@ impl Engine {
                     };
                     // Preserve case
                     let toned_vowel = if chars[1].is_uppercase() {
-                        toned_vowel.to_uppercase().next().unwrap_or(toned_vowel)
+                        chars::to_upper(toned_vowel)
                     } else {
                         toned_vowel
                     };
@@ -4392,12 +6897,15 @@ impl Engine {
     /// Example: "tẽt" (from typing "text") → "text " (restored + space)
     /// Example: "ễpct" (from typing "expect") → "expect " (restored + space)
     fn try_auto_restore_on_space(&self) -> Result {
-        if let Some(mut raw_chars) = self.should_auto_restore(true) {
+        let restored = self
+            .should_auto_restore(true)
+            .or_else(|| self.should_revert_invalid());
+        if let Some(mut raw_chars) = restored {
             // Add space at the end
             raw_chars.push(' ');
             // Backspace count = current buffer length (displayed chars)
             let backspace = self.buf.len() as u8;
-            Result::send(backspace, &raw_chars)
+            Result::restore(backspace, &raw_chars)
         } else {
             Result::none()
         }
@@ -4410,15 +6918,185 @@ impl Engine {
     /// Does NOT include the break key (it's passed through by the app).
     /// Example: "ễpct" + comma → "expect" (comma added by app)
     fn try_auto_restore_on_break(&self) -> Result {
-        if let Some(raw_chars) = self.should_auto_restore(true) {
+        let restored = self
+            .should_auto_restore(true)
+            .or_else(|| self.should_revert_invalid());
+        if let Some(raw_chars) = restored {
             // Backspace count = current buffer length (displayed chars)
             let backspace = self.buf.len() as u8;
-            Result::send(backspace, &raw_chars)
+            Result::restore(backspace, &raw_chars)
         } else {
             Result::none()
         }
     }
 
+    /// Finish a word whose apostrophe turned out to be trailing ("việt'" + space)
+    ///
+    /// The word before the apostrophe is finished as if the apostrophe had been a
+    /// break key, then `key` is processed on an empty buffer. If the word is
+    /// auto-restored, the apostrophe already on screen is re-emitted after it.
+    fn finish_trailing_apostrophe(&mut self, key: u16, caps: bool, shift: bool) -> Result {
+        self.buf.pop();
+        self.raw_input.pop();
+
+        let restore_result = self.try_auto_restore_on_break();
+        self.auto_capitalize_used = false;
+        self.clear();
+        self.word_history.clear();
+        self.spaces_after_commit = 0;
+
+        if restore_result.action == 0 {
+            return self.handle_key(key, caps, false, shift);
+        }
+
+        let mut output: Vec<char> = restore_result.chars[..restore_result.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        output.push('\'');
+        // Space output includes the space itself (see try_auto_restore_on_space)
+        if key == keys::SPACE {
+            output.push(' ');
+        }
+        Result::restore(restore_result.backspace + 1, &output)
+    }
+
+    /// Respell an i/y variant word on commit ("lí" → "lý" with prefer y)
+    ///
+    /// Only initial + i/y words whose initial allows both spellings
+    /// (`IY_VARIANT_INITIALS`); the mark and case stay. `space` appends the
+    /// space like `try_auto_restore_on_space`.
+    fn apply_iy_preference(&mut self, space: bool) -> Option<Result> {
+        let preferred = match self.iy_preference {
+            1 => keys::Y,
+            2 => keys::I,
+            _ => return None,
+        };
+        if self.buf.len() != 2 || self.has_non_letter_prefix {
+            return None;
+        }
+        let initial = self.buf.get(0)?;
+        if initial.stroke || !constants::IY_VARIANT_INITIALS.contains(&initial.key) {
+            return None;
+        }
+        let vowel = self.buf.get_mut(1)?;
+        if !matches!(vowel.key, keys::I | keys::Y) || vowel.key == preferred {
+            return None;
+        }
+        vowel.key = preferred;
+
+        let mut output: Vec<char> = self.buf.to_full_string().chars().skip(1).collect();
+        if space {
+            output.push(' ');
+        }
+        Some(Result::send(1, &output))
+    }
+
+    /// Close the current part of a hyphenated word and start the next one
+    ///
+    /// The part stays on screen as typed: an English-looking part is restored
+    /// together with the rest of the word at its end, in a single edit.
+    fn continue_hyphen_compound(&mut self) {
+        let restore = self.try_auto_restore_on_break();
+        let (raw, shown, any_restore) = if restore.action != 0 {
+            let raw: Vec<char> = restore.chars[..restore.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c))
+                .collect();
+            (raw, restore.backspace as usize, true)
+        } else {
+            (
+                self.buf.to_full_string().chars().collect(),
+                self.buf.len(),
+                false,
+            )
+        };
+        let mut hyphen_text = std::mem::take(&mut self.hyphen_text);
+        hyphen_text.extend(raw);
+        hyphen_text.push('-');
+        let hyphen_shown = self.hyphen_shown + shown + 1;
+        let hyphen_restore = self.hyphen_restore || any_restore;
+
+        self.auto_capitalize_used = false;
+        self.clear();
+        self.word_history.clear();
+        self.spaces_after_commit = 0;
+        self.hyphen_text = hyphen_text;
+        self.hyphen_shown = hyphen_shown;
+        self.hyphen_restore = hyphen_restore;
+        // Still a prefix for shortcuts like "->" typed right after the word
+        self.shortcut_prefix.push('-');
+    }
+
+    /// Widen a word-end restore to the earlier parts of a hyphenated word
+    ///
+    /// English-looking parts go back to raw keystrokes, Vietnamese parts stay
+    /// ("ưell-known" → "well-known", "việt-tẽt" → "việt-text"). `space` appends the space like
+    /// `try_auto_restore_on_space`. Too long to fit one `Result`: `result` as is.
+    fn finish_hyphen_compound(&self, result: Result, space: bool) -> Result {
+        if self.hyphen_shown == 0 || (result.action == 0 && !self.hyphen_restore) {
+            return result;
+        }
+        let mut output = self.hyphen_text.clone();
+        if result.action != 0 {
+            output.extend(
+                result.chars[..result.count as usize]
+                    .iter()
+                    .filter_map(|&c| char::from_u32(c)),
+            );
+        } else {
+            output.extend(self.buf.to_full_string().chars());
+            if space {
+                output.push(' ');
+            }
+        }
+        let backspace = self.hyphen_shown + self.buf.len();
+        if output.len() > MAX || backspace > u8::MAX as usize {
+            return result;
+        }
+        Result::restore(backspace as u8, &output)
+    }
+
+    fn reset_hyphen_compound(&mut self) {
+        self.hyphen_text.clear();
+        self.hyphen_shown = 0;
+        self.hyphen_restore = false;
+    }
+
+    /// Raw keystrokes to revert to if the buffer can't be Vietnamese
+    ///
+    /// Used by `revert_invalid`: no English heuristics, a transformed word that is
+    /// structurally invalid ("pởe" from "power") goes back to exactly what was typed.
+    fn should_revert_invalid(&self) -> Option<Vec<char>> {
+        if !self.revert_invalid || self.raw_input.is_empty() {
+            return None;
+        }
+
+        let has_transforms = self
+            .buf
+            .iter()
+            .any(|c| c.tone > 0 || c.mark > 0 || c.stroke);
+        if !has_transforms || !self.is_buffer_invalid_vietnamese() {
+            return None;
+        }
+
+        // "đi's": only the stem before the clitic has to be Vietnamese
+        if self.ends_with_clitic() {
+            let stem = self.buf.iter().take_while(|c| c.key != keys::QUOTE);
+            let (keys, tones): (Vec<u16>, Vec<u8>) = stem.map(|c| (c.key, c.tone)).unzip();
+            if validation::is_valid_with_tones(&keys, &tones) {
+                return None;
+            }
+        }
+
+        let raw_chars: Vec<char> = self
+            .raw_input
+            .iter()
+            .filter_map(|&(key, caps, shift)| utils::key_to_char_ext(key, caps, shift))
+            .collect();
+        (!raw_chars.is_empty()).then_some(raw_chars)
+    }
+
     /// Restore buffer to raw ASCII (undo all Vietnamese transforms)
     ///
     /// Called when ESC is pressed. Replaces transformed output with original keystrokes.
@@ -4451,7 +7129,7 @@ impl Engine {
         // Backspace count = current buffer length (displayed chars)
         let backspace = self.buf.len() as u8;
 
-        Result::send(backspace, &raw_chars)
+        Result::restore(backspace, &raw_chars)
     }
 
     /// Restore raw_input from buffer (for ESC restore to work after backspace-restore)
@@ -4670,4 +7348,19 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_isolate_keeps_inner_spaces() {
+        // Only the committing space moves after the PDI; spaces inside the
+        // restored text stay in the isolated span
+        let e = Engine::new();
+        let restore = super::Result::restore(3, &['a', ' ', 'b', ' ']);
+        let r = e.isolate_committed_word(restore);
+        let out: String = r.chars[..r.count as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        assert_eq!(out, "\u{2066}a b\u{2069} ");
+        assert_eq!(r.backspace, 3);
+    }
 }
@@ -4,13 +4,31 @@
 //! Shortcuts can be specific to input methods (Telex/VNI) or apply to all.
 
 use super::buffer::MAX;
-use std::collections::HashMap;
+use crate::utils;
+use std::collections::{HashMap, HashSet};
 
 /// Maximum replacement length in UTF-32 codepoints (matches Result.chars array size)
 /// This limit ensures replacement fits in the FFI result buffer.
 /// Note: Vietnamese characters with diacritics (ồ, ế, ẫ) count as 1 codepoint each.
 pub const MAX_REPLACEMENT_LEN: usize = MAX - 1; // -1 to leave room for trailing space
 
+/// Longest replacement stored, in codepoints. Output beyond `MAX_REPLACEMENT_LEN`
+/// is truncated unless the engine's overflow mode splits it (`Engine::set_overflow_mode`).
+pub const MAX_STORED_REPLACEMENT_LEN: usize = 1024;
+
+/// Vietnamese typographic symbols (opt-in via `ShortcutTable::add_symbol_defaults`)
+///
+/// Triggers end with ')' so they fire immediately when the closing paren is typed.
+pub const SYMBOL_DEFAULTS: &[(&str, &str)] = &[
+    ("(dong)", "₫"), // đồng currency sign
+    ("(do)", "°"),   // độ (degree)
+];
+
+/// Break chars that fire word-boundary shortcuts by default (space only)
+///
+/// Enter is '\n' and Tab is '\t' when passed to `ShortcutTable::set_terminators`.
+pub const DEFAULT_TERMINATORS: &[char] = &[' '];
+
 /// Input method that shortcut applies to
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum InputMethod {
@@ -39,6 +57,9 @@ pub enum CaseMode {
     Exact,
     /// Match case of trigger: "VN" → "VIỆT NAM", "vn" → "Việt Nam"
     MatchCase,
+    /// Like MatchCase, but a capitalized trigger title-cases every word:
+    /// "hn" → "hà nội", "Hn" → "Hà Nội", "HN" → "HÀ NỘI"
+    TitleCase,
 }
 
 /// A single shortcut entry
@@ -56,24 +77,32 @@ pub struct Shortcut {
     pub enabled: bool,
     /// Which input method this shortcut applies to
     pub input_method: InputMethod,
+    /// Fire while typing as soon as the trigger is complete (opt-in)
+    pub instant: bool,
+    /// Computes the replacement from the typed trigger at match time
+    /// (see `Shortcut::with_fn`); `replacement` is unused when set
+    pub replace_fn: Option<fn(&str) -> String>,
 }
 
 impl Shortcut {
-    /// Validate and truncate replacement if it exceeds MAX_REPLACEMENT_LEN.
+    /// Validate and truncate replacement if it exceeds MAX_STORED_REPLACEMENT_LEN.
     /// Counts UTF-32 codepoints (Vietnamese diacritics = 1 codepoint each).
     fn validate_replacement(replacement: &str) -> String {
         let char_count = replacement.chars().count();
-        if char_count <= MAX_REPLACEMENT_LEN {
+        if char_count <= MAX_STORED_REPLACEMENT_LEN {
             replacement.to_string()
         } else {
-            // Truncate to MAX_REPLACEMENT_LEN codepoints
-            replacement.chars().take(MAX_REPLACEMENT_LEN).collect()
+            // Truncate to MAX_STORED_REPLACEMENT_LEN codepoints
+            replacement
+                .chars()
+                .take(MAX_STORED_REPLACEMENT_LEN)
+                .collect()
         }
     }
 
     /// Create a new shortcut with word boundary trigger (applies to all input methods)
     /// Issue #86: Case-insensitive matching, smart case output (ko→không, KO→KHÔNG, Ko→Không)
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (63) codepoints if too long.
+    /// Replacement is truncated to MAX_STORED_REPLACEMENT_LEN (1024) codepoints if too long.
     pub fn new(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
@@ -82,12 +111,14 @@ impl Shortcut {
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::All,
+            instant: false,
+            replace_fn: None,
         }
     }
 
     /// Create an immediate trigger shortcut (applies to all input methods).
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (63) codepoints if too long.
+    /// Replacement is truncated to MAX_STORED_REPLACEMENT_LEN (1024) codepoints if too long.
     pub fn immediate(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
@@ -96,12 +127,14 @@ impl Shortcut {
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::All,
+            instant: false,
+            replace_fn: None,
         }
     }
 
     /// Create a Telex-specific shortcut with immediate trigger.
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (63) codepoints if too long.
+    /// Replacement is truncated to MAX_STORED_REPLACEMENT_LEN (1024) codepoints if too long.
     pub fn telex(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
@@ -110,12 +143,14 @@ impl Shortcut {
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::Telex,
+            instant: false,
+            replace_fn: None,
         }
     }
 
     /// Create a VNI-specific shortcut with immediate trigger.
     /// Issue #86: Case-insensitive matching, smart case output
-    /// Replacement is truncated to MAX_REPLACEMENT_LEN (63) codepoints if too long.
+    /// Replacement is truncated to MAX_STORED_REPLACEMENT_LEN (1024) codepoints if too long.
     pub fn vni(trigger: &str, replacement: &str) -> Self {
         Self {
             trigger: trigger.to_lowercase(), // Store lowercase for case-insensitive matching
@@ -124,6 +159,31 @@ impl Shortcut {
             case_mode: CaseMode::MatchCase, // Smart case transformation
             enabled: true,
             input_method: InputMethod::Vni,
+            instant: false,
+            replace_fn: None,
+        }
+    }
+
+    /// Create a word-boundary shortcut whose replacement is computed at match time
+    ///
+    /// `f` gets the trigger as typed ("S100" keeps its case) and returns the
+    /// replacement, used as is (no case transform). For dynamic expansions like
+    /// number-to-words or unit conversion.
+    ///
+    /// `f` must be a pure function: it may run on every match, so it should be
+    /// fast, have no side effects, and return the same output for the same input.
+    pub fn with_fn(trigger: &str, f: fn(&str) -> String) -> Self {
+        let mut shortcut = Self::new(trigger, "");
+        shortcut.case_mode = CaseMode::Exact;
+        shortcut.replace_fn = Some(f);
+        shortcut
+    }
+
+    /// Replacement for the trigger as typed (`replace_fn` output if set)
+    fn replacement_for(&self, typed: &str) -> String {
+        match self.replace_fn {
+            Some(f) => Self::validate_replacement(&f(typed)),
+            None => self.replacement.clone(),
         }
     }
 
@@ -133,6 +193,21 @@ impl Shortcut {
         self
     }
 
+    /// Title-case every word of the replacement when the trigger is capitalized
+    pub fn title_case(mut self) -> Self {
+        self.case_mode = CaseMode::TitleCase;
+        self
+    }
+
+    /// Builder: fire as soon as the trigger is typed, without waiting for space
+    ///
+    /// Explicit opt-in for letter triggers ("teh" → "the"), since an instant
+    /// trigger that is a prefix of a longer word would fire mid-word.
+    pub fn instant(mut self) -> Self {
+        self.instant = true;
+        self
+    }
+
     /// Check if shortcut applies to given input method
     ///
     /// - If shortcut is for `All`: matches any method
@@ -165,12 +240,28 @@ pub struct ShortcutMatch {
 }
 
 /// Shortcut table manager
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ShortcutTable {
     /// Shortcuts indexed by trigger (lowercase)
     shortcuts: HashMap<String, Shortcut>,
     /// Sorted triggers by length (longest first) for matching
     sorted_triggers: Vec<String>,
+    /// Longest trigger length in chars (0 when empty)
+    max_trigger_len: usize,
+    /// Proper prefixes of enabled triggers, for `has_prefix`
+    prefixes: HashSet<String>,
+    /// Break chars that fire word-boundary shortcuts
+    terminators: Vec<char>,
+    /// Also match with diacritics stripped from both sides ("việt" matches "viet")
+    accent_insensitive: bool,
+    /// Cut match output to MAX_REPLACEMENT_LEN so it fits one result
+    truncate_output: bool,
+}
+
+impl Default for ShortcutTable {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ShortcutTable {
@@ -178,6 +269,11 @@ impl ShortcutTable {
         Self {
             shortcuts: HashMap::new(),
             sorted_triggers: vec![],
+            max_trigger_len: 0,
+            prefixes: HashSet::new(),
+            truncate_output: true,
+            terminators: DEFAULT_TERMINATORS.to_vec(),
+            accent_insensitive: false,
         }
     }
 
@@ -227,6 +323,20 @@ impl ShortcutTable {
         table
     }
 
+    /// Add Vietnamese symbol shortcuts from SYMBOL_DEFAULTS: "(dong)" → "₫", "(do)" → "°"
+    pub fn add_symbol_defaults(&mut self) {
+        for &(trigger, replacement) in SYMBOL_DEFAULTS {
+            self.add(Shortcut::immediate(trigger, replacement));
+        }
+    }
+
+    /// Remove Vietnamese symbol shortcuts added by `add_symbol_defaults`
+    pub fn remove_symbol_defaults(&mut self) {
+        for &(trigger, _) in SYMBOL_DEFAULTS {
+            self.remove(trigger);
+        }
+    }
+
     /// Add a shortcut
     pub fn add(&mut self, shortcut: Shortcut) {
         let trigger = shortcut.trigger.clone();
@@ -243,6 +353,57 @@ impl ShortcutTable {
         result
     }
 
+    /// Set which break chars fire word-boundary shortcuts
+    ///
+    /// Use '\n' for Enter and '\t' for Tab. Default: `DEFAULT_TERMINATORS` (space).
+    /// E.g., `&[' ', '\n']` expands on Enter too; leaving out '(' keeps "fn(" as typed.
+    /// Not affected by `clear`.
+    pub fn set_terminators(&mut self, terminators: &[char]) {
+        self.terminators = terminators.to_vec();
+    }
+
+    /// Break chars that fire word-boundary shortcuts
+    pub fn terminators(&self) -> &[char] {
+        &self.terminators
+    }
+
+    /// Check if `ch` fires word-boundary shortcuts
+    pub fn is_terminator(&self, ch: char) -> bool {
+        self.terminators.contains(&ch)
+    }
+
+    /// Set whether triggers also match with diacritics ignored
+    ///
+    /// When true, a buffer that doesn't match exactly is compared ASCII-folded
+    /// against folded triggers, so "việt" fires trigger "viet". Exact matches win.
+    /// Not affected by `clear`.
+    pub fn set_accent_insensitive(&mut self, enabled: bool) {
+        self.accent_insensitive = enabled;
+    }
+
+    /// Set whether match output is cut to `MAX_REPLACEMENT_LEN` codepoints
+    ///
+    /// Default true. The engine turns it off when its overflow mode can deliver
+    /// longer output. Not affected by `clear`.
+    pub fn set_truncate_output(&mut self, enabled: bool) {
+        self.truncate_output = enabled;
+    }
+
+    /// Check if a shortcut exists for `trigger`
+    ///
+    /// Case-insensitive like matching: triggers are stored lowercase.
+    pub fn contains(&self, trigger: &str) -> bool {
+        self.shortcuts.contains_key(&trigger.to_lowercase())
+    }
+
+    /// Check if `buffer` is a proper prefix of some enabled trigger
+    ///
+    /// Case-insensitive like matching. With trigger "vn", "v" is a prefix
+    /// but "vn" itself is not.
+    pub fn has_prefix(&self, buffer: &str) -> bool {
+        self.prefixes.contains(&buffer.to_lowercase())
+    }
+
     /// Check if buffer matches any shortcut (for any input method)
     ///
     /// Returns (trigger, shortcut) if match found
@@ -270,6 +431,21 @@ impl ShortcutTable {
                 }
             }
         }
+
+        if !self.accent_insensitive {
+            return None;
+        }
+        // Accent-insensitive fallback: folding keeps char counts, so backspace stays right
+        let buffer_folded = utils::to_ascii_folded(&buffer_lower);
+        for trigger in &self.sorted_triggers {
+            if buffer_folded == utils::to_ascii_folded(trigger) {
+                if let Some(shortcut) = self.shortcuts.get(trigger) {
+                    if shortcut.enabled && shortcut.applies_to(method) {
+                        return Some((trigger, shortcut));
+                    }
+                }
+            }
+        }
         None
     }
 
@@ -312,7 +488,8 @@ impl ShortcutTable {
 
         match shortcut.condition {
             TriggerCondition::Immediate => {
-                let output = self.apply_case(buffer, &shortcut.replacement, shortcut.case_mode);
+                let replacement = shortcut.replacement_for(buffer);
+                let output = self.apply_case(buffer, &replacement, shortcut.case_mode);
                 Some(ShortcutMatch {
                     // Use char count, not byte length (UTF-8 chars like đ are multi-byte)
                     backspace_count: trigger.chars().count(),
@@ -322,8 +499,8 @@ impl ShortcutTable {
             }
             TriggerCondition::OnWordBoundary => {
                 if is_word_boundary {
-                    let mut output =
-                        self.apply_case(buffer, &shortcut.replacement, shortcut.case_mode);
+                    let replacement = shortcut.replacement_for(buffer);
+                    let mut output = self.apply_case(buffer, &replacement, shortcut.case_mode);
                     // Append the trigger key (space, etc.)
                     if let Some(ch) = key_char {
                         output.push(ch);
@@ -341,11 +518,40 @@ impl ShortcutTable {
         }
     }
 
-    /// Apply case transformation based on mode
+    /// Try to match an instant shortcut while the word is still being typed
+    ///
+    /// Only shortcuts built with `Shortcut::instant` match; the trigger key is
+    /// part of the trigger, so it is never included in the output.
+    pub fn try_match_instant(&self, buffer: &str, method: InputMethod) -> Option<ShortcutMatch> {
+        let (trigger, shortcut) = self.lookup_for_method(buffer, method)?;
+        if !shortcut.instant {
+            return None;
+        }
+        Some(ShortcutMatch {
+            backspace_count: trigger.chars().count(),
+            output: self.apply_case(
+                buffer,
+                &shortcut.replacement_for(buffer),
+                shortcut.case_mode,
+            ),
+            include_trigger_key: false,
+        })
+    }
+
+    /// Apply case transformation based on mode, truncating if `truncate_output`
     fn apply_case(&self, trigger: &str, replacement: &str, mode: CaseMode) -> String {
+        let cased = self.apply_case_mode(trigger, replacement, mode);
+        if self.truncate_output && cased.chars().count() > MAX_REPLACEMENT_LEN {
+            cased.chars().take(MAX_REPLACEMENT_LEN).collect()
+        } else {
+            cased
+        }
+    }
+
+    fn apply_case_mode(&self, trigger: &str, replacement: &str, mode: CaseMode) -> String {
         match mode {
             CaseMode::Exact => replacement.to_string(),
-            CaseMode::MatchCase => {
+            CaseMode::MatchCase | CaseMode::TitleCase => {
                 if trigger.chars().all(|c| c.is_uppercase()) {
                     // All uppercase → replacement all uppercase
                     replacement.to_uppercase()
@@ -355,11 +561,16 @@ impl ShortcutTable {
                     .map(|c| c.is_uppercase())
                     .unwrap_or(false)
                 {
-                    // First char uppercase → capitalize replacement
-                    let mut chars = replacement.chars();
-                    match chars.next() {
-                        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-                        None => String::new(),
+                    if mode == CaseMode::TitleCase {
+                        // First char uppercase → capitalize each word
+                        replacement
+                            .split(' ')
+                            .map(Self::capitalize)
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    } else {
+                        // First char uppercase → capitalize replacement
+                        Self::capitalize(replacement)
                     }
                 } else {
                     // Lowercase → keep replacement as-is
@@ -369,11 +580,44 @@ impl ShortcutTable {
         }
     }
 
+    /// Uppercase the first character of a word
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            None => String::new(),
+        }
+    }
+
     /// Rebuild sorted triggers list (longest first)
     fn rebuild_sorted_triggers(&mut self) {
         self.sorted_triggers = self.shortcuts.keys().cloned().collect();
         self.sorted_triggers
             .sort_by_key(|s| std::cmp::Reverse(s.len()));
+        self.max_trigger_len = self
+            .sorted_triggers
+            .iter()
+            .map(|t| t.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.prefixes = self
+            .shortcuts
+            .values()
+            .filter(|s| s.enabled)
+            .flat_map(|s| {
+                s.trigger
+                    .char_indices()
+                    .skip(1)
+                    .map(|(i, _)| s.trigger[..i].to_string())
+            })
+            .collect();
+    }
+
+    /// Length of the longest trigger in chars (0 when empty)
+    ///
+    /// Lets callers reject words that are too long to match without string work.
+    pub fn max_trigger_len(&self) -> usize {
+        self.max_trigger_len
     }
 
     /// Check if shortcut table is empty
@@ -386,10 +630,22 @@ impl ShortcutTable {
         self.shortcuts.len()
     }
 
+    /// Iterate over all shortcuts (longest trigger first)
+    ///
+    /// Order is stable while the table is unmodified, so callers can
+    /// address shortcuts by index (e.g. a settings UI listing).
+    pub fn iter(&self) -> impl Iterator<Item = &Shortcut> {
+        self.sorted_triggers
+            .iter()
+            .filter_map(|t| self.shortcuts.get(t))
+    }
+
     /// Clear all shortcuts
     pub fn clear(&mut self) {
         self.shortcuts.clear();
         self.sorted_triggers.clear();
+        self.max_trigger_len = 0;
+        self.prefixes.clear();
     }
 }
 
@@ -514,6 +770,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_title_case_matching() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("hn", "hà nội").title_case());
+
+        // Lowercase "hn" → "hà nội" (as-is)
+        assert_shortcut_match(
+            &table,
+            "hn",
+            Some(' '),
+            true,
+            "hà nội ",
+            2,
+            InputMethod::All,
+        );
+
+        // Title case "Hn" → "Hà Nội" (every word capitalized)
+        assert_shortcut_match(
+            &table,
+            "Hn",
+            Some(' '),
+            true,
+            "Hà Nội ",
+            2,
+            InputMethod::All,
+        );
+
+        // Uppercase "HN" → "HÀ NỘI"
+        assert_shortcut_match(
+            &table,
+            "HN",
+            Some(' '),
+            true,
+            "HÀ NỘI ",
+            2,
+            InputMethod::All,
+        );
+    }
+
     #[test]
     fn test_immediate_shortcut() {
         let table = table_with_immediate("w", "ư");
@@ -691,12 +986,29 @@ mod tests {
             "Test text should exceed limit"
         );
 
+        // Stored whole, but match output is cut to fit one result
         let shortcut = Shortcut::new("long", long_text);
-        let result_count = shortcut.replacement.chars().count();
+        assert_eq!(shortcut.replacement, long_text);
+        let mut table = ShortcutTable::new();
+        table.add(shortcut);
+        let m = table.try_match("long", Some(' '), true).unwrap();
+        let output: Vec<char> = m.output.chars().collect();
         assert_eq!(
-            result_count, MAX_REPLACEMENT_LEN,
+            output.len(),
+            MAX_REPLACEMENT_LEN + 1,
             "Should truncate to MAX_REPLACEMENT_LEN"
         );
+        assert_eq!(output.last(), Some(&' '), "Trigger key is kept");
+
+        // Without truncation the full replacement comes out
+        table.set_truncate_output(false);
+        let m = table.try_match("long", Some(' '), true).unwrap();
+        assert_eq!(m.output, format!("{} ", long_text));
+
+        // Storage itself is capped
+        let huge = "a".repeat(MAX_STORED_REPLACEMENT_LEN + 10);
+        let shortcut = Shortcut::new("huge", &huge);
+        assert_eq!(shortcut.replacement.len(), MAX_STORED_REPLACEMENT_LEN);
     }
 
     #[test]
@@ -837,4 +1149,149 @@ mod tests {
             InputMethod::All,
         );
     }
+
+    #[test]
+    fn test_iter() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("vn", "Việt Nam"));
+        table.add(Shortcut::telex("hcm", "Hồ Chí Minh"));
+
+        let items: Vec<_> = table.iter().collect();
+        assert_eq!(items.len(), table.len());
+        // Longest trigger first
+        assert_eq!(items[0].trigger, "hcm");
+        assert_eq!(items[0].input_method, InputMethod::Telex);
+        assert_eq!(items[1].trigger, "vn");
+        assert_eq!(items[1].input_method, InputMethod::All);
+    }
+
+    #[test]
+    fn test_instant_matching() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("teh", "the").instant());
+        table.add(Shortcut::new("vn", "Việt Nam"));
+
+        let m = table.try_match_instant("Teh", InputMethod::All).unwrap();
+        assert_eq!(m.output, "The");
+        assert_eq!(m.backspace_count, 3);
+        assert!(!m.include_trigger_key);
+
+        // Not opted in: only fires on word boundary
+        assert!(table.try_match_instant("vn", InputMethod::All).is_none());
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut table = table_with_shortcut("ko", "không");
+        assert!(table.contains("ko"));
+        assert!(table.contains("KO"));
+        assert!(!table.contains("k"));
+
+        table.remove("ko");
+        assert!(!table.contains("ko"));
+    }
+
+    #[test]
+    fn test_accent_insensitive() {
+        let mut table = table_with_shortcut("viet", "Việt Nam");
+        assert!(table.try_match("việt", Some(' '), true).is_none());
+
+        table.set_accent_insensitive(true);
+        let m = table.try_match("việt", Some(' '), true).unwrap();
+        assert_eq!(m.backspace_count, 4);
+        assert_eq!(m.output, "Việt Nam ");
+        assert!(table.try_match("VIỆT", Some(' '), true).is_some());
+        assert!(table.try_match("đi", Some(' '), true).is_none());
+
+        // Exact trigger wins over a folded one
+        table.add(Shortcut::new("việt", "tiếng Việt"));
+        let m = table.try_match("việt", Some(' '), true).unwrap();
+        assert_eq!(m.output, "tiếng Việt ");
+    }
+
+    #[test]
+    fn test_has_prefix() {
+        let mut table = table_with_shortcut("hcm", "Hồ Chí Minh");
+        assert!(table.has_prefix("h"));
+        assert!(table.has_prefix("HC"));
+        assert!(
+            !table.has_prefix("hcm"),
+            "full trigger is not a proper prefix"
+        );
+        assert!(!table.has_prefix("hx"));
+        assert!(!table.has_prefix(""));
+
+        table.remove("hcm");
+        assert!(!table.has_prefix("h"));
+    }
+
+    #[test]
+    fn test_terminators() {
+        let mut table = ShortcutTable::default();
+        assert_eq!(table.terminators(), DEFAULT_TERMINATORS);
+        assert!(table.is_terminator(' '));
+        assert!(!table.is_terminator('\n'));
+
+        table.set_terminators(&['\n', ',']);
+        assert!(!table.is_terminator(' '));
+        assert!(table.is_terminator(','));
+
+        // Survives clearing the shortcuts
+        table.clear();
+        assert!(table.is_terminator('\n'));
+    }
+
+    #[test]
+    fn test_max_trigger_len() {
+        let mut table = ShortcutTable::new();
+        assert_eq!(table.max_trigger_len(), 0);
+
+        table.add(Shortcut::new("vn", "Việt Nam"));
+        table.add(Shortcut::new("đc", "được"));
+        table.add(Shortcut::new("hcm", "Hồ Chí Minh"));
+        assert_eq!(table.max_trigger_len(), 3);
+
+        table.remove("hcm");
+        // Counted in chars, not bytes ("đc" is 3 bytes)
+        assert_eq!(table.max_trigger_len(), 2);
+
+        table.clear();
+        assert_eq!(table.max_trigger_len(), 0);
+    }
+
+    #[test]
+    fn test_with_fn_computes_replacement() {
+        fn spell(typed: &str) -> String {
+            match typed.trim_start_matches(['s', 'S']) {
+                "100" => "một trăm".to_string(),
+                n => n.to_string(),
+            }
+        }
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::with_fn("s100", spell));
+
+        let m = table.try_match("s100", Some(' '), true).unwrap();
+        assert_eq!(m.output, "một trăm ");
+        assert_eq!(m.backspace_count, 4);
+        // Case-insensitive lookup; the function sees the trigger as typed
+        let m = table.try_match("S100", Some(' '), true).unwrap();
+        assert_eq!(m.output, "một trăm ");
+        assert!(table.try_match("s100", None, false).is_none());
+    }
+
+    #[test]
+    fn test_symbol_defaults_add_and_remove() {
+        let mut table = ShortcutTable::new();
+        table.add(Shortcut::new("vn", "Việt Nam"));
+
+        table.add_symbol_defaults();
+        assert_eq!(table.len(), 1 + SYMBOL_DEFAULTS.len());
+        let m = table.try_match("(dong)", None, false).unwrap();
+        assert_eq!(m.output, "₫");
+        assert_eq!(m.backspace_count, 6);
+
+        table.remove_symbol_defaults();
+        assert_eq!(table.len(), 1, "User shortcuts are kept");
+        assert!(table.lookup("(dong)").is_none());
+    }
 }
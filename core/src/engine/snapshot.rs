@@ -0,0 +1,733 @@
+//! Engine State Snapshot
+//!
+//! Compact binary dump of the engine state for reproducing bug reports:
+//! a user attaches the blob, the maintainer loads it and replays the next keys.
+//!
+//! Format (little-endian), version 4:
+//! - `b"GNHS"` magic, `u8` version
+//! - settings: one byte per flag/mode, `u32` per timer, in `write_settings` order,
+//!   then the standalone-'w' / literal-"oo" initials and protected words
+//! - word state: buffer chars, raw input, pending positions, `last_transform`,
+//!   word flags, shortcut prefix, pending T9 letter and key timers, keys held
+//!   back by deferred emit, hyphenated compound text
+//! - word history, oldest first, with the multi-word chain length
+//!
+//! Not included: shortcuts and the output filter (host-registered, may hold
+//! function pointers), and output not yet picked up (`next_result`, `last_edit`).
+
+use super::buffer::{Buffer, Char};
+use super::{Engine, Transform};
+use crate::data::chars::{mark, tone};
+use crate::data::keys;
+use crate::input::t9;
+use crate::utils;
+
+const MAGIC: &[u8; 4] = b"GNHS";
+const VERSION: u8 = 4;
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn bool(&mut self, v: bool) {
+        self.0.push(v as u8);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn opt_u16(&mut self, v: Option<u16>) {
+        self.bool(v.is_some());
+        self.u16(v.unwrap_or(0));
+    }
+
+    fn str(&mut self, s: &str) {
+        self.u16(s.len() as u16);
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn keys(&mut self, keys: &[u16]) {
+        self.u16(keys.len() as u16);
+        for &key in keys {
+            self.u16(key);
+        }
+    }
+
+    fn raw_keys(&mut self, keys: &[(u16, bool, bool)]) {
+        self.u16(keys.len() as u16);
+        for &(key, caps, shift) in keys {
+            self.u16(key);
+            self.u8(caps as u8 | (shift as u8) << 1);
+        }
+    }
+
+    fn chars(&mut self, buf: &Buffer) {
+        self.u16(buf.len() as u16);
+        for c in buf.iter() {
+            self.u16(c.key);
+            self.u8(c.caps as u8 | (c.stroke as u8) << 1);
+            self.u8(c.tone);
+            self.u8(c.mark);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        self.u8().map(|b| b != 0)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn opt_u16(&mut self) -> Option<Option<u16>> {
+        let some = self.bool()?;
+        let v = self.u16()?;
+        Some(some.then_some(v))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn keys(&mut self) -> Option<Vec<u16>> {
+        (0..self.u16()?).map(|_| self.u16()).collect()
+    }
+
+    fn raw_keys(&mut self) -> Option<Vec<(u16, bool, bool)>> {
+        (0..self.u16()?)
+            .map(|_| {
+                let key = self.u16()?;
+                let bits = self.u8()?;
+                Some((key, bits & 1 != 0, bits & 2 != 0))
+            })
+            .collect()
+    }
+
+    fn chars(&mut self) -> Option<Buffer> {
+        let mut buf = Buffer::new();
+        for _ in 0..self.u16()? {
+            let key = self.u16()?;
+            let bits = self.u8()?;
+            let mut c = Char::new(key, bits & 1 != 0);
+            c.stroke = bits & 2 != 0;
+            c.tone = self.u8()?;
+            c.mark = self.u8()?;
+            buf.push(c);
+        }
+        Some(buf)
+    }
+}
+
+impl Engine {
+    /// Serialize the engine state into a compact binary blob (see module docs)
+    ///
+    /// Load it with `from_snapshot` to replay a bug report from the exact state.
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut w = Writer(Vec::with_capacity(128));
+        w.0.extend_from_slice(MAGIC);
+        w.u8(VERSION);
+        self.write_settings(&mut w);
+
+        // Word state
+        w.chars(&self.buf);
+        w.raw_keys(&self.raw_input);
+        w.opt_u16(self.pending_breve_pos.map(|p| p as u16));
+        w.opt_u16(self.pending_u_horn_pos.map(|p| p as u16));
+        w.opt_u16(self.reverted_run_key);
+        match self.last_transform {
+            None => w.u8(0),
+            Some(Transform::Mark(key, v)) => {
+                w.u8(1);
+                w.u16(key);
+                w.u8(v);
+            }
+            Some(Transform::Tone(key, v)) => {
+                w.u8(2);
+                w.u16(key);
+                w.u8(v);
+            }
+            Some(Transform::Stroke(key)) => {
+                w.u8(3);
+                w.u16(key);
+            }
+            Some(Transform::ShortPatternStroke) => w.u8(4),
+            Some(Transform::WAsVowel) => w.u8(5),
+            Some(Transform::WShortcutSkipped) => w.u8(6),
+        }
+        for flag in [
+            self.has_non_letter_prefix,
+            self.stroke_reverted,
+            self.had_mark_revert,
+            self.pending_mark_revert_pop,
+            self.had_any_transform,
+            self.had_vowel_triggered_circumflex,
+            self.restored_pending_clear,
+            self.pending_capitalize,
+            self.auto_capitalize_used,
+            self.in_code_span,
+            self.raw_current_word,
+            self.rtl_context,
+            self.shortcut_declined,
+            self.hyphen_restore,
+            self.apostrophe_stem_kept,
+        ] {
+            w.bool(flag);
+        }
+        w.u8(self.spaces_after_commit);
+        w.u32(self.idle_ms);
+        w.str(&self.shortcut_prefix);
+
+        // Pending T9 letter as (digit, selected letter), and key timers
+        match self.t9_cycle {
+            Some(cycle) => {
+                w.bool(true);
+                w.u16(cycle.digit);
+                w.bool(cycle.caps);
+                w.u16(cycle.key());
+            }
+            None => w.bool(false),
+        }
+        w.u32(self.t9_idle_ms);
+        w.opt_u16(self.last_timed_key.map(|(key, _)| key));
+        w.u32(self.last_timed_key.map_or(0, |(_, ms)| ms));
+        w.raw_keys(&self.deferred_keys);
+        w.str(&self.hyphen_text.iter().collect::<String>());
+        w.u16(self.hyphen_shown as u16);
+
+        // Word history, oldest first
+        let history = &self.word_history;
+        w.u8(history.len as u8);
+        for n in (0..history.len).rev() {
+            if let Some(buf) = history.recent(n) {
+                w.chars(buf);
+            }
+        }
+        w.u8(history.chain as u8);
+        w.0
+    }
+
+    /// Rebuild an engine from a `snapshot` blob
+    ///
+    /// State not in the snapshot starts from `Engine::new()` defaults.
+    /// Returns None for a blob with a wrong magic/version, truncated data or
+    /// out-of-range values (unknown modes, positions past the buffer).
+    pub fn from_snapshot(data: &[u8]) -> Option<Engine> {
+        let mut r = Reader(data);
+        if r.take(4)? != MAGIC || r.u8()? != VERSION {
+            return None;
+        }
+        let mut e = Engine::new();
+        e.read_settings(&mut r)?;
+
+        e.buf = r.chars()?;
+        e.raw_input = r.raw_keys()?;
+        e.pending_breve_pos = r.opt_u16()?.map(usize::from);
+        e.pending_u_horn_pos = r.opt_u16()?.map(usize::from);
+        e.reverted_run_key = r.opt_u16()?;
+        e.last_transform = match r.u8()? {
+            0 => None,
+            1 => Some(Transform::Mark(r.u16()?, r.u8()?)),
+            2 => Some(Transform::Tone(r.u16()?, r.u8()?)),
+            3 => Some(Transform::Stroke(r.u16()?)),
+            4 => Some(Transform::ShortPatternStroke),
+            5 => Some(Transform::WAsVowel),
+            6 => Some(Transform::WShortcutSkipped),
+            _ => return None,
+        };
+        for flag in [
+            &mut e.has_non_letter_prefix,
+            &mut e.stroke_reverted,
+            &mut e.had_mark_revert,
+            &mut e.pending_mark_revert_pop,
+            &mut e.had_any_transform,
+            &mut e.had_vowel_triggered_circumflex,
+            &mut e.restored_pending_clear,
+            &mut e.pending_capitalize,
+            &mut e.auto_capitalize_used,
+            &mut e.in_code_span,
+            &mut e.raw_current_word,
+            &mut e.rtl_context,
+            &mut e.shortcut_declined,
+            &mut e.hyphen_restore,
+            &mut e.apostrophe_stem_kept,
+        ] {
+            *flag = r.bool()?;
+        }
+        e.spaces_after_commit = r.u8()?;
+        e.idle_ms = r.u32()?;
+        e.shortcut_prefix = r.str()?;
+
+        if r.bool()? {
+            let mut cycle = t9::Cycle::new(r.u16()?, r.bool()?)?;
+            let key = r.u16()?;
+            for _ in 0..t9::letters(cycle.digit)?.len() {
+                if cycle.key() == key {
+                    break;
+                }
+                cycle.advance();
+            }
+            if cycle.key() != key {
+                return None;
+            }
+            e.t9_cycle = Some(cycle);
+        }
+        e.t9_idle_ms = r.u32()?;
+        let timed_key = r.opt_u16()?;
+        let timed_ms = r.u32()?;
+        e.last_timed_key = timed_key.map(|key| (key, timed_ms));
+        e.deferred_keys = r.raw_keys()?;
+        e.hyphen_text = r.str()?.chars().collect();
+        e.hyphen_shown = r.u16()? as usize;
+
+        for _ in 0..r.u8()? {
+            e.word_history.push(r.chars()?);
+        }
+        let chain = r.u8()? as usize;
+        if chain > e.word_history.len {
+            return None;
+        }
+        e.word_history.chain = chain;
+        e.is_consistent().then_some(e)
+    }
+
+    /// Loaded values are in range: modes known, chars well-formed, positions
+    /// inside the buffer (a crafted blob must not make the next key panic)
+    fn is_consistent(&self) -> bool {
+        let modes_ok = self.method <= 1
+            && self.layout <= keys::LAYOUT_LINUX
+            && self.oa_oe_style <= 1
+            && self.ctrl_behavior <= 1
+            && self.esc_behavior <= 2
+            && self.overflow_mode <= super::OVERFLOW_ERROR
+            && self.iy_preference <= 2;
+        let key_ok = |key: u16| utils::key_to_char(key, false).is_some();
+        let chars_ok = |buf: &Buffer| {
+            buf.iter()
+                .all(|c| key_ok(c.key) && c.tone <= tone::HORN && c.mark <= mark::NANG)
+        };
+        let in_buf = |pos: Option<usize>| pos.is_none_or(|p| p < self.buf.len());
+        modes_ok
+            && chars_ok(&self.buf)
+            && (0..self.word_history.len)
+                .filter_map(|n| self.word_history.recent(n))
+                .all(chars_ok)
+            && self.raw_input.iter().all(|&(key, _, _)| key_ok(key))
+            && self.deferred_keys.iter().all(|&(key, _, _)| key_ok(key))
+            && in_buf(self.pending_breve_pos)
+            && in_buf(self.pending_u_horn_pos)
+    }
+
+    fn write_settings(&self, w: &mut Writer) {
+        w.u8(self.method);
+        w.u8(self.layout);
+        w.u8(self.oa_oe_style);
+        w.u8(self.ctrl_behavior);
+        w.u8(self.esc_behavior);
+        w.u8(self.overflow_mode);
+        w.u8(self.capitalize_triggers);
+        w.u8(self.iy_preference);
+        for flag in self.setting_flags() {
+            w.bool(flag);
+        }
+        w.u32(self.idle_commit_ms);
+        w.u32(self.t9_timeout_ms);
+        w.u32(self.repeat_filter_ms);
+        w.u32(self.mark_revert_gap_ms);
+        w.u32(self.min_restore_len as u32);
+
+        w.bool(self.w_valid_initials.is_some());
+        w.keys(self.w_valid_initials.as_deref().unwrap_or_default());
+        w.keys(&self.double_o_literal_initials);
+        let mut protected: Vec<&String> = self.protected_words.iter().collect();
+        protected.sort();
+        w.u16(protected.len() as u16);
+        for word in protected {
+            w.str(word);
+        }
+    }
+
+    fn read_settings(&mut self, r: &mut Reader) -> Option<()> {
+        self.method = r.u8()?;
+        self.layout = r.u8()?;
+        self.oa_oe_style = r.u8()?;
+        self.ctrl_behavior = r.u8()?;
+        self.esc_behavior = r.u8()?;
+        self.overflow_mode = r.u8()?;
+        self.capitalize_triggers = r.u8()?;
+        self.iy_preference = r.u8()?;
+        for flag in [
+            &mut self.enabled,
+            &mut self.t9,
+            &mut self.skip_w_shortcut,
+            &mut self.esc_restore_enabled,
+            &mut self.free_tone_enabled,
+            &mut self.modern_tone,
+            &mut self.english_auto_restore,
+            &mut self.auto_capitalize,
+            &mut self.code_span,
+            &mut self.diff_output,
+            &mut self.apostrophe_in_word,
+            &mut self.revert_invalid,
+            &mut self.smart_backspace,
+            &mut self.strict_finals,
+            &mut self.defer_breve,
+            &mut self.bidi_isolation,
+            &mut self.eager_uo_horn,
+            &mut self.vni_numpad,
+            &mut self.shortcut_exact_boundary,
+            &mut self.full_replace,
+            &mut self.deferred_emit,
+            &mut self.hyphen_in_word,
+            &mut self.backspace_restore,
+            &mut self.simple_mode,
+            &mut self.nfd_output,
+            &mut self.no_echo,
+        ] {
+            *flag = r.bool()?;
+        }
+        self.idle_commit_ms = r.u32()?;
+        self.t9_timeout_ms = r.u32()?;
+        self.repeat_filter_ms = r.u32()?;
+        self.mark_revert_gap_ms = r.u32()?;
+        self.min_restore_len = r.u32()? as usize;
+
+        let custom_w_initials = r.bool()?;
+        let w_initials = r.keys()?;
+        self.w_valid_initials = custom_w_initials.then_some(w_initials);
+        self.double_o_literal_initials = r.keys()?;
+        for _ in 0..r.u16()? {
+            self.protected_words.insert(r.str()?);
+        }
+        self.shortcuts
+            .set_truncate_output(self.overflow_mode == super::OVERFLOW_TRUNCATE);
+        Some(())
+    }
+
+    /// On/off settings in snapshot order (must match `read_settings`)
+    fn setting_flags(&self) -> [bool; 26] {
+        [
+            self.enabled,
+            self.t9,
+            self.skip_w_shortcut,
+            self.esc_restore_enabled,
+            self.free_tone_enabled,
+            self.modern_tone,
+            self.english_auto_restore,
+            self.auto_capitalize,
+            self.code_span,
+            self.diff_output,
+            self.apostrophe_in_word,
+            self.revert_invalid,
+            self.smart_backspace,
+            self.strict_finals,
+            self.defer_breve,
+            self.bidi_isolation,
+            self.eager_uo_horn,
+            self.vni_numpad,
+            self.shortcut_exact_boundary,
+            self.full_replace,
+            self.deferred_emit,
+            self.hyphen_in_word,
+            self.backspace_restore,
+            self.simple_mode,
+            self.nfd_output,
+            self.no_echo,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::keys;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut e = Engine::new();
+        e.set_english_auto_restore(true);
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T] {
+            e.on_key(key, false, false);
+        }
+
+        let blob = e.snapshot();
+        let mut restored = Engine::from_snapshot(&blob).unwrap();
+        assert_eq!(restored.snapshot(), blob);
+        assert_eq!(restored.get_buffer_string(), "viêt");
+        assert_eq!(restored.raw_input_string(), "vieet");
+
+        // Replaying the next key behaves the same
+        let a = e.on_key(keys::J, false, false);
+        let b = restored.on_key(keys::J, false, false);
+        assert_eq!(a.chars, b.chars);
+        assert_eq!(e.get_buffer_string(), restored.get_buffer_string());
+    }
+
+    type Apply = fn(&mut Engine);
+
+    /// One non-default value per setting, applied on a fresh engine
+    const SETTINGS: &[(&str, Apply)] = &[
+        ("method", |e| e.set_method(1)),
+        ("t9", |e| e.set_method(4)),
+        ("layout", |e| e.set_layout(keys::LAYOUT_WINDOWS)),
+        ("oa_oe_style", |e| e.set_oa_oe_style(0)),
+        ("ctrl_behavior", |e| e.set_ctrl_behavior(1)),
+        ("esc_behavior", |e| e.set_esc_behavior(1)),
+        ("overflow_mode", |e| e.set_overflow_mode(1)),
+        ("enabled", |e| e.set_enabled(false)),
+        ("skip_w_shortcut", |e| e.set_skip_w_shortcut(true)),
+        ("esc_restore", |e| e.set_esc_restore(true)),
+        ("free_tone", |e| e.set_free_tone(true)),
+        ("modern_tone", |e| e.set_modern_tone(false)),
+        ("english_auto_restore", |e| e.set_english_auto_restore(true)),
+        ("auto_capitalize", |e| e.set_auto_capitalize(true)),
+        ("code_span", |e| e.set_code_span(true)),
+        ("diff_output", |e| e.set_diff_output(true)),
+        ("apostrophe_in_word", |e| e.set_apostrophe_in_word(true)),
+        ("revert_invalid", |e| e.set_revert_invalid(true)),
+        ("smart_backspace", |e| e.set_smart_backspace(true)),
+        ("strict_finals", |e| e.set_strict_finals(true)),
+        ("defer_breve", |e| e.set_defer_breve(true)),
+        ("bidi_isolation", |e| e.set_bidi_isolation(true)),
+        ("eager_uo_horn", |e| e.set_eager_uo_horn(true)),
+        ("idle_commit_ms", |e| e.set_idle_commit_ms(500)),
+        ("t9_timeout_ms", |e| e.set_t9_timeout_ms(700)),
+        ("repeat_filter_ms", |e| e.set_repeat_filter_ms(30)),
+        ("w_valid_initials", |e| {
+            e.set_w_valid_initials(&[keys::T, keys::N])
+        }),
+        ("w_valid_initials_empty", |e| e.set_w_valid_initials(&[])),
+        ("vni_numpad", |e| e.set_vni_numpad(true)),
+        ("shortcut_exact_boundary", |e| {
+            e.set_shortcut_require_exact_boundary(true)
+        }),
+        ("full_replace", |e| e.set_full_replace(true)),
+        ("deferred_emit", |e| e.set_deferred_emit(true)),
+        ("hyphen_in_word", |e| e.set_hyphen_in_word(true)),
+        ("backspace_restore", |e| e.set_backspace_restore(false)),
+        ("simple_mode", |e| e.set_simple_mode(true)),
+        ("nfd_output", |e| e.set_nfd_output(true)),
+        ("no_echo", |e| e.set_no_echo(true)),
+        ("capitalize_triggers", |e| e.set_capitalize_triggers(0)),
+        ("iy_preference", |e| e.set_iy_preference(1)),
+        ("mark_revert_gap_ms", |e| e.set_mark_revert_gap_ms(120)),
+        ("min_restore_len", |e| e.set_min_restore_len(4)),
+        ("double_o_literal_initials", |e| {
+            e.set_double_o_literal_initials(&[keys::C])
+        }),
+        ("protected_words", |e| {
+            e.add_protected_word("git");
+            e.add_protected_word("cargo");
+        }),
+    ];
+
+    #[test]
+    fn test_snapshot_keeps_every_setting() {
+        let default = Engine::new().snapshot();
+        let mut all = Engine::new();
+        for (name, apply) in SETTINGS {
+            let mut e = Engine::new();
+            apply(&mut e);
+            let blob = e.snapshot();
+            assert_ne!(blob, default, "{} not in snapshot", name);
+            let restored = Engine::from_snapshot(&blob).unwrap();
+            assert_eq!(restored.snapshot(), blob, "{} lost on load", name);
+            apply(&mut all);
+        }
+        let blob = all.snapshot();
+        assert_eq!(Engine::from_snapshot(&blob).unwrap().snapshot(), blob);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_word_history() {
+        let mut e = Engine::new();
+        for key in [
+            keys::V,
+            keys::I,
+            keys::E,
+            keys::E,
+            keys::T,
+            keys::J,
+            keys::SPACE,
+        ] {
+            e.on_key(key, false, false);
+        }
+
+        let mut restored = Engine::from_snapshot(&e.snapshot()).unwrap();
+        // Backspace-after-space reopens the committed word on both engines
+        for engine in [&mut e, &mut restored] {
+            engine.on_key(keys::DELETE, false, false);
+            engine.on_key(keys::S, false, false);
+        }
+        assert_eq!(restored.get_buffer_string(), e.get_buffer_string());
+        assert_eq!(restored.get_buffer_string(), "viết");
+    }
+
+    #[test]
+    fn test_snapshot_keeps_pending_t9_letter() {
+        let mut e = Engine::new();
+        e.set_method(4);
+        for key in [keys::N7, keys::N7, keys::N7] {
+            e.on_key(key, false, false);
+        }
+
+        let blob = e.snapshot();
+        let mut restored = Engine::from_snapshot(&blob).unwrap();
+        assert_eq!(restored.snapshot(), blob);
+        let a = e.tick(1000);
+        let b = restored.tick(1000);
+        assert_eq!(a.chars, b.chars);
+        assert_eq!(restored.get_buffer_string(), "r");
+    }
+
+    #[test]
+    fn test_snapshot_keeps_deferred_and_hyphen_state() {
+        let mut e = Engine::new();
+        e.set_deferred_emit(true);
+        e.set_hyphen_in_word(true);
+        for key in [keys::T, keys::O, keys::O, keys::MINUS, keys::V, keys::I] {
+            e.on_key(key, false, false);
+        }
+
+        assert!(!e.deferred_keys.is_empty() && !e.hyphen_text.is_empty());
+
+        let blob = e.snapshot();
+        let mut restored = Engine::from_snapshot(&blob).unwrap();
+        assert_eq!(restored.snapshot(), blob);
+        for key in [keys::E, keys::E, keys::T, keys::SPACE] {
+            let a = e.on_key(key, false, false);
+            let b = restored.on_key(key, false, false);
+            assert_eq!((a.action, a.backspace), (b.action, b.backspace));
+            assert_eq!(a.chars[..a.count as usize], b.chars[..b.count as usize]);
+        }
+    }
+
+    /// Snapshots of engines in a few mid-word states, as fuzzing seeds
+    fn seed_snapshots() -> Vec<Vec<u8>> {
+        let mut seeds = Vec::new();
+        for (method, input) in [(0, "dduwow"), (0, "vieetj hoaf "), (1, "tra8"), (0, "huow")] {
+            let mut e = Engine::new();
+            e.set_method(method);
+            e.set_defer_breve(true);
+            e.set_hyphen_in_word(true);
+            crate::utils::type_word(&mut e, input);
+            seeds.push(e.snapshot());
+        }
+        let mut e = Engine::new();
+        e.set_method(4);
+        e.on_key(keys::N7, false, false);
+        seeds.push(e.snapshot());
+        seeds
+    }
+
+    /// Load a blob and drive the engine through keys and editing APIs
+    fn exercise(data: &[u8]) {
+        let Some(mut e) = Engine::from_snapshot(data) else {
+            return;
+        };
+        for key in [
+            keys::A,
+            keys::W,
+            keys::S,
+            keys::N6,
+            keys::DELETE,
+            keys::O,
+            keys::MINUS,
+            keys::N,
+            keys::DELETE,
+            keys::DELETE,
+            keys::ESC,
+            keys::D,
+            keys::SPACE,
+            keys::DELETE,
+            keys::R,
+        ] {
+            e.on_key(key, false, false);
+            while e.next_result().is_some() {}
+        }
+        e.swap_hoi_nga();
+        e.split_at(1);
+        e.force_raw_current_word();
+        e.tick(10_000);
+        e.on_key(keys::SPACE, false, false);
+    }
+
+    #[test]
+    fn test_snapshot_corrupted_blobs_never_panic() {
+        // Every byte of each seed set to a spread of values, then some
+        // pseudo-random multi-byte corruptions
+        let mut seed: u32 = 0x2545_f491;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        for blob in seed_snapshots() {
+            for i in 5..blob.len() {
+                for v in [0x00, 0x01, 0x02, 0x03, 0x07, 0x7f, 0x80, 0xff] {
+                    let mut data = blob.clone();
+                    data[i] = v;
+                    exercise(&data);
+                }
+            }
+            for _ in 0..2000 {
+                let mut data = blob.clone();
+                for _ in 0..4 {
+                    let i = 5 + next() as usize % (data.len() - 5);
+                    data[i] = next() as u8;
+                }
+                exercise(&data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_rejects_bad_data() {
+        let blob = Engine::new().snapshot();
+        assert!(Engine::from_snapshot(&blob[..blob.len() - 1]).is_none());
+        assert!(Engine::from_snapshot(b"nope").is_none());
+        let mut wrong_version = blob.clone();
+        wrong_version[4] = 99;
+        assert!(Engine::from_snapshot(&wrong_version).is_none());
+
+        // Positions past the buffer and unknown modes
+        let mut e = Engine::new();
+        crate::utils::type_word(&mut e, "ddi");
+        e.pending_u_horn_pos = Some(22016);
+        assert!(Engine::from_snapshot(&e.snapshot()).is_none());
+        let mut e = Engine::new();
+        e.overflow_mode = 9;
+        assert!(Engine::from_snapshot(&e.snapshot()).is_none());
+    }
+}
@@ -225,11 +225,49 @@ pub fn is_valid_structure(buffer_keys: &[u16]) -> bool {
     true
 }
 
+/// Longest Vietnamese syllable in chars ("nghiêng", "nghiệng")
+const MAX_SYLLABLE_LEN: usize = 7;
+
+/// Split run-on text into syllables ("tôiđihọc" → ["tôi", "đi", "học"])
+///
+/// Greedy: each step takes the longest leading chunk that is a valid syllable.
+/// Whitespace separates words and is dropped; a char that starts no valid
+/// syllable becomes its own segment so no text is lost.
+pub fn segment(word: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    for part in word.split_whitespace() {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        while start < chars.len() {
+            let max_end = (start + MAX_SYLLABLE_LEN).min(chars.len());
+            let end = (start + 1..=max_end)
+                .rev()
+                .find(|&end| {
+                    let candidate: String = chars[start..end].iter().collect();
+                    super::validation::is_valid_word(&candidate)
+                })
+                .unwrap_or(start + 1);
+            segments.push(chars[start..end].iter().collect());
+            start = end;
+        }
+    }
+    segments
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::utils::keys_from_str;
 
+    #[test]
+    fn segment_run_on_text() {
+        assert_eq!(segment("tôiđihọc"), ["tôi", "đi", "học"]);
+        assert_eq!(segment("Việt Nam"), ["Việt", "Nam"]);
+        assert_eq!(segment("nghiêngngười"), ["nghiêng", "người"]);
+        assert_eq!(segment("tôi2"), ["tôi", "2"]);
+        assert!(segment("  ").is_empty());
+    }
+
     #[test]
     fn parse_simple_syllable() {
         let s = parse(&keys_from_str("ba"));
@@ -4,7 +4,7 @@
 //! Uses valid patterns from docs/vietnamese-language-system.md Section 7.6.1
 
 use super::syllable::{parse, Syllable};
-use crate::data::chars::tone;
+use crate::data::chars::{self, tone};
 use crate::data::constants;
 use crate::data::keys;
 
@@ -258,6 +258,34 @@ fn rule_valid_vowel_pattern(
 // PUBLIC API
 // =============================================================================
 
+/// Longest valid initial consonant ("ngh")
+const MAX_INITIAL_LEN: usize = 3;
+
+/// Longest valid final consonant ("ng", "ch", "nh")
+const MAX_FINAL_LEN: usize = 2;
+
+/// Cheap structural reject: keys that cannot fit in one syllable
+///
+/// `parse` takes an initial, one vowel run and at most `MAX_FINAL_LEN` final keys,
+/// so anything longer always fails `rule_valid_initial` or `rule_all_chars_parsed`.
+/// Only scans up to the end of the first vowel run, so long run-on words
+/// ("supercalifragilistic...") are rejected in O(1) instead of a full parse per key.
+fn overruns_syllable(keys: &[u16]) -> bool {
+    let Some(vowel_start) = keys
+        .iter()
+        .take(MAX_INITIAL_LEN + 1)
+        .position(|&k| keys::is_vowel(k))
+    else {
+        // No vowel within reach: initial too long (or no vowel at all)
+        return keys.len() > MAX_INITIAL_LEN;
+    };
+    let vowel_end = keys[vowel_start..]
+        .iter()
+        .position(|&k| !keys::is_vowel(k))
+        .map_or(keys.len(), |n| vowel_start + n);
+    keys.len() - vowel_end > MAX_FINAL_LEN
+}
+
 /// Validate buffer as Vietnamese syllable - runs all rules
 pub fn validate(snap: &BufferSnapshot) -> ValidationResult {
     if snap.keys.is_empty() {
@@ -278,6 +306,9 @@ pub fn validate(snap: &BufferSnapshot) -> ValidationResult {
 /// Quick check if buffer could be valid Vietnamese (with modifier info)
 /// This will fully validate modifier requirements (e.g., E+U requires circumflex)
 pub fn is_valid_with_tones(keys: &[u16], tones: &[u8]) -> bool {
+    if overruns_syllable(keys) {
+        return false;
+    }
     let snap = BufferSnapshot {
         keys: keys.to_vec(),
         tones: tones.to_vec(),
@@ -291,10 +322,32 @@ pub fn is_valid_with_tones(keys: &[u16], tones: &[u8]) -> bool {
 /// NOTE: This cannot fully validate modifier requirements.
 /// Use is_valid_with_tones() for complete validation.
 pub fn is_valid(buffer_keys: &[u16]) -> bool {
+    if overruns_syllable(buffer_keys) {
+        return false;
+    }
     let snap = BufferSnapshot::from_keys(buffer_keys.to_vec());
     validate(&snap).is_valid()
 }
 
+/// Validate a standalone Vietnamese word string (e.g., from a spell checker)
+///
+/// Parses each character via `chars::parse_char` into keys + tones and runs
+/// full validation. Returns false for empty strings or unknown characters.
+pub fn is_valid_word(word: &str) -> bool {
+    let mut keys = Vec::with_capacity(word.len());
+    let mut tones = Vec::with_capacity(word.len());
+    for c in word.chars() {
+        match chars::parse_char(c) {
+            Some(parsed) => {
+                keys.push(parsed.key);
+                tones.push(parsed.tone);
+            }
+            None => return false,
+        }
+    }
+    !keys.is_empty() && is_valid_with_tones(&keys, &tones)
+}
+
 /// Rules for pre-transformation validation (excludes vowel pattern check)
 /// Used to validate buffer structure before applying tone/mark transformations.
 /// Allows intermediate states like "aa" that become valid after transformation.
@@ -312,7 +365,7 @@ const RULES_FOR_TRANSFORM: &[Rule] = &[
 /// Used by try_tone/try_stroke to validate buffer structure before transformation.
 /// Does NOT check vowel patterns since intermediate states like "aa" → "â" are valid.
 pub fn is_valid_for_transform(buffer_keys: &[u16]) -> bool {
-    if buffer_keys.is_empty() {
+    if buffer_keys.is_empty() || overruns_syllable(buffer_keys) {
         return false;
     }
 
@@ -328,6 +381,57 @@ pub fn is_valid_for_transform(buffer_keys: &[u16]) -> bool {
     true
 }
 
+/// Memo of the last keys-only validation on the engine's buffer
+///
+/// A lookup is keyed by buffer length and last key, confirmed against the
+/// stored keys so results stay exact (tone/mark edits don't change keys).
+/// Several checks per keystroke then share one parse. When a key is appended
+/// to a buffer that already overruns a syllable, only the new key is looked
+/// at: the overrun can only grow, so the result is invalid without parsing.
+#[derive(Default)]
+pub struct ValidationCache {
+    keys: Vec<u16>,
+    overrun: bool,
+    valid: Option<bool>,
+    valid_for_transform: Option<bool>,
+}
+
+impl ValidationCache {
+    /// Cached `is_valid`
+    pub fn is_valid(&mut self, keys: &[u16]) -> bool {
+        self.sync(keys);
+        *self.valid.get_or_insert_with(|| is_valid(keys))
+    }
+
+    /// Cached `is_valid_for_transform`
+    pub fn is_valid_for_transform(&mut self, keys: &[u16]) -> bool {
+        self.sync(keys);
+        *self
+            .valid_for_transform
+            .get_or_insert_with(|| is_valid_for_transform(keys))
+    }
+
+    /// Point the cache at `keys`, keeping whatever results still apply
+    fn sync(&mut self, keys: &[u16]) {
+        let same_key = self.keys.len() == keys.len() && self.keys.last() == keys.last();
+        if same_key && self.keys == keys {
+            return;
+        }
+
+        let extends = keys.len() > self.keys.len() && keys.starts_with(&self.keys);
+        if extends {
+            self.keys.extend_from_slice(&keys[self.keys.len()..]);
+        } else {
+            self.keys.clear();
+            self.keys.extend_from_slice(keys);
+        }
+        self.overrun = (extends && self.overrun) || overruns_syllable(keys);
+        let known = self.overrun.then_some(false);
+        self.valid = known;
+        self.valid_for_transform = known;
+    }
+}
+
 /// Check if the buffer shows patterns that suggest foreign word input.
 ///
 /// This is a heuristic to detect when the user is likely typing a foreign word
@@ -582,4 +686,101 @@ mod tests {
             "'ăi' should be invalid"
         );
     }
+
+    #[test]
+    fn test_is_valid_word() {
+        for w in ["việt", "Nam", "đường", "nguyễn", "khuyến", "giường", "nêu"] {
+            assert!(is_valid_word(w), "'{}' should be valid", w);
+        }
+        for w in ["", "neu", "tăi", "test", "ci", "việt nam", "a1"] {
+            assert!(!is_valid_word(w), "'{}' should be invalid", w);
+        }
+    }
+
+    #[test]
+    fn test_breve_with_k_final_valid() {
+        // Ethnic minority place names: Đắk Lắk, Buôn Ma Thuột
+        for w in ["Đắk", "Lắk", "đắk", "Buôn", "Ma", "Thuột"] {
+            assert!(is_valid_word(w), "'{}' should be valid", w);
+        }
+    }
+
+    #[test]
+    fn test_validation_cache_matches_direct_checks() {
+        // Typed key by key with backspaces and in-place rewrites, like the engine
+        let mut cache = ValidationCache::default();
+        for word in [
+            "vieetnam",
+            "supercalifragilistic",
+            "nghieng",
+            "dadu",
+            "chuyeenj",
+            "thuongw",
+        ] {
+            let mut keys = Vec::new();
+            for key in keys_from_str(word) {
+                keys.push(key);
+                for current in [keys.clone(), keys[..keys.len() - 1].to_vec()] {
+                    assert_eq!(cache.is_valid(&current), is_valid(&current), "{word}");
+                    assert_eq!(
+                        cache.is_valid_for_transform(&current),
+                        is_valid_for_transform(&current),
+                        "{word}"
+                    );
+                }
+            }
+            // Same length and last key, different keys: must not hit the cache
+            let mut swapped = keys.clone();
+            swapped[0] = keys::B;
+            assert_eq!(cache.is_valid(&swapped), is_valid(&swapped), "{word}");
+        }
+    }
+
+    #[test]
+    fn test_overruns_syllable_never_rejects_valid() {
+        // Every key sequence up to 5 letters: the fast reject must agree with full rules
+        let alphabet = [
+            keys::A,
+            keys::O,
+            keys::I,
+            keys::U,
+            keys::N,
+            keys::G,
+            keys::H,
+            keys::Q,
+            keys::T,
+        ];
+        let mut seqs: Vec<Vec<u16>> = vec![vec![]];
+        for _ in 0..5 {
+            seqs = seqs
+                .iter()
+                .flat_map(|s| {
+                    alphabet.iter().map(move |&k| {
+                        let mut next = s.clone();
+                        next.push(k);
+                        next
+                    })
+                })
+                .collect();
+            for keys in &seqs {
+                if !overruns_syllable(keys) {
+                    continue;
+                }
+                let snap = BufferSnapshot::from_keys(keys.clone());
+                let syllable = parse(keys);
+                assert!(!validate(&snap).is_valid(), "{:?}", keys);
+                assert!(
+                    RULES_FOR_TRANSFORM
+                        .iter()
+                        .any(|rule| rule(&snap, &syllable).is_some()),
+                    "{:?}",
+                    keys
+                );
+            }
+        }
+
+        assert!(overruns_syllable(&keys_from_str("supercalifragilistic")));
+        assert!(overruns_syllable(&keys_from_str("nghtr")));
+        assert!(!overruns_syllable(&keys_from_str("nghieng")));
+    }
 }
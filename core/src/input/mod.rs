@@ -3,6 +3,7 @@
 //! Defines key mappings for Vietnamese input methods.
 //! Engine handles all pattern matching based on buffer scan.
 
+pub mod t9;
 pub mod telex;
 pub mod vni;
 
@@ -71,3 +72,83 @@ pub fn get(id: u8) -> &'static dyn Method {
         _ => &TELEX,
     }
 }
+
+/// Keycodes for each modifier role of a method, for onscreen keyboards
+///
+/// Unused slots are `keys::UNKNOWN`. A key can fill several roles
+/// (Telex 'w' is both horn and breve).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModifierKeys {
+    /// Mark keys, indexed by mark - 1 (sắc, huyền, hỏi, ngã, nặng)
+    pub marks: [u16; 5],
+    /// Circumflex keys (Telex: a, e, o; VNI: 6)
+    pub circumflex: [u16; 3],
+    /// Horn key (ơ, ư)
+    pub horn: u16,
+    /// Breve key (ă)
+    pub breve: u16,
+    /// Stroke key (đ)
+    pub stroke: u16,
+    /// Remove diacritics key
+    pub remove: u16,
+}
+
+impl ModifierKeys {
+    /// Collect the modifier keys of `method` (internal keycodes)
+    pub fn of(method: &dyn Method) -> Self {
+        let mut m = Self {
+            marks: [keys::UNKNOWN; 5],
+            circumflex: [keys::UNKNOWN; 3],
+            horn: keys::UNKNOWN,
+            breve: keys::UNKNOWN,
+            stroke: keys::UNKNOWN,
+            remove: keys::UNKNOWN,
+        };
+        let mut circumflex = 0;
+        for key in (0..128).filter(|&k| keys::is_letter(k) || keys::is_number(k)) {
+            if let Some(mark) = method.mark(key) {
+                m.marks[mark as usize - 1] = key;
+            }
+            match method.tone(key) {
+                Some(ToneType::Circumflex) if circumflex < m.circumflex.len() => {
+                    m.circumflex[circumflex] = key;
+                    circumflex += 1;
+                }
+                Some(ToneType::Horn) => {
+                    m.horn = key;
+                    // Telex horn key also makes ă
+                    if method.tone_targets(key).contains(&keys::A) {
+                        m.breve = key;
+                    }
+                }
+                Some(ToneType::Breve) => m.breve = key,
+                _ => {}
+            }
+            if method.stroke(key) {
+                m.stroke = key;
+            }
+            if method.remove(key) {
+                m.remove = key;
+            }
+        }
+        m
+    }
+
+    /// Same keys as codes of another layout (`keys::LAYOUT_*`)
+    pub fn to_layout(self, layout: u8) -> Self {
+        let map = |code: u16| {
+            keys::KeyCode::from_platform(keys::LAYOUT_MACOS, code)
+                .and_then(|k| k.to_platform(layout))
+                .unwrap_or(keys::UNKNOWN)
+        };
+        Self {
+            marks: self.marks.map(map),
+            circumflex: self.circumflex.map(map),
+            horn: map(self.horn),
+            breve: map(self.breve),
+            stroke: map(self.stroke),
+            remove: map(self.remove),
+        }
+    }
+}
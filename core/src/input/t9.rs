@@ -0,0 +1,84 @@
+//! T9 Keypad Input
+//!
+//! Phone keypad front-end for Telex: digits 2-9 cycle through their letter
+//! group on repeat, and the selected letter is then typed as Telex.
+//! - 2=abc, 3=def, 4=ghi, 5=jkl, 6=mno, 7=pqrs, 8=tuv, 9=wxyz
+//! - Another key (or the timeout) commits the pending letter
+//!
+//! Example: "ddaau" → 3 · 3 2 2 · 2 8 8 ("·" = timeout)
+
+use crate::data::keys;
+
+/// Method id passed to `Engine::set_method`
+pub const METHOD_ID: u8 = 4;
+
+/// Default time before a pending letter commits on its own
+pub const DEFAULT_TIMEOUT_MS: u32 = 1000;
+
+/// Letter group of a keypad digit
+pub fn letters(key: u16) -> Option<&'static [u16]> {
+    match key {
+        keys::N2 => Some(&[keys::A, keys::B, keys::C]),
+        keys::N3 => Some(&[keys::D, keys::E, keys::F]),
+        keys::N4 => Some(&[keys::G, keys::H, keys::I]),
+        keys::N5 => Some(&[keys::J, keys::K, keys::L]),
+        keys::N6 => Some(&[keys::M, keys::N, keys::O]),
+        keys::N7 => Some(&[keys::P, keys::Q, keys::R, keys::S]),
+        keys::N8 => Some(&[keys::T, keys::U, keys::V]),
+        keys::N9 => Some(&[keys::W, keys::X, keys::Y, keys::Z]),
+        _ => None,
+    }
+}
+
+/// Letter being selected on a keypad digit (shown on screen, not yet typed)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    pub digit: u16,
+    pub caps: bool,
+    index: usize,
+}
+
+impl Cycle {
+    /// Start cycling on `digit`; `None` if the key has no letter group
+    pub fn new(digit: u16, caps: bool) -> Option<Self> {
+        letters(digit).map(|_| Self {
+            digit,
+            caps,
+            index: 0,
+        })
+    }
+
+    /// Move to the next letter of the group, wrapping around
+    pub fn advance(&mut self) {
+        let len = letters(self.digit).map_or(1, |l| l.len());
+        self.index = (self.index + 1) % len;
+    }
+
+    /// Currently selected letter key
+    pub fn key(&self) -> u16 {
+        letters(self.digit).map_or(keys::UNKNOWN, |l| l[self.index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_wraps() {
+        let mut c = Cycle::new(keys::N7, false).unwrap();
+        let mut seen = vec![c.key()];
+        for _ in 0..4 {
+            c.advance();
+            seen.push(c.key());
+        }
+        assert_eq!(seen, [keys::P, keys::Q, keys::R, keys::S, keys::P]);
+    }
+
+    #[test]
+    fn test_no_group() {
+        assert!(Cycle::new(keys::N0, false).is_none());
+        assert!(Cycle::new(keys::N1, false).is_none());
+        assert!(Cycle::new(keys::A, false).is_none());
+    }
+}
@@ -11,8 +11,8 @@
 //!
 //! // Process each keystroke
 //! ImeResult* r = ime_key(keycode, is_shift, is_ctrl);
-//! if (r && r->action == 1) {
-//!     // Send r->backspace deletes, then r->chars
+//! if (r && r->action != 0) {
+//!     // Send (1) or Restore (2): r->backspace deletes, then r->chars
 //! }
 //! ime_free(r);
 //!
@@ -26,7 +26,8 @@ pub mod input;
 pub mod updater;
 pub mod utils;
 
-use engine::{Engine, Result};
+use engine::{Engine, Result, ResultU16};
+use input::ModifierKeys;
 use std::sync::Mutex;
 
 // Global engine instance (thread-safe via Mutex)
@@ -54,10 +55,62 @@ pub extern "C" fn ime_init() {
     *guard = Some(Engine::new());
 }
 
+/// Initialize the IME engine and report how it went.
+///
+/// Same as `ime_init`, but tells the host about anomalies:
+/// - `0`: fresh init
+/// - `1`: replaced an existing engine (double init)
+/// - `-1`: engine mutex was poisoned and has been recovered
+///
+/// Poison takes precedence over replacement.
+#[no_mangle]
+pub extern "C" fn ime_init_status() -> i32 {
+    let (mut guard, poisoned) = match ENGINE.lock() {
+        Ok(guard) => (guard, false),
+        Err(e) => {
+            ENGINE.clear_poison();
+            (e.into_inner(), true)
+        }
+    };
+    let replaced = guard.replace(Engine::new()).is_some();
+    if poisoned {
+        -1
+    } else if replaced {
+        1
+    } else {
+        0
+    }
+}
+
+/// `ime_features` bit: built with the `keyboard-types` feature
+pub const FEATURE_KEYBOARD_TYPES: u32 = 0x01;
+
+/// Get the core version (e.g., "0.1.0").
+///
+/// Returns a static null-terminated string: do not free it.
+/// Works before `ime_init`.
+#[no_mangle]
+pub extern "C" fn ime_version() -> *const std::os::raw::c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const std::os::raw::c_char
+}
+
+/// Get a bitmask of optional features compiled into this build.
+///
+/// See the `FEATURE_*` constants. Hosts linked against an older core can
+/// check bits before relying on a feature. Works before `ime_init`.
+#[no_mangle]
+pub extern "C" fn ime_features() -> u32 {
+    let mut features = 0;
+    if cfg!(feature = "keyboard-types") {
+        features |= FEATURE_KEYBOARD_TYPES;
+    }
+    features
+}
+
 /// Process a key event and return the result.
 ///
 /// # Arguments
-/// * `key` - macOS virtual keycode (0-127 for standard keys)
+/// * `key` - keycode in the active layout (macOS by default, see `ime_key_layout`)
 /// * `caps` - true if CapsLock is pressed (for uppercase letters)
 /// * `ctrl` - true if Cmd/Ctrl/Alt is pressed (bypasses IME)
 ///
@@ -67,6 +120,7 @@ pub extern "C" fn ime_init() {
 ///
 /// # Result struct
 /// * `action`: 0=None (pass through), 1=Send (replace text), 2=Restore
+///   (replace text with the raw keystrokes: ESC, auto-restore; applied like Send)
 /// * `backspace`: number of characters to delete
 /// * `chars`: UTF-32 codepoints to insert
 /// * `count`: number of valid chars
@@ -88,7 +142,7 @@ pub extern "C" fn ime_key(key: u16, caps: bool, ctrl: bool) -> *mut Result {
 /// Process a key event with extended parameters.
 ///
 /// # Arguments
-/// * `key` - macOS virtual keycode (0-127 for standard keys)
+/// * `key` - keycode in the active layout (macOS by default, see `ime_key_layout`)
 /// * `caps` - true if CapsLock is pressed (for uppercase letters)
 /// * `ctrl` - true if Cmd/Ctrl/Alt is pressed (bypasses IME)
 /// * `shift` - true if Shift key is pressed (for symbols like @, #, $)
@@ -114,321 +168,2207 @@ pub extern "C" fn ime_key_ext(key: u16, caps: bool, ctrl: bool, shift: bool) ->
     }
 }
 
-/// Set the input method.
+/// Process a key event, returning UTF-16 output.
+///
+/// Same as `ime_key_ext`, but `chars` holds UTF-16 code units (surrogate
+/// pairs for chars outside the BMP) and `count` is in code units.
+/// `backspace` still counts characters.
+///
+/// # Returns
+/// * Pointer to `ResultU16` struct (caller must free with `ime_free_u16`)
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_key_utf16(key: u16, caps: bool, ctrl: bool, shift: bool) -> *mut ResultU16 {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.on_key_ext(key, caps, ctrl, shift);
+        Box::into_raw(Box::new(ResultU16::from(&r)))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Process a key event with a host timestamp, filtering held-key repeats.
+///
+/// Same as `ime_key_ext`; when `ime_repeat_filter_ms` is set, a letter or
+/// number repeating the previous key within the threshold is dropped
+/// (action=1 with no chars, key consumed).
 ///
 /// # Arguments
-/// * `method` - 0 for Telex, 1 for VNI
+/// * `timestamp_ms` - host event time in milliseconds (may wrap)
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_key_timed(
+    key: u16,
+    caps: bool,
+    ctrl: bool,
+    shift: bool,
+    timestamp_ms: u32,
+) -> *mut Result {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.on_key_timed(key, caps, ctrl, shift, timestamp_ms);
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Set whether "uo" + horn marks both vowels immediately.
 ///
+/// When `enabled` is true, "huow" → "hươ" right away; when false (default),
+/// only 'o' gets the horn ("huơ") until a final is typed ("duowc" → "dươc").
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_method(method: u8) {
+pub extern "C" fn ime_eager_uo_horn(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_method(method);
+        e.set_eager_uo_horn(enabled);
     }
 }
 
-/// Enable or disable the engine.
+/// Set whether breve waits for a final consonant in open syllables.
 ///
-/// When disabled, `ime_key` returns action=0 (pass through).
+/// When `enabled` is true, "aw" stays "aw" and "trawm" → "trăm";
+/// when false (default), "aw" → "ă" immediately.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_enabled(enabled: bool) {
+pub extern "C" fn ime_defer_breve(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_enabled(enabled);
+        e.set_defer_breve(enabled);
     }
 }
 
-/// Set whether to skip w→ư shortcut in Telex mode.
+/// Set whether words committed by space are wrapped in bidi isolates.
 ///
-/// When `skip` is true, typing 'w' at word start stays as 'w'
-/// instead of converting to 'ư'.
+/// For words committed right after right-to-left text (typed via `ime_key_char` or
+/// reported by the cursor context): the word is re-sent as U+2066 word U+2069 + space.
+/// The markers add 2 codepoints per word that later backspaces must delete too.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_skip_w_shortcut(skip: bool) {
+pub extern "C" fn ime_bidi_isolation(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_skip_w_shortcut(skip);
+        e.set_bidi_isolation(enabled);
     }
 }
 
-/// Set whether ESC key restores raw ASCII input.
+/// Set whether a word typed past a shortcut trigger stays unexpanded.
 ///
-/// When `enabled` is true (default), pressing ESC restores original keystrokes.
-/// When `enabled` is false, ESC key is passed through without restoration.
+/// When `enabled` is true, "vnx" + backspace + space leaves "vn" as typed
+/// instead of expanding it. Results of keys that go past a complete trigger
+/// always carry the shortcut_cancelled flag (0x04).
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_esc_restore(enabled: bool) {
+pub extern "C" fn ime_shortcut_exact_boundary(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_esc_restore(enabled);
+        e.set_shortcut_require_exact_boundary(enabled);
     }
 }
 
-/// Set whether to enable free tone placement (skip validation).
+/// Set what happens to shortcut output longer than one `Result`.
+///
+/// # Arguments
+/// * `mode` - 0=truncate (default), 1=split (drain the rest with `ime_next_result`),
+///   2=error (nothing replaced, `flags` bit 0x02 set)
 ///
-/// When `enabled` is true, allows placing diacritics anywhere without
-/// spelling validation (e.g., "Zìa" is allowed).
-/// When `enabled` is false (default), validates Vietnamese spelling rules.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_free_tone(enabled: bool) {
+pub extern "C" fn ime_overflow_mode(mode: u8) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_free_tone(enabled);
+        e.set_overflow_mode(mode);
     }
 }
 
-/// Set whether to use modern orthography for tone placement.
+/// Get the next queued chunk of a split output (overflow mode 1).
+///
+/// Call after `ime_key` until it returns null; apply each chunk in order.
+/// The queue is dropped on the next `ime_key`.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+/// * `null` if nothing is queued or engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_next_result() -> *mut Result {
+    let mut guard = lock_engine();
+    match guard.as_mut().and_then(|e| e.next_result()) {
+        Some(r) => Box::into_raw(Box::new(r)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Set the repeat filter threshold used by `ime_key_timed`.
+///
+/// # Arguments
+/// * `ms` - repeats of the same key within this time are dropped (0 = disabled, default)
 ///
-/// When `modern` is true: hoà, thuý (tone on second vowel - new style)
-/// When `modern` is false (default): hòa, thúy (tone on first vowel - traditional)
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_modern(modern: bool) {
+pub extern "C" fn ime_repeat_filter_ms(ms: u32) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_modern_tone(modern);
+        e.set_repeat_filter_ms(ms);
     }
 }
 
-/// Enable/disable English auto-restore (experimental feature).
+/// Set the double-tap window for mark keys used by `ime_key_timed`.
+///
+/// # Arguments
+/// * `ms` - a mark key repeated within this time keeps the mark instead of
+///   reverting it (0 = disabled, default)
 ///
-/// When `enabled` is true, automatically restores English words that were
-/// accidentally transformed (e.g., "tẽt" → "text", "ễpct" → "expect").
-/// When `enabled` is false (default), no auto-restore happens.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_english_auto_restore(enabled: bool) {
+pub extern "C" fn ime_mark_revert_gap_ms(ms: u32) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_english_auto_restore(enabled);
+        e.set_mark_revert_gap_ms(ms);
     }
 }
 
-/// Enable/disable auto-capitalize after sentence-ending punctuation.
+/// Process a typed character instead of a keycode.
+///
+/// For hosts without macOS/Windows/Linux keycodes: the character is mapped
+/// to its key internally, so hosts don't need their own char → keycode table.
+///
+/// # Arguments
+/// * `ch` - Unicode scalar value of the typed character (e.g., 'a', '@', '\x08')
+/// * `caps` - true for uppercase (implied by an uppercase `ch`)
+/// * `ctrl` - true if Cmd/Ctrl/Alt is pressed (bypasses IME)
+/// * `shift` - true if Shift is pressed (implied by shifted symbols like '@')
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+/// * `null` if engine not initialized or `ch` is not a valid Unicode scalar
+#[no_mangle]
+pub extern "C" fn ime_key_char(ch: u32, caps: bool, ctrl: bool, shift: bool) -> *mut Result {
+    let Some(c) = char::from_u32(ch) else {
+        return std::ptr::null_mut();
+    };
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.on_char(c, caps, ctrl, shift);
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Set whether backtick toggles a code span with Vietnamese transforms off.
+///
+/// When `enabled` is true, the first `` ` `` enters code span mode (keys pass
+/// through untransformed, shortcuts still work) and the second exits it.
+/// Enter and ESC also exit. Default: OFF.
 ///
-/// When `enabled` is true, automatically capitalizes the first letter
-/// after sentence-ending punctuation (. ! ? Enter).
-/// When `enabled` is false (default), no auto-capitalize happens.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_auto_capitalize(enabled: bool) {
+pub extern "C" fn ime_code_span(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.set_auto_capitalize(enabled);
+        e.set_code_span(enabled);
     }
 }
 
-/// Clear the input buffer.
+/// Set whether to minimize backspaces in results.
 ///
-/// Call on word boundaries (space, punctuation).
-/// Preserves word history for backspace-after-space feature.
+/// When `enabled` is true, replacements skip leading chars that are already
+/// on screen (e.g., ESC restore "việt" → "vieejt" sends 2 backspaces + "eejt"
+/// instead of 4 + "vieejt"). Useful where backspacing is expensive.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_clear() {
+pub extern "C" fn ime_diff_output(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.clear();
+        e.set_diff_output(enabled);
     }
 }
 
-/// Clear everything including word history.
+/// Set whether every edit replaces the whole composing word.
 ///
-/// Call when cursor position changes (mouse click, arrow keys, focus change).
-/// This prevents accidental restore from stale history.
+/// When `enabled` is true, results delete the full word on screen and resend it
+/// (e.g., "viêt" + j sends 4 backspaces + "việt" instead of 2 + "ệt"), for hosts
+/// that can't apply partial edits reliably. Overrides `ime_diff_output`.
 /// No-op if engine not initialized.
 #[no_mangle]
-pub extern "C" fn ime_clear_all() {
+pub extern "C" fn ime_full_replace(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        e.clear_all();
+        e.set_full_replace(enabled);
     }
 }
 
-/// Get the full composed buffer as UTF-32 codepoints.
+/// Set whether output is held back until the word ends.
 ///
-/// Used for "Select All + Replace" injection method where the entire
-/// buffer content is needed instead of incremental backspace + chars.
+/// When `enabled` is true, keys of a word pass through as typed and the
+/// composed word replaces them in a single edit on space/punctuation
+/// (e.g., "vieetj" + space sends 6 backspaces + "việt "), for hosts that
+/// can't handle backspacing mid-word. Overrides `ime_full_replace`.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_deferred_emit(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_deferred_emit(enabled);
+    }
+}
+
+/// Set whether invalid Vietnamese reverts to raw keystrokes on word end.
 ///
-/// # Arguments
-/// * `out` - Pointer to output buffer for UTF-32 codepoints
-/// * `max_len` - Maximum number of codepoints to write
+/// Unlike English auto-restore, no English detection is involved: any
+/// transformed word that can't be a Vietnamese syllable is reverted on
+/// space or punctuation (e.g., "ắeome" → "awesome"). Default: OFF.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_revert_invalid(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_revert_invalid(enabled);
+    }
+}
+
+/// Set what Ctrl/Cmd chords do to the word being composed.
 ///
-/// # Returns
-/// Number of codepoints written to `out`.
+/// # Arguments
+/// * `mode` - 0 = clear the word (default), 1 = ignore the chord and keep
+///   composing (e.g., Cmd+Shift+Left selection doesn't drop the word)
 ///
-/// # Safety
-/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+/// No-op if engine not initialized.
 #[no_mangle]
-pub unsafe extern "C" fn ime_get_buffer(out: *mut u32, max_len: i64) -> i64 {
-    if out.is_null() || max_len <= 0 {
-        return 0;
+pub extern "C" fn ime_ctrl_behavior(mode: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_ctrl_behavior(mode);
     }
+}
 
-    let guard = lock_engine();
-    if let Some(ref e) = *guard {
-        let full = e.get_buffer_string();
-        let utf32: Vec<u32> = full.chars().map(|c| c as u32).collect();
-        let len = utf32.len().min(max_len as usize);
-        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
-        len as i64
-    } else {
-        0
+/// Set what ESC does to the word being composed.
+///
+/// # Arguments
+/// * `mode` - 0 = restore raw keystrokes if `ime_esc_restore` is on (default),
+///   1 = commit and keep the Vietnamese word, 2 = clear only
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_esc_behavior(mode: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_esc_behavior(mode);
     }
 }
 
-/// Free a result pointer returned by `ime_key`.
+/// Set whether backspace removes one diacritic layer at a time.
 ///
-/// # Safety
-/// * `r` must be a pointer returned by `ime_key`, or null
-/// * Must be called exactly once per non-null `ime_key` return
-/// * Do not use `r` after calling this function
+/// When `enabled` is true, backspace over "ế" gives "ê", then "e", then
+/// deletes the letter (UniKey style). Default: OFF.
+/// No-op if engine not initialized.
 #[no_mangle]
-pub unsafe extern "C" fn ime_free(r: *mut Result) {
-    if !r.is_null() {
-        drop(Box::from_raw(r));
+pub extern "C" fn ime_smart_backspace(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_smart_backspace(enabled);
     }
 }
 
-// ============================================================
-// Shortcut FFI
-// ============================================================
+/// Set whether backspace after a committed word restores it for editing.
+///
+/// When `enabled` is false, backspace after "việt " just deletes the space
+/// and the word stays committed (for hosts with their own undo). Default: ON.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_backspace_restore(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_backspace_restore(enabled);
+    }
+}
 
-/// Add a shortcut to the engine.
+/// Set the spelling of i/y variant words ("lí/lý", "kĩ/kỹ") on commit.
 ///
 /// # Arguments
-/// * `trigger` - C string for trigger (e.g., "vn")
-/// * `replacement` - C string for replacement (e.g., "Việt Nam")
+/// * `mode` - 0 = keep as typed (default), 1 = prefer y, 2 = prefer i
 ///
-/// # Safety
-/// Both pointers must be valid null-terminated UTF-8 strings.
+/// No-op if engine not initialized.
 #[no_mangle]
-pub unsafe extern "C" fn ime_add_shortcut(
-    trigger: *const std::os::raw::c_char,
-    replacement: *const std::os::raw::c_char,
-) {
-    if trigger.is_null() || replacement.is_null() {
-        return;
+pub extern "C" fn ime_iy_preference(mode: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_iy_preference(mode);
     }
+}
 
-    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
-    };
-    let replacement_str = match std::ffi::CStr::from_ptr(replacement).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
-    };
+/// Set simple mode, a minimal predictable Telex for beginners.
+///
+/// When `enabled` is true, turns off breve deferral and English auto-restore,
+/// keeps a placed tone mark where it is as letters are added, and drops the
+/// "did" → "đi" short-pattern stroke. See `Engine::set_simple_mode`.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_simple_mode(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_simple_mode(enabled);
+    }
+}
 
+/// Set whether output is decomposed (NFD) instead of precomposed (NFC).
+///
+/// When `enabled` is true, vowels are sent as base letter plus combining marks
+/// and `backspace` counts codepoints of the decomposed text on screen.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_nfd_output(enabled: bool) {
     let mut guard = lock_engine();
     if let Some(ref mut e) = *guard {
-        // Auto-detect shortcut type:
-        // - If trigger contains only non-letter chars (like "->", "=>"), use immediate trigger
-        // - Otherwise use word boundary trigger (traditional abbreviations like "vn" → "Việt Nam")
-        let is_symbol_trigger = trigger_str.chars().all(|c| !c.is_alphabetic());
-        let shortcut = if is_symbol_trigger {
-            engine::shortcut::Shortcut::immediate(trigger_str, replacement_str)
-        } else {
-            engine::shortcut::Shortcut::new(trigger_str, replacement_str)
-        };
-        e.shortcuts_mut().add(shortcut);
+        e.set_nfd_output(enabled);
     }
 }
 
-/// Remove a shortcut from the engine.
+/// Set no-echo mode for fields that don't display input (password fields).
 ///
-/// # Arguments
-/// * `trigger` - C string for trigger to remove
+/// When `enabled` is true, every key passes through as typed: no composition,
+/// no backspaces. Call when focus enters/leaves a secure field.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_no_echo(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_no_echo(enabled);
+    }
+}
+
+/// Set all on/off features at once from a bitmask.
 ///
-/// # Safety
-/// Pointer must be a valid null-terminated UTF-8 string.
+/// Bit assignments are stable (`engine::FEATURE_*`); unknown bits are ignored:
+/// * bit 0 - skip_w_shortcut
+/// * bit 1 - esc_restore
+/// * bit 2 - free_tone
+/// * bit 3 - modern_tone
+/// * bit 4 - english_auto_restore
+/// * bit 5 - auto_capitalize
+/// * bit 6 - code_span
+/// * bit 7 - diff_output
+/// * bit 8 - full_replace
+/// * bit 9 - deferred_emit
+/// * bit 10 - apostrophe_in_word
+/// * bit 11 - hyphen_in_word
+/// * bit 12 - revert_invalid
+/// * bit 13 - smart_backspace
+/// * bit 14 - backspace_restore
+/// * bit 15 - strict_finals
+/// * bit 16 - defer_breve
+/// * bit 17 - bidi_isolation
+/// * bit 18 - eager_uo_horn
+/// * bit 19 - shortcut_exact_boundary
+/// * bit 20 - simple_mode
+/// * bit 21 - nfd_output
+/// * bit 22 - no_echo
+/// * bit 23 - vni_numpad
+///
+/// No-op if engine not initialized.
 #[no_mangle]
-pub unsafe extern "C" fn ime_remove_shortcut(trigger: *const std::os::raw::c_char) {
+pub extern "C" fn ime_set_flags(flags: u32) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_feature_flags(flags);
+    }
+}
+
+/// Get all on/off features as a bitmask (bits as in `ime_set_flags`).
+///
+/// Returns 0 if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_get_flags() -> u32 {
+    let guard = lock_engine();
+    guard.as_ref().map_or(0, |e| e.feature_flags())
+}
+
+/// Set whether an apostrophe between letters stays part of the word.
+///
+/// When `enabled` is true, contractions like "don't" are composed and
+/// auto-restored as one word. Leading/trailing apostrophes still break.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_apostrophe_in_word(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_apostrophe_in_word(enabled);
+    }
+}
+
+/// Set whether a hyphen between letters keeps the word open.
+///
+/// When `enabled` is true, "việt-trường" is composed part by part, and
+/// English parts like "well-known" are auto-restored at the end of the word.
+/// Leading/trailing hyphens still break, so "->" shortcuts keep working.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_hyphen_in_word(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_hyphen_in_word(enabled);
+    }
+}
+
+/// Set idle time after which `ime_tick` commits the current word.
+///
+/// # Arguments
+/// * `ms` - idle threshold in milliseconds (0 = disabled, default)
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_idle_commit_ms(ms: u32) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_idle_commit_ms(ms);
+    }
+}
+
+/// Set how long a T9 keypad letter stays selectable before `ime_tick` commits it.
+///
+/// # Arguments
+/// * `ms` - timeout in milliseconds (default 1000)
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_t9_timeout_ms(ms: u32) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_t9_timeout_ms(ms);
+    }
+}
+
+/// Advance the idle timer. Call periodically from a host timer.
+///
+/// When the idle threshold is reached since the last key, the current word
+/// is committed (buffer cleared). All timekeeping is host-driven.
+///
+/// # Arguments
+/// * `elapsed_ms` - milliseconds elapsed since the previous `ime_tick`
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+///   with action=2 (Restore) if auto-restore replaced the committed word
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_tick(elapsed_ms: u32) -> *mut Result {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.tick(elapsed_ms);
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Keep the current word raw (no Vietnamese transforms) until the next word.
+///
+/// For a "this word is English" hotkey. If the word already has transforms,
+/// the result restores it to the raw keystrokes.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+///   with action=2 (Restore) if the word was reverted, action=0 if nothing
+///   on screen needs to change
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_force_raw_word() -> *mut Result {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.force_raw_current_word();
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Commit the current word up to `index` (in chars) and keep the rest composing.
+///
+/// For live correction in editors. Nothing visible changes: the result
+/// replaces the remainder with itself so composing hosts can re-anchor it.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+///   with action=0 if `index` is 0 or past the end of the word
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_split_at(index: u32) -> *mut Result {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.split_at(index as usize);
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Swap hỏi ↔ ngã on the current word.
+///
+/// # Returns
+/// * Pointer to `Result` struct (caller must free with `ime_free`)
+///   with action=0 if the word has neither hỏi nor ngã
+/// * `null` if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_swap_hoi_nga() -> *mut Result {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let r = e.swap_hoi_nga();
+        Box::into_raw(Box::new(r))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// Set the input method.
+///
+/// # Arguments
+/// * `method` - 0 for Telex, 1 for VNI, 4 for T9 keypad (digits 2-9 cycle
+///   letters, typed as Telex)
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_method(method: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_method(method);
+    }
+}
+
+/// Enable/disable VNI tone/mark digits on the numeric keypad.
+///
+/// When `enabled` is true, keypad digits behave like top-row digits in VNI
+/// (keypad 6 after "vie" → "viê"). Off by default so number entry on the
+/// keypad is never composed.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_vni_numpad(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_vni_numpad(enabled);
+    }
+}
+
+/// Get the current input method.
+///
+/// # Returns
+/// * 0 for Telex, 1 for VNI, 4 for T9
+/// * 255 if engine not initialized
+#[no_mangle]
+pub extern "C" fn ime_get_method() -> u8 {
+    let guard = lock_engine();
+    match *guard {
+        Some(ref e) => e.method(),
+        None => 255,
+    }
+}
+
+/// Set the keycode layout used by `ime_key` / `ime_key_ext`.
+///
+/// # Arguments
+/// * `layout` - 0 for macOS virtual keycodes, 1 for Windows virtual-key codes,
+///   2 for Linux evdev scancodes
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_key_layout(layout: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_layout(layout);
+    }
+}
+
+/// Enable or disable the engine.
+///
+/// When disabled, `ime_key` returns action=0 (pass through).
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_enabled(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_enabled(enabled);
+    }
+}
+
+/// Set whether to skip w→ư shortcut in Telex mode.
+///
+/// When `skip` is true, typing 'w' at word start stays as 'w'
+/// instead of converting to 'ư'.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_skip_w_shortcut(skip: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_skip_w_shortcut(skip);
+    }
+}
+
+/// Set whether ESC key restores raw ASCII input.
+///
+/// When `enabled` is true (default), pressing ESC restores original keystrokes.
+/// When `enabled` is false, ESC key is passed through without restoration.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_esc_restore(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_esc_restore(enabled);
+    }
+}
+
+/// Set whether to enable free tone placement (skip validation).
+///
+/// When `enabled` is true, allows placing diacritics anywhere without
+/// spelling validation (e.g., "Zìa" is allowed).
+/// When `enabled` is false (default), validates Vietnamese spelling rules.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_free_tone(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_free_tone(enabled);
+    }
+}
+
+/// Set whether to use modern orthography for tone placement.
+///
+/// When `modern` is true: hoà, thuý (tone on second vowel - new style)
+/// When `modern` is false (default): hòa, thúy (tone on first vowel - traditional)
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_modern(modern: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_modern_tone(modern);
+    }
+}
+
+/// Set tone placement for open oa/oe syllables only.
+///
+/// - 0: traditional (hòa, khỏe)
+/// - 1: modern (hoà, khoẻ)
+///
+/// `ime_modern` sets this too; call this afterwards to override oa/oe alone.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_oa_oe_style(style: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_oa_oe_style(style);
+    }
+}
+
+/// Enable/disable English auto-restore (experimental feature).
+///
+/// When `enabled` is true, automatically restores English words that were
+/// accidentally transformed (e.g., "tẽt" → "text", "ễpct" → "expect").
+/// When `enabled` is false (default), no auto-restore happens.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_english_auto_restore(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_english_auto_restore(enabled);
+    }
+}
+
+/// Set the minimum word length for English auto-restore.
+///
+/// # Arguments
+/// * `len` - words with fewer keystrokes never auto-restore (0 = no minimum, default)
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_min_restore_len(len: usize) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_min_restore_len(len);
+    }
+}
+
+/// Enable/disable auto-capitalize after sentence-ending punctuation.
+///
+/// When `enabled` is true, automatically capitalizes the first letter
+/// after sentence-ending punctuation (. ! ? Enter).
+/// When `enabled` is false (default), no auto-capitalize happens.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_auto_capitalize(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_auto_capitalize(enabled);
+    }
+}
+
+/// Set which punctuation triggers auto-capitalize.
+///
+/// `triggers` bits: 0x01 = '.', 0x02 = '!', 0x04 = '?', 0x08 = Enter, 0x10 = ':'
+/// (default 0x0F). See `engine::CAPITALIZE_*`.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_capitalize_triggers(triggers: u8) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_capitalize_triggers(triggers);
+    }
+}
+
+/// Clear the input buffer.
+///
+/// Call on word boundaries (space, punctuation).
+/// Preserves word history for backspace-after-space feature.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_clear() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.clear();
+    }
+}
+
+/// Notify the engine of a space the app inserted itself.
+///
+/// Call when a space is inserted outside keystroke flow (e.g., autocomplete).
+/// Commits the current word to history without producing any output,
+/// so backspace-after-space still restores it.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_notify_space() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.notify_space();
+    }
+}
+
+/// Clear everything including word history.
+///
+/// Call when cursor position changes (mouse click, arrow keys, focus change).
+/// This prevents accidental restore from stale history.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_clear_all() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.clear_all();
+    }
+}
+
+/// Cancel a pending auto-capitalize, keeping buffer and word history.
+///
+/// Call after a mouse click or programmatic edit when only the
+/// capitalization intent changed.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_cancel_capitalize() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.cancel_pending_capitalize();
+    }
+}
+
+/// Get the keycodes of each modifier role for the active method.
+///
+/// Onscreen keyboards use this to highlight modifier keys without
+/// hardcoding Telex/VNI. Codes are in the active layout; unused slots
+/// are `0xFFFF`.
+///
+/// No-op if `out` is null or engine not initialized.
+///
+/// # Safety
+/// `out` must be null or point to a writable `ModifierKeys`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_modifier_keys(out: *mut ModifierKeys) {
+    if out.is_null() {
+        return;
+    }
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        *out = e.modifier_keys();
+    }
+}
+
+/// Get the full composed buffer as UTF-32 codepoints.
+///
+/// Used for "Select All + Replace" injection method where the entire
+/// buffer content is needed instead of incremental backspace + chars.
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+///
+/// # Returns
+/// Number of codepoints written to `out`.
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_get_buffer(out: *mut u32, max_len: i64) -> i64 {
+    if out.is_null() || max_len <= 0 {
+        return 0;
+    }
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        let full = e.get_buffer_string();
+        let utf32: Vec<u32> = full.chars().map(|c| c as u32).collect();
+        let len = utf32.len().min(max_len as usize);
+        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+        len as i64
+    } else {
+        0
+    }
+}
+
+/// Get the full composed buffer as a newly allocated UTF-8 C string.
+///
+/// Alternative to `ime_get_buffer` for hosts that prefer not to pass
+/// out-buffers (Python ctypes, Node FFI, ...).
+///
+/// # Ownership
+/// The caller owns the returned string and must release it with
+/// `ime_free_cstr` exactly once. Do not free it with the C `free()`.
+///
+/// # Returns
+/// Null-terminated string, or null if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_buffer_cstr() -> *mut std::os::raw::c_char {
+    let guard = lock_engine();
+    match *guard {
+        Some(ref e) => std::ffi::CString::new(e.get_buffer_string())
+            .map_or(std::ptr::null_mut(), std::ffi::CString::into_raw),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Free a string returned by `ime_buffer_cstr` or `ime_segment`.
+///
+/// # Safety
+/// * `s` must be a pointer returned by `ime_buffer_cstr` or `ime_segment`, or null
+/// * Must be called exactly once per non-null return
+/// * Do not use `s` after calling this function
+#[no_mangle]
+pub unsafe extern "C" fn ime_free_cstr(s: *mut std::os::raw::c_char) {
+    if !s.is_null() {
+        drop(std::ffi::CString::from_raw(s));
+    }
+}
+
+/// Split run-on Vietnamese text into syllables ("tôiđihọc" → "tôi đi học").
+///
+/// Works before `ime_init`. See `engine::syllable::segment`.
+///
+/// # Ownership
+/// The caller owns the returned string and must release it with `ime_free_cstr`.
+///
+/// # Returns
+/// Space-separated syllables, or null if `text` is null or not valid UTF-8.
+///
+/// # Safety
+/// `text` must be null or a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_segment(
+    text: *const std::os::raw::c_char,
+) -> *mut std::os::raw::c_char {
+    if text.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(text) = std::ffi::CStr::from_ptr(text).to_str() else {
+        return std::ptr::null_mut();
+    };
+    let joined = engine::syllable::segment(text).join(" ");
+    std::ffi::CString::new(joined).map_or(std::ptr::null_mut(), std::ffi::CString::into_raw)
+}
+
+/// Compose raw keystrokes into text with a throwaway engine ("vieetj" → "việt").
+///
+/// Stateless: works before `ime_init` and doesn't touch the global engine.
+/// Uses default options; see `engine::compose`.
+///
+/// # Arguments
+/// * `raw` - C string of keystrokes (spaces and punctuation included)
+/// * `method` - 0 = Telex, 1 = VNI
+/// * `out` - Pointer to output buffer for UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 for null or invalid UTF-8 input).
+///
+/// # Safety
+/// `raw` must be null or a valid null-terminated string.
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_compose(
+    raw: *const std::os::raw::c_char,
+    method: u8,
+    out: *mut u32,
+    max_len: i64,
+) -> i64 {
+    if raw.is_null() || out.is_null() || max_len <= 0 {
+        return 0;
+    }
+    let Ok(raw) = std::ffi::CStr::from_ptr(raw).to_str() else {
+        return 0;
+    };
+
+    let composed = engine::compose(raw, method, engine::ComposeOptions::default());
+    let utf32: Vec<u32> = composed.chars().map(|c| c as u32).collect();
+    let len = utf32.len().min(max_len as usize);
+    std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+    len as i64
+}
+
+/// Get the raw keystrokes of the current word as UTF-32 codepoints.
+///
+/// Debug helper for field issues: shows exactly what was typed
+/// (e.g., "vieetj") rather than the composed buffer ("việt").
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+///
+/// # Returns
+/// Number of codepoints written to `out`.
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_get_raw_input(out: *mut u32, max_len: i64) -> i64 {
+    if out.is_null() || max_len <= 0 {
+        return 0;
+    }
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        let raw = e.raw_input_string();
+        let utf32: Vec<u32> = raw.chars().map(|c| c as u32).collect();
+        let len = utf32.len().min(max_len as usize);
+        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+        len as i64
+    } else {
+        0
+    }
+}
+
+/// Get the break chars accumulated for prefix shortcuts as UTF-32 codepoints.
+///
+/// Debug helper: shows the pending prefix (e.g., "-" while typing "->").
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+///
+/// # Returns
+/// Number of codepoints written to `out`.
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_get_shortcut_prefix(out: *mut u32, max_len: i64) -> i64 {
+    if out.is_null() || max_len <= 0 {
+        return 0;
+    }
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        let utf32: Vec<u32> = e.shortcut_prefix_str().chars().map(|c| c as u32).collect();
+        let len = utf32.len().min(max_len as usize);
+        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+        len as i64
+    } else {
+        0
+    }
+}
+
+/// Get the edit made by the most recent `ime_key` result.
+///
+/// Lets a host that missed or dropped a `Result` recover the last edit.
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for the inserted UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+/// * `backspace` - Receives the backspace count (may be null)
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 if engine not initialized).
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+/// `backspace` must be null or point to a writable `u8`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_last_edit(out: *mut u32, max_len: i64, backspace: *mut u8) -> i64 {
+    if out.is_null() || max_len <= 0 {
+        return 0;
+    }
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        let (bs, inserted) = e.last_edit();
+        if !backspace.is_null() {
+            *backspace = bs;
+        }
+        let utf32: Vec<u32> = inserted.iter().map(|&c| c as u32).collect();
+        let len = utf32.len().min(max_len as usize);
+        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+        len as i64
+    } else {
+        0
+    }
+}
+
+/// Get the tone and mark of the current word's diacritic vowel.
+///
+/// For UI that shows the active diacritic without parsing the composed text.
+/// See `Engine::current_tone_mark`.
+///
+/// # Arguments
+/// * `tone` - Receives 0=none, 1=circumflex, 2=horn/breve (may be null)
+/// * `mark` - Receives 0=none, 1=sắc, 2=huyền, 3=hỏi, 4=ngã, 5=nặng (may be null)
+///
+/// Writes (0, 0) if the buffer is empty or engine not initialized.
+///
+/// # Safety
+/// `tone` and `mark` must each be null or point to a writable `u8`.
+#[no_mangle]
+pub unsafe extern "C" fn ime_current_tone_mark(tone: *mut u8, mark: *mut u8) {
+    let guard = lock_engine();
+    let (t, m) = guard.as_ref().map_or((0, 0), |e| e.current_tone_mark());
+    if !tone.is_null() {
+        *tone = t;
+    }
+    if !mark.is_null() {
+        *mark = m;
+    }
+}
+
+/// Take the engine's decision trace as newline-joined UTF-32 text.
+///
+/// Only built with the `trace` cargo feature. Entries are cleared by the call,
+/// and anything past `max_len` codepoints is dropped.
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for UTF-32 codepoints
+/// * `max_len` - Maximum number of codepoints to write
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 if engine not initialized).
+///
+/// # Safety
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[cfg(feature = "trace")]
+#[no_mangle]
+pub unsafe extern "C" fn ime_take_trace(out: *mut u32, max_len: i64) -> i64 {
+    if out.is_null() || max_len <= 0 {
+        return 0;
+    }
+
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let text = e.take_trace().join("\n");
+        let utf32: Vec<u32> = text.chars().map(|c| c as u32).collect();
+        let len = utf32.len().min(max_len as usize);
+        std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+        len as i64
+    } else {
+        0
+    }
+}
+
+/// Dump the engine state as a binary snapshot (for bug reports).
+///
+/// Call with `out` null or `max_len` 0 to query the required size.
+///
+/// # Arguments
+/// * `out` - Pointer to output buffer for the snapshot bytes
+/// * `max_len` - Size of `out` in bytes
+///
+/// # Returns
+/// Snapshot size in bytes; nothing is written if it exceeds `max_len`.
+/// 0 if engine not initialized.
+///
+/// # Safety
+/// `out` must be null or point to valid memory of at least `max_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_snapshot(out: *mut u8, max_len: i64) -> i64 {
+    let guard = lock_engine();
+    let Some(ref e) = *guard else {
+        return 0;
+    };
+    let blob = e.snapshot();
+    if !out.is_null() && max_len >= blob.len() as i64 {
+        std::ptr::copy_nonoverlapping(blob.as_ptr(), out, blob.len());
+    }
+    blob.len() as i64
+}
+
+/// Replace the engine with one loaded from an `ime_snapshot` blob.
+///
+/// # Returns
+/// `true` if loaded; `false` (engine unchanged) for null or invalid data.
+///
+/// # Safety
+/// `data` must be null or point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_load_snapshot(data: *const u8, len: usize) -> bool {
+    if data.is_null() {
+        return false;
+    }
+    let bytes = std::slice::from_raw_parts(data, len);
+    match Engine::from_snapshot(bytes) {
+        Some(e) => {
+            *lock_engine() = Some(e);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Tell the engine the host deleted `count` chars without calling `ime_key`.
+///
+/// Use when a backspace bypassed the engine (e.g. key swallowed by the app)
+/// so the composing word stays in sync with the screen.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_notify_external_delete(count: usize) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.notify_external_delete(count);
+    }
+}
+
+/// Get how Vietnamese the current word looks (0-100).
+///
+/// See `Engine::vietnamese_confidence`. Returns 0 if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_confidence() -> u8 {
+    let guard = lock_engine();
+    guard.as_ref().map_or(0, |e| e.vietnamese_confidence())
+}
+
+/// Reset the prefix shortcut accumulator, keeping the current word.
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_clear_shortcut_prefix() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.clear_shortcut_prefix();
+    }
+}
+
+/// Check whether the host should swallow the key that produced `r`.
+///
+/// Same as `Result::should_suppress_key`. Returns false if `r` is null.
+///
+/// # Safety
+/// `r` must be null or a pointer returned by `ime_key` and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ime_should_suppress(r: *const Result) -> bool {
+    !r.is_null() && (*r).should_suppress_key()
+}
+
+/// Free a result pointer returned by `ime_key`.
+///
+/// # Safety
+/// * `r` must be a pointer returned by `ime_key`, or null
+/// * Must be called exactly once per non-null `ime_key` return
+/// * Do not use `r` after calling this function
+#[no_mangle]
+pub unsafe extern "C" fn ime_free(r: *mut Result) {
+    if !r.is_null() {
+        drop(Box::from_raw(r));
+    }
+}
+
+/// Free a result pointer returned by `ime_key_utf16`.
+///
+/// # Safety
+/// * `r` must be a pointer returned by `ime_key_utf16`, or null
+/// * Must be called exactly once per non-null `ime_key_utf16` return
+/// * Do not use `r` after calling this function
+#[no_mangle]
+pub unsafe extern "C" fn ime_free_u16(r: *mut ResultU16) {
+    if !r.is_null() {
+        drop(Box::from_raw(r));
+    }
+}
+
+// ============================================================
+// Shortcut FFI
+// ============================================================
+
+/// Add a shortcut to the engine.
+///
+/// # Arguments
+/// * `trigger` - C string for trigger (e.g., "vn")
+/// * `replacement` - C string for replacement (e.g., "Việt Nam")
+///
+/// # Safety
+/// Both pointers must be valid null-terminated UTF-8 strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_add_shortcut(
+    trigger: *const std::os::raw::c_char,
+    replacement: *const std::os::raw::c_char,
+) {
+    ime_add_shortcut_method(trigger, replacement, 0);
+}
+
+/// Add a shortcut that only applies to one input method.
+///
+/// # Arguments
+/// * `trigger` - C string for trigger (e.g., "d9")
+/// * `replacement` - C string for replacement
+/// * `method` - 0 = All, 1 = Telex only, 2 = VNI only (same values as
+///   `ime_shortcut_method_at`); other values are ignored
+///
+/// # Safety
+/// Both pointers must be valid null-terminated UTF-8 strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_add_shortcut_method(
+    trigger: *const std::os::raw::c_char,
+    replacement: *const std::os::raw::c_char,
+    method: u8,
+) {
+    use engine::shortcut::InputMethod;
+
+    if trigger.is_null() || replacement.is_null() {
+        return;
+    }
+    let input_method = match method {
+        0 => InputMethod::All,
+        1 => InputMethod::Telex,
+        2 => InputMethod::Vni,
+        _ => return,
+    };
+
+    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let replacement_str = match std::ffi::CStr::from_ptr(replacement).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        // Auto-detect shortcut type:
+        // - If trigger contains only non-letter chars (like "->", "=>"), use immediate trigger
+        // - Otherwise use word boundary trigger (traditional abbreviations like "vn" → "Việt Nam")
+        let is_symbol_trigger = trigger_str.chars().all(|c| !c.is_alphabetic());
+        let shortcut = if is_symbol_trigger {
+            engine::shortcut::Shortcut::immediate(trigger_str, replacement_str)
+        } else {
+            engine::shortcut::Shortcut::new(trigger_str, replacement_str)
+        };
+        e.shortcuts_mut().add(shortcut.for_method(input_method));
+    }
+}
+
+/// Remove a shortcut from the engine.
+///
+/// # Arguments
+/// * `trigger` - C string for trigger to remove
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_remove_shortcut(trigger: *const std::os::raw::c_char) {
+    if trigger.is_null() {
+        return;
+    }
+
+    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.shortcuts_mut().remove(trigger_str);
+    }
+}
+
+/// Check whether a shortcut exists for a trigger.
+///
+/// Case-insensitive, matching how triggers are typed ("KO" finds "ko").
+/// Returns false if engine not initialized or the string is invalid.
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_has_shortcut(trigger: *const std::os::raw::c_char) -> bool {
     if trigger.is_null() {
+        return false;
+    }
+
+    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
+        Ok(s) => s,
+        Err(_) => return false,
+    };
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        e.shortcuts().contains(trigger_str)
+    } else {
+        false
+    }
+}
+
+/// Enable or disable built-in Vietnamese symbol shortcuts.
+///
+/// When `enabled` is true, "(dong)" → "₫" and "(do)" → "°" are added to the
+/// shortcut table; when false they are removed. User shortcuts are untouched.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_enable_symbol_shortcuts(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_symbol_shortcuts(enabled);
+    }
+}
+
+/// Set which break chars fire word-boundary shortcuts.
+///
+/// # Arguments
+/// * `terminators` - C string of terminator chars; "\n" stands for Enter and
+///   "\t" for Tab (e.g., " \n" expands on Space and Enter). Default: " ".
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_shortcut_terminators(terminators: *const std::os::raw::c_char) {
+    if terminators.is_null() {
+        return;
+    }
+
+    let terminators_str = match std::ffi::CStr::from_ptr(terminators).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        let chars: Vec<char> = terminators_str.chars().collect();
+        e.shortcuts_mut().set_terminators(&chars);
+    }
+}
+
+/// Set whether shortcut triggers match with diacritics ignored.
+///
+/// When `enabled` is true, "việt" fires a shortcut with trigger "viet"
+/// (exact triggers still win). Default: OFF.
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_shortcut_accent_insensitive(enabled: bool) {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.shortcuts_mut().set_accent_insensitive(enabled);
+    }
+}
+
+/// Clear all shortcuts from the engine.
+#[no_mangle]
+pub extern "C" fn ime_clear_shortcuts() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.shortcuts_mut().clear();
+    }
+}
+
+/// Get the number of shortcuts in the engine.
+///
+/// Returns 0 if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_shortcut_count() -> usize {
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        e.shortcuts().len()
+    } else {
+        0
+    }
+}
+
+/// Copy a null-terminated UTF-32 string into `out`, truncating to fit.
+unsafe fn write_utf32(s: &str, out: *mut u32, max: usize) {
+    let utf32: Vec<u32> = s.chars().map(|c| c as u32).collect();
+    let len = utf32.len().min(max - 1);
+    std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+    *out.add(len) = 0;
+}
+
+/// Get the shortcut at `index` as null-terminated UTF-32 strings.
+///
+/// Order matches `ime_shortcut_method_at` and is stable until the
+/// shortcut table is modified. Strings longer than `max - 1` codepoints
+/// are truncated.
+///
+/// # Arguments
+/// * `index` - Shortcut index (0..ime_shortcut_count())
+/// * `trigger_out` - Output buffer for trigger codepoints
+/// * `repl_out` - Output buffer for replacement codepoints
+/// * `max` - Capacity of each output buffer in codepoints (including terminator)
+///
+/// # Returns
+/// true if the shortcut exists and was written, false otherwise.
+///
+/// # Safety
+/// `trigger_out` and `repl_out` must each point to valid memory of at least
+/// `max * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_shortcut_at(
+    index: usize,
+    trigger_out: *mut u32,
+    repl_out: *mut u32,
+    max: usize,
+) -> bool {
+    if trigger_out.is_null() || repl_out.is_null() || max == 0 {
+        return false;
+    }
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        if let Some(s) = e.shortcuts().iter().nth(index) {
+            write_utf32(&s.trigger, trigger_out, max);
+            write_utf32(&s.replacement, repl_out, max);
+            return true;
+        }
+    }
+    false
+}
+
+/// Get the input method the shortcut at `index` applies to.
+///
+/// # Returns
+/// 0 = All, 1 = Telex only, 2 = VNI only, 255 if index is out of range
+/// or engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_shortcut_method_at(index: usize) -> u8 {
+    use engine::shortcut::InputMethod;
+
+    let guard = lock_engine();
+    if let Some(ref e) = *guard {
+        if let Some(s) = e.shortcuts().iter().nth(index) {
+            return match s.input_method {
+                InputMethod::All => 0,
+                InputMethod::Telex => 1,
+                InputMethod::Vni => 2,
+            };
+        }
+    }
+    255
+}
+
+// ============================================================
+// Protected Words FFI
+// ============================================================
+
+/// Add a word that should never get Vietnamese transforms.
+///
+/// While the typed keys are a prefix of a protected word (case-insensitive),
+/// letters are kept raw. Useful for brand names and technical terms
+/// (e.g., "React", "css").
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_add_protected_word(word: *const std::os::raw::c_char) {
+    if word.is_null() {
+        return;
+    }
+
+    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.add_protected_word(word_str);
+    }
+}
+
+/// Clear all protected words.
+///
+/// No-op if engine not initialized.
+#[no_mangle]
+pub extern "C" fn ime_clear_protected_words() {
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.clear_protected_words();
+    }
+}
+
+// ============================================================
+// Validation FFI
+// ============================================================
+
+/// Check if a standalone word is a valid Vietnamese syllable.
+///
+/// Does not touch the engine state, so it works before `ime_init`.
+///
+/// # Returns
+/// `false` for null, invalid UTF-8, empty or non-Vietnamese input.
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string or null.
+#[no_mangle]
+pub unsafe extern "C" fn ime_validate_word(word: *const std::os::raw::c_char) -> bool {
+    if word.is_null() {
+        return false;
+    }
+
+    match std::ffi::CStr::from_ptr(word).to_str() {
+        Ok(s) => engine::validation::is_valid_word(s),
+        Err(_) => false,
+    }
+}
+
+// ============================================================
+// Conversion FFI
+// ============================================================
+
+/// Run a string conversion from a C string into a UTF-32 output buffer.
+unsafe fn convert_to_utf32(
+    word: *const std::os::raw::c_char,
+    out: *mut u32,
+    max_len: i64,
+    convert: fn(&str) -> String,
+) -> i64 {
+    if word.is_null() || out.is_null() || max_len <= 0 {
+        return 0;
+    }
+    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    let utf32: Vec<u32> = convert(word_str).chars().map(|c| c as u32).collect();
+    let len = utf32.len().min(max_len as usize);
+    std::ptr::copy_nonoverlapping(utf32.as_ptr(), out, len);
+    len as i64
+}
+
+/// Convert Vietnamese text to Telex keystrokes ("việt" → "vieejt").
+///
+/// Does not touch the engine state, so it works before `ime_init`.
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 for null or invalid UTF-8).
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string or null.
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_to_telex_ascii(
+    word: *const std::os::raw::c_char,
+    out: *mut u32,
+    max_len: i64,
+) -> i64 {
+    convert_to_utf32(word, out, max_len, utils::to_telex_ascii)
+}
+
+/// Convert Vietnamese text to VNI keystrokes ("việt" → "vie65t").
+///
+/// Does not touch the engine state, so it works before `ime_init`.
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 for null or invalid UTF-8).
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string or null.
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_to_vni_ascii(
+    word: *const std::os::raw::c_char,
+    out: *mut u32,
+    max_len: i64,
+) -> i64 {
+    convert_to_utf32(word, out, max_len, utils::to_vni_ascii)
+}
+
+/// Strip Vietnamese diacritics ("Việt Nam" → "Viet Nam"), e.g. for URL slugs.
+///
+/// Does not touch the engine state, so it works before `ime_init`.
+///
+/// # Returns
+/// Number of codepoints written to `out` (0 for null or invalid UTF-8).
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string or null.
+/// `out` must point to valid memory of at least `max_len * sizeof(u32)` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_to_ascii_folded(
+    word: *const std::os::raw::c_char,
+    out: *mut u32,
+    max_len: i64,
+) -> i64 {
+    convert_to_utf32(word, out, max_len, utils::to_ascii_folded)
+}
+
+// ============================================================
+// Word Restore FFI
+// ============================================================
+
+/// Restore buffer from a Vietnamese word string.
+///
+/// Used when native app detects cursor at word boundary and user
+/// wants to continue editing (e.g., backspace into previous word).
+/// Parses Vietnamese characters back to buffer components.
+///
+/// # Arguments
+/// * `word` - C string containing the Vietnamese word to restore
+///
+/// # Safety
+/// Pointer must be a valid null-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ime_restore_word(word: *const std::os::raw::c_char) {
+    if word.is_null() {
+        return;
+    }
+    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.restore_word(word_str);
+    }
+}
+
+/// Restore buffer from a Vietnamese word and the keycodes that typed it.
+///
+/// Like `ime_restore_word`, but ESC restore gives back exactly these keys
+/// instead of guessed ones (e.g., "việt" with V,I,E,6,T,5 in VNI).
+///
+/// # Arguments
+/// * `word` - C string containing the Vietnamese word to restore
+/// * `raw_keys` - Keycodes in typing order (may be null if `len` is 0)
+/// * `len` - Number of keycodes
+///
+/// # Safety
+/// `word` must be a valid null-terminated UTF-8 string; `raw_keys` must point
+/// to at least `len` keycodes.
+#[no_mangle]
+pub unsafe extern "C" fn ime_restore_with_raw(
+    word: *const std::os::raw::c_char,
+    raw_keys: *const u16,
+    len: usize,
+) {
+    if word.is_null() || (raw_keys.is_null() && len > 0) {
+        return;
+    }
+    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let keys = if len == 0 {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(raw_keys, len)
+    };
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.restore_with_raw(word_str, keys);
+    }
+}
+
+/// Sync the engine with the text around the cursor.
+///
+/// Call when the app moves the cursor or edits text outside the engine
+/// (mouse click, arrow keys, paste). The engine restores the word to the
+/// left of the cursor for editing, or stays empty if the cursor is inside a word.
+///
+/// # Arguments
+/// * `before` - C string with text left of the cursor (at least the current word)
+/// * `after` - C string with text right of the cursor (may be empty)
+///
+/// # Safety
+/// Both pointers must be valid null-terminated UTF-8 strings.
+#[no_mangle]
+pub unsafe extern "C" fn ime_set_cursor_context(
+    before: *const std::os::raw::c_char,
+    after: *const std::os::raw::c_char,
+) {
+    if before.is_null() || after.is_null() {
         return;
     }
+    let before_str = match std::ffi::CStr::from_ptr(before).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let after_str = match std::ffi::CStr::from_ptr(after).to_str() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let mut guard = lock_engine();
+    if let Some(ref mut e) = *guard {
+        e.set_cursor_context(before_str, after_str);
+    }
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::keys;
+    use serial_test::serial;
+    use std::ffi::CString;
+
+    #[test]
+    #[serial]
+    fn test_ffi_flow() {
+        ime_init();
+        ime_method(0); // Telex
+
+        // Type 'a' + 's' -> á
+        let r1 = ime_key(keys::A, false, false);
+        assert!(!r1.is_null());
+        unsafe { ime_free(r1) };
+
+        let r2 = ime_key(keys::S, false, false);
+        assert!(!r2.is_null());
+        unsafe {
+            assert_eq!((*r2).chars[0], 'á' as u32);
+            ime_free(r2);
+        }
+
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_init_status_ffi() {
+        *lock_engine() = None;
+        assert_eq!(ime_init_status(), 0, "fresh init");
+        assert_eq!(ime_init_status(), 1, "replaced existing engine");
+
+        // Poison the mutex by panicking while holding it
+        let _ = std::thread::spawn(|| {
+            let _guard = ENGINE.lock();
+            panic!("poison");
+        })
+        .join();
+        assert!(ENGINE.is_poisoned());
+        assert_eq!(ime_init_status(), -1, "recovered from poison");
+        assert!(!ENGINE.is_poisoned());
+        assert_eq!(ime_init_status(), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_buffer_cstr_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T, keys::J] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let s = ime_buffer_cstr();
+        assert!(!s.is_null());
+        unsafe {
+            assert_eq!(std::ffi::CStr::from_ptr(s).to_str().unwrap(), "việt");
+            ime_free_cstr(s);
+            ime_free_cstr(std::ptr::null_mut()); // null is a no-op
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_last_edit_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        for key in [keys::A, keys::S] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let mut out = [0u32; 8];
+        let mut bs = 0u8;
+        let len = unsafe { ime_last_edit(out.as_mut_ptr(), 8, &mut bs) };
+        assert_eq!(bs, 1);
+        assert_eq!(&out[..len as usize], &['á' as u32]);
+
+        // Character input records its edit too
+        ime_clear();
+        for ch in ['e', 'e'] {
+            let r = ime_key_char(ch as u32, false, false, false);
+            unsafe { ime_free(r) };
+        }
+        let len = unsafe { ime_last_edit(out.as_mut_ptr(), 8, &mut bs) };
+        assert_eq!(bs, 1);
+        assert_eq!(&out[..len as usize], &['ê' as u32]);
+        ime_clear();
+    }
+
+    #[test]
+    fn test_segment_ffi() {
+        let text = CString::new("tôiđihọc").unwrap();
+        let s = unsafe { ime_segment(text.as_ptr()) };
+        assert!(!s.is_null());
+        unsafe {
+            assert_eq!(std::ffi::CStr::from_ptr(s).to_str().unwrap(), "tôi đi học");
+            ime_free_cstr(s);
+        }
+        assert!(unsafe { ime_segment(std::ptr::null()) }.is_null());
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_ffi() {
+        ime_init();
+        ime_method(0);
+        ime_clear();
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let size = unsafe { ime_snapshot(std::ptr::null_mut(), 0) };
+        assert!(size > 0);
+        let mut blob = vec![0u8; size as usize];
+        assert_eq!(unsafe { ime_snapshot(blob.as_mut_ptr(), size) }, size);
+
+        ime_clear();
+        assert!(unsafe { ime_load_snapshot(blob.as_ptr(), blob.len()) });
+        let r = ime_key(keys::J, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).chars[0], 'ệ' as u32);
+            ime_free(r);
+        }
+
+        assert!(!unsafe { ime_load_snapshot(blob.as_ptr(), 3) });
+        assert!(!unsafe { ime_load_snapshot(std::ptr::null(), 0) });
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_notify_external_delete_ffi() {
+        ime_init();
+        ime_method(0);
+        ime_clear();
+        for key in [keys::V, keys::I, keys::E, keys::T] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        // Host swallowed a backspace: "viet" → "vie" on screen
+        ime_notify_external_delete(1);
+        let r = ime_key(keys::E, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).backspace, 1);
+            assert_eq!((*r).chars[0], 'ê' as u32);
+            ime_free(r);
+        }
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_compose_ffi() {
+        let raw = CString::new("vieetj nam").unwrap();
+        let mut out = [0u32; 32];
+        let len = unsafe { ime_compose(raw.as_ptr(), 0, out.as_mut_ptr(), 32) };
+        let text: String = out[..len as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        assert_eq!(text, "việt nam");
+
+        // Truncated to max_len; null input writes nothing
+        assert_eq!(
+            unsafe { ime_compose(raw.as_ptr(), 0, out.as_mut_ptr(), 4) },
+            4
+        );
+        assert_eq!(
+            unsafe { ime_compose(std::ptr::null(), 0, out.as_mut_ptr(), 32) },
+            0
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_auto_capitalize_ffi() {
+        ime_init();
+        ime_method(0);
+        ime_clear_all();
+        ime_auto_capitalize(true);
+
+        for key in [keys::DOT, keys::SPACE] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        let r = ime_key(keys::A, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert!((*r).auto_capitalized());
+            assert_eq!((*r).chars[0], 'A' as u32);
+            ime_free(r);
+        }
+
+        ime_auto_capitalize(false);
+        ime_clear_all();
+    }
+
+    #[test]
+    #[serial]
+    fn test_key_utf16_ffi() {
+        ime_init();
+        ime_method(0);
+        ime_clear_shortcuts();
+        ime_clear();
+
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        let r = ime_key_utf16(keys::J, false, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            let res = &*r;
+            assert_eq!(res.backspace, 2);
+            assert_eq!(res.count, 2);
+            assert_eq!(res.chars[..2], ['ệ' as u16, 't' as u16]);
+            assert_eq!(res.tone_index, 0);
+            ime_free_u16(r);
+        }
+
+        // Chars outside the BMP become surrogate pairs
+        let trigger = CString::new("cf").unwrap();
+        let replacement = CString::new("☕😀").unwrap();
+        unsafe { ime_add_shortcut(trigger.as_ptr(), replacement.as_ptr()) };
+        ime_clear();
+        for key in [keys::C, keys::F] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        let r = ime_key_utf16(keys::SPACE, false, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            let res = &*r;
+            let units = &res.chars[..res.count as usize];
+            assert_eq!(String::from_utf16(units).unwrap(), "☕😀 ");
+            assert_eq!(units.len(), 4);
+            ime_free_u16(r);
+        }
+
+        ime_clear_shortcuts();
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_should_suppress_ffi() {
+        ime_init();
+        ime_method(0);
+        ime_clear_shortcuts();
+        ime_clear();
+
+        // Plain letter passes through
+        let r = ime_key(keys::A, false, false);
+        unsafe {
+            assert!(!ime_should_suppress(r));
+            ime_free(r);
+        }
+        // Tone key edits the word
+        let r = ime_key(keys::S, false, false);
+        unsafe {
+            assert!(ime_should_suppress(r));
+            ime_free(r);
+            assert!(!ime_should_suppress(std::ptr::null()));
+        }
+
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_with_raw_ffi() {
+        ime_init();
+        ime_method(1);
+        ime_esc_restore(true);
+        ime_clear();
+
+        let word = CString::new("việt").unwrap();
+        let raw = [keys::V, keys::I, keys::E, keys::N6, keys::T, keys::N5];
+        unsafe { ime_restore_with_raw(word.as_ptr(), raw.as_ptr(), raw.len()) };
+        let r = ime_key(keys::ESC, false, false);
+        unsafe {
+            let res = &*r;
+            let out: String = res.chars[..res.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c))
+                .collect();
+            assert_eq!(res.backspace, 4);
+            assert_eq!(out, "vie6t5");
+            ime_free(r);
+        }
+
+        ime_method(0);
+        ime_esc_restore(false);
+        ime_clear();
+    }
 
-    let trigger_str = match std::ffi::CStr::from_ptr(trigger).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
-    };
+    #[test]
+    #[serial]
+    fn test_feature_flags_ffi() {
+        ime_init();
+        let defaults = ime_get_flags();
+        assert_ne!(defaults & engine::FEATURE_MODERN_TONE, 0);
+        assert_eq!(defaults & engine::FEATURE_ENGLISH_AUTO_RESTORE, 0);
 
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.shortcuts_mut().remove(trigger_str);
+        ime_set_flags(engine::FEATURE_ENGLISH_AUTO_RESTORE | engine::FEATURE_SMART_BACKSPACE);
+        assert_eq!(
+            ime_get_flags(),
+            engine::FEATURE_ENGLISH_AUTO_RESTORE | engine::FEATURE_SMART_BACKSPACE
+        );
+        // Same as the single-feature call
+        ime_modern(true);
+        assert_ne!(ime_get_flags() & engine::FEATURE_MODERN_TONE, 0);
+
+        ime_set_flags(defaults);
+        assert_eq!(ime_get_flags(), defaults);
     }
-}
 
-/// Clear all shortcuts from the engine.
-#[no_mangle]
-pub extern "C" fn ime_clear_shortcuts() {
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.shortcuts_mut().clear();
+    #[test]
+    #[serial]
+    fn test_current_tone_mark_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+        ime_clear();
+
+        let (mut tone, mut mark) = (9u8, 9u8);
+        unsafe { ime_current_tone_mark(&mut tone, &mut mark) };
+        assert_eq!((tone, mark), (0, 0));
+
+        // "vieetj" → việt: circumflex + nặng
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T, keys::J] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        unsafe { ime_current_tone_mark(&mut tone, &mut mark) };
+        assert_eq!((tone, mark), (1, 5));
+        unsafe { ime_current_tone_mark(std::ptr::null_mut(), std::ptr::null_mut()) };
+        ime_clear();
     }
-}
 
-// ============================================================
-// Word Restore FFI
-// ============================================================
+    #[test]
+    #[serial]
+    fn test_swap_hoi_nga_ffi() {
+        ime_init();
+        ime_method(0); // Telex
 
-/// Restore buffer from a Vietnamese word string.
-///
-/// Used when native app detects cursor at word boundary and user
-/// wants to continue editing (e.g., backspace into previous word).
-/// Parses Vietnamese characters back to buffer components.
-///
-/// # Arguments
-/// * `word` - C string containing the Vietnamese word to restore
-///
-/// # Safety
-/// Pointer must be a valid null-terminated UTF-8 string.
-#[no_mangle]
-pub unsafe extern "C" fn ime_restore_word(word: *const std::os::raw::c_char) {
-    if word.is_null() {
-        return;
+        // "mir" → mỉ
+        for key in [keys::M, keys::I, keys::R] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let r = ime_swap_hoi_nga();
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).action, 1);
+            assert_eq!((*r).backspace, 1);
+            assert_eq!((*r).chars[0], 'ĩ' as u32);
+            ime_free(r);
+        }
+
+        ime_clear();
     }
-    let word_str = match std::ffi::CStr::from_ptr(word).to_str() {
-        Ok(s) => s,
-        Err(_) => return,
-    };
-    let mut guard = lock_engine();
-    if let Some(ref mut e) = *guard {
-        e.restore_word(word_str);
+
+    #[test]
+    #[serial]
+    fn test_tick_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+        ime_idle_commit_ms(500);
+
+        for key in [keys::A, keys::S] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let r = ime_tick(600);
+        assert!(!r.is_null());
+        unsafe { ime_free(r) };
+
+        // Word committed: 's' now starts a new word instead of toggling the mark
+        let r = ime_key(keys::S, false, false);
+        unsafe {
+            assert_eq!((*r).action, 0);
+            ime_free(r);
+        }
+
+        ime_idle_commit_ms(0);
+        ime_clear();
     }
-}
 
-// ============================================================
-// Tests
-// ============================================================
+    #[test]
+    #[serial]
+    fn test_get_method_ffi() {
+        ime_init();
+        assert_eq!(ime_get_method(), 0, "Default method should be Telex");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::data::keys;
-    use serial_test::serial;
-    use std::ffi::CString;
+        ime_method(1);
+        assert_eq!(ime_get_method(), 1);
+
+        ime_method(0);
+        assert_eq!(ime_get_method(), 0);
+    }
 
     #[test]
     #[serial]
-    fn test_ffi_flow() {
+    fn test_key_layout_ffi() {
         ime_init();
         ime_method(0); // Telex
+        ime_key_layout(keys::LAYOUT_WINDOWS);
 
-        // Type 'a' + 's' -> á
-        let r1 = ime_key(keys::A, false, false);
-        assert!(!r1.is_null());
+        // Windows VK 'A' (0x41) + 'S' (0x53) -> á
+        let r1 = ime_key(0x41, false, false);
         unsafe { ime_free(r1) };
-
-        let r2 = ime_key(keys::S, false, false);
+        let r2 = ime_key(0x53, false, false);
         assert!(!r2.is_null());
         unsafe {
             assert_eq!((*r2).chars[0], 'á' as u32);
             ime_free(r2);
         }
 
+        ime_key_layout(keys::LAYOUT_MACOS);
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_key_char_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+                       // Layout is ignored for characters
+        ime_key_layout(keys::LAYOUT_WINDOWS);
+
+        let r1 = ime_key_char('A' as u32, false, false, false);
+        unsafe { ime_free(r1) };
+        let r2 = ime_key_char('s' as u32, false, false, false);
+        assert!(!r2.is_null());
+        unsafe {
+            assert_eq!((*r2).chars[0], 'Á' as u32);
+            ime_free(r2);
+        }
+
+        // Invalid scalar (surrogate) → null
+        assert!(ime_key_char(0xD800, false, false, false).is_null());
+
+        ime_key_layout(keys::LAYOUT_MACOS);
         ime_clear();
     }
 
@@ -509,6 +2449,105 @@ mod tests {
         ime_clear();
     }
 
+    #[test]
+    #[serial]
+    fn test_shortcut_ffi_enumerate() {
+        ime_init();
+        ime_clear_shortcuts();
+
+        let trigger = CString::new("hcm").unwrap();
+        let replacement = CString::new("Hồ Chí Minh").unwrap();
+        unsafe {
+            ime_add_shortcut(trigger.as_ptr(), replacement.as_ptr());
+        }
+        assert_eq!(ime_shortcut_count(), 1);
+        let upper = CString::new("HCM").unwrap();
+        let missing = CString::new("vn").unwrap();
+        unsafe {
+            assert!(ime_has_shortcut(upper.as_ptr()));
+            assert!(!ime_has_shortcut(missing.as_ptr()));
+            assert!(!ime_has_shortcut(std::ptr::null()));
+        }
+
+        let mut trigger_out = [0u32; 32];
+        let mut repl_out = [0u32; 32];
+        let found =
+            unsafe { ime_shortcut_at(0, trigger_out.as_mut_ptr(), repl_out.as_mut_ptr(), 32) };
+        assert!(found);
+        let to_string = |buf: &[u32]| -> String {
+            buf.iter()
+                .take_while(|&&c| c != 0)
+                .filter_map(|&c| char::from_u32(c))
+                .collect()
+        };
+        assert_eq!(to_string(&trigger_out), "hcm");
+        assert_eq!(to_string(&repl_out), "Hồ Chí Minh");
+        assert_eq!(ime_shortcut_method_at(0), 0); // All
+
+        // Out of range
+        let found =
+            unsafe { ime_shortcut_at(1, trigger_out.as_mut_ptr(), repl_out.as_mut_ptr(), 32) };
+        assert!(!found);
+        assert_eq!(ime_shortcut_method_at(1), 255);
+
+        // Truncated to fit buffer (including terminator)
+        let mut small = [0u32; 4];
+        let found = unsafe { ime_shortcut_at(0, trigger_out.as_mut_ptr(), small.as_mut_ptr(), 4) };
+        assert!(found);
+        assert_eq!(to_string(&small), "Hồ ");
+
+        ime_clear_shortcuts();
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_shortcut_ffi_add_for_method() {
+        ime_init();
+        ime_clear_shortcuts();
+
+        let trigger = CString::new("vn").unwrap();
+        let replacement = CString::new("Việt Nam").unwrap();
+        unsafe {
+            ime_add_shortcut_method(trigger.as_ptr(), replacement.as_ptr(), 2);
+            // Unknown method is ignored
+            ime_add_shortcut_method(trigger.as_ptr(), replacement.as_ptr(), 9);
+        }
+        assert_eq!(ime_shortcut_count(), 1);
+        assert_eq!(ime_shortcut_method_at(0), 2); // VNI only
+
+        // Not expanded in Telex
+        ime_method(0);
+        ime_clear();
+        for key in [keys::V, keys::N, keys::SPACE] {
+            let r = ime_key(key, false, false);
+            if !r.is_null() {
+                unsafe {
+                    assert_ne!((*r).chars[0], 'V' as u32);
+                    ime_free(r);
+                }
+            }
+        }
+
+        // Expanded in VNI
+        ime_method(1);
+        ime_clear();
+        let mut last = std::ptr::null_mut();
+        for key in [keys::V, keys::N, keys::SPACE] {
+            unsafe { ime_free(last) };
+            last = ime_key(key, false, false);
+        }
+        assert!(!last.is_null());
+        unsafe {
+            assert_eq!((*last).chars[0], 'V' as u32);
+            ime_free(last);
+        }
+
+        ime_method(0);
+        ime_clear_shortcuts();
+        ime_clear();
+    }
+
     #[test]
     #[serial]
     fn test_shortcut_ffi_null_safety() {
@@ -674,6 +2713,174 @@ mod tests {
         ime_clear();
     }
 
+    #[test]
+    #[serial]
+    fn test_protected_word_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        let word = CString::new("Rust").unwrap();
+        unsafe {
+            ime_add_protected_word(word.as_ptr());
+            ime_add_protected_word(std::ptr::null());
+        }
+
+        // "rus" stays raw: 's' is not applied as sắc mark
+        for key in [keys::R, keys::U, keys::S] {
+            let r = ime_key(key, false, false);
+            assert!(!r.is_null());
+            unsafe {
+                assert_eq!((*r).action, 0, "Protected word should pass through");
+                ime_free(r);
+            }
+        }
+
+        ime_clear_protected_words();
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_raw_input_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        for key in [keys::V, keys::I, keys::E, keys::E, keys::T, keys::J] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+
+        let mut out = [0u32; 16];
+        let len = unsafe { ime_get_raw_input(out.as_mut_ptr(), out.len() as i64) };
+        let raw: String = out[..len as usize]
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+            .collect();
+        assert_eq!(raw, "vieetj");
+
+        // Truncated to max_len
+        let len = unsafe { ime_get_raw_input(out.as_mut_ptr(), 3) };
+        assert_eq!(len, 3);
+
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_shortcut_prefix_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        let r = ime_key(keys::MINUS, false, false);
+        unsafe { ime_free(r) };
+
+        let mut out = [0u32; 8];
+        let len = unsafe { ime_get_shortcut_prefix(out.as_mut_ptr(), out.len() as i64) };
+        assert_eq!(len, 1);
+        assert_eq!(out[0], '-' as u32);
+
+        ime_clear_shortcut_prefix();
+        let len = unsafe { ime_get_shortcut_prefix(out.as_mut_ptr(), out.len() as i64) };
+        assert_eq!(len, 0);
+
+        ime_clear();
+    }
+
+    #[test]
+    fn test_validate_word_ffi() {
+        let valid = CString::new("tiếng").unwrap();
+        let invalid = CString::new("chrome").unwrap();
+        unsafe {
+            assert!(ime_validate_word(valid.as_ptr()));
+            assert!(!ime_validate_word(invalid.as_ptr()));
+            assert!(!ime_validate_word(std::ptr::null()));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_set_cursor_context_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+
+        let before = CString::new("xin chào việt").unwrap();
+        let after = CString::new("").unwrap();
+        unsafe {
+            ime_set_cursor_context(before.as_ptr(), after.as_ptr());
+            ime_set_cursor_context(std::ptr::null(), after.as_ptr());
+        }
+
+        // Null call is a no-op: "việt" is still restored, 's' changes the mark
+        let r = ime_key(keys::S, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).action, 1, "Should send replacement");
+            ime_free(r);
+        }
+
+        ime_clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_notify_space_ffi() {
+        ime_init();
+        ime_method(0); // Telex
+        ime_clear_all();
+
+        for key in [keys::A, keys::F] {
+            let r = ime_key(key, false, false);
+            unsafe { ime_free(r) };
+        }
+        ime_notify_space();
+
+        // Backspace deletes the host-inserted space and restores "à"
+        let r = ime_key(keys::DELETE, false, false);
+        assert!(!r.is_null());
+        unsafe {
+            assert_eq!((*r).action, 1, "Should delete the space");
+            assert_eq!((*r).backspace, 1);
+            ime_free(r);
+        }
+
+        ime_clear_all();
+    }
+
+    #[test]
+    fn test_version_and_features_ffi() {
+        let version = unsafe { std::ffi::CStr::from_ptr(ime_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+
+        let has_keyboard_types = ime_features() & FEATURE_KEYBOARD_TYPES != 0;
+        assert_eq!(has_keyboard_types, cfg!(feature = "keyboard-types"));
+    }
+
+    #[test]
+    fn test_conversion_ffi() {
+        let word = CString::new("Việt").unwrap();
+        let mut out = [0u32; 16];
+        let to_string = |buf: &[u32], len: i64| -> String {
+            buf[..len as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c))
+                .collect()
+        };
+        unsafe {
+            let len = ime_to_telex_ascii(word.as_ptr(), out.as_mut_ptr(), 16);
+            assert_eq!(to_string(&out, len), "Vieejt");
+            let len = ime_to_vni_ascii(word.as_ptr(), out.as_mut_ptr(), 16);
+            assert_eq!(to_string(&out, len), "Vie65t");
+            let len = ime_to_ascii_folded(word.as_ptr(), out.as_mut_ptr(), 16);
+            assert_eq!(to_string(&out, len), "Viet");
+            // Truncated to max_len, null input writes nothing
+            assert_eq!(ime_to_telex_ascii(word.as_ptr(), out.as_mut_ptr(), 2), 2);
+            assert_eq!(
+                ime_to_telex_ascii(std::ptr::null(), out.as_mut_ptr(), 16),
+                0
+            );
+        }
+    }
+
     #[test]
     #[serial]
     fn test_restore_word_ffi() {
@@ -4,7 +4,7 @@
 //! Also includes test utilities under #[cfg(test)].
 
 use crate::data::{
-    chars::tone,
+    chars::{self, tone},
     keys,
     vowel::{Modifier, Vowel},
 };
@@ -49,6 +49,8 @@ pub fn key_to_char(key: u16, caps: bool) -> Option<char> {
         keys::N7 => return Some('7'),
         keys::N8 => return Some('8'),
         keys::N9 => return Some('9'),
+        // Only reaches the buffer as an intra-word apostrophe ("don't")
+        keys::QUOTE => return Some('\''),
         _ => return None,
     };
     Some(if caps { ch.to_ascii_uppercase() } else { ch })
@@ -139,6 +141,81 @@ pub fn has_gi_initial(buf: &Buffer) -> bool {
         && third.map(keys::is_vowel).unwrap_or(false)
 }
 
+/// Convert Vietnamese text to the Telex keystrokes that type it
+///
+/// Modifiers follow each char: "việt" → "vieejt", "đường" → "dduwowfng".
+/// Non-Vietnamese chars (digits, punctuation, spaces) pass through unchanged.
+pub fn to_telex_ascii(word: &str) -> String {
+    to_keystrokes(word, false)
+}
+
+/// Convert Vietnamese text to the VNI keystrokes that type it
+///
+/// Modifiers follow each char: "việt" → "vie65t", "đường" → "d9u7o72ng".
+/// Non-Vietnamese chars (digits, punctuation, spaces) pass through unchanged.
+pub fn to_vni_ascii(word: &str) -> String {
+    to_keystrokes(word, true)
+}
+
+/// Strip all diacritics from Vietnamese text, keeping case ("Việt Nam" → "Viet Nam")
+///
+/// Useful for URL slugs and search keys. Other chars pass through unchanged.
+pub fn to_ascii_folded(word: &str) -> String {
+    word.chars()
+        .map(|c| {
+            chars::parse_char(c)
+                .and_then(|p| key_to_char(p.key, p.caps))
+                .unwrap_or(c)
+        })
+        .collect()
+}
+
+/// Shared reverse conversion: base letter, then tone/stroke key, then mark key
+fn to_keystrokes(word: &str, vni: bool) -> String {
+    const TELEX_MARKS: [char; 5] = ['s', 'f', 'r', 'x', 'j'];
+    const VNI_MARKS: [char; 5] = ['1', '2', '3', '4', '5'];
+
+    let mut out = String::with_capacity(word.len() * 2);
+    for c in word.chars() {
+        let Some(p) = chars::parse_char(c) else {
+            out.push(c);
+            continue;
+        };
+        let Some(base) = key_to_char(p.key, p.caps) else {
+            out.push(c);
+            continue;
+        };
+        // Modifier letters follow the char's case so ALL CAPS stays ALL CAPS
+        let case = |ch: char| {
+            if p.caps {
+                ch.to_ascii_uppercase()
+            } else {
+                ch
+            }
+        };
+        out.push(base);
+
+        if p.stroke {
+            out.push(if vni { '9' } else { case('d') });
+        }
+        match p.tone {
+            tone::CIRCUMFLEX => out.push(if vni { '6' } else { base }),
+            tone::HORN if vni => out.push(if p.key == keys::A { '8' } else { '7' }),
+            tone::HORN => out.push(case('w')),
+            _ => {}
+        }
+        if p.mark > 0 {
+            let i = (p.mark - 1) as usize;
+            out.push(if vni {
+                VNI_MARKS[i]
+            } else {
+                case(TELEX_MARKS[i])
+            });
+        }
+    }
+    out
+}
+
 mod test_utils {
     //! Shared test utilities for inline tests
     //!
@@ -266,7 +343,7 @@ mod test_utils {
 
             if key == keys::DELETE {
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     // Restore from history - apply backspaces and replacement
                     for _ in 0..r.backspace {
                         screen.pop();
@@ -286,7 +363,7 @@ mod test_utils {
             // ESC key: restore to raw ASCII
             if key == keys::ESC {
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     for _ in 0..r.backspace {
                         screen.pop();
                     }
@@ -302,7 +379,7 @@ mod test_utils {
             if key == keys::SPACE {
                 // Space can trigger shortcuts - process result
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     // Shortcut triggered - apply backspaces and replacement
                     for _ in 0..r.backspace {
                         screen.pop();
@@ -320,7 +397,7 @@ mod test_utils {
             }
 
             let r = e.on_key_ext(key, is_caps, false, shift);
-            if r.action == Action::Send as u8 {
+            if r.action != Action::None as u8 {
                 for _ in 0..r.backspace {
                     screen.pop();
                 }
@@ -446,7 +523,7 @@ mod test_utils {
 
             if key == keys::DELETE {
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     // Restore from history - apply backspaces and replacement
                     for _ in 0..r.backspace {
                         screen.pop();
@@ -465,7 +542,7 @@ mod test_utils {
 
             if key == keys::ESC {
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     for _ in 0..r.backspace {
                         screen.pop();
                     }
@@ -480,7 +557,7 @@ mod test_utils {
 
             if key == keys::SPACE {
                 let r = e.on_key_ext(key, false, false, false);
-                if r.action == Action::Send as u8 {
+                if r.action != Action::None as u8 {
                     for _ in 0..r.backspace {
                         screen.pop();
                     }
@@ -496,7 +573,7 @@ mod test_utils {
             }
 
             let r = e.on_key_ext(key, is_caps, false, shift);
-            if r.action == Action::Send as u8 {
+            if r.action != Action::None as u8 {
                 for _ in 0..r.backspace {
                     screen.pop();
                 }
@@ -0,0 +1,88 @@
+//! Apostrophe in word: "don't" stays one word for composition and auto-restore
+
+mod common;
+use common::type_word;
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::Engine;
+
+fn engine_apostrophe() -> Engine {
+    let mut e = Engine::new();
+    e.set_apostrophe_in_word(true);
+    e.set_english_auto_restore(true);
+    e
+}
+
+#[test]
+fn apostrophe_breaks_word_by_default() {
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    // "isn" → "ín" is valid Vietnamese, the apostrophe commits it
+    assert_eq!(type_word(&mut e, "isn't "), "ín't ");
+}
+
+#[test]
+fn apostrophe_keeps_contraction_together() {
+    for (input, expected) in [
+        ("don't ", "don't "),
+        ("isn't ", "isn't "),
+        ("we'll ", "we'll "),
+        ("it's ", "it's "),
+        ("rock'n'roll ", "rock'n'roll "),
+    ] {
+        let mut e = engine_apostrophe();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn apostrophe_blocks_transforms_across_it() {
+    // Mark, tone and stroke keys after the apostrophe are plain letters
+    for (input, expected) in [
+        ("ddi's", "đi's"),
+        ("ddi's ", "đi's "),
+        ("vieetj's ", "việt's "),
+        ("nguwowif's ", "người's "),
+        ("ddi'ss", "đi'ss"),
+        ("ddi'aa", "đi'aa"),
+        ("ddi'dd", "đi'dd"),
+        ("ddi'w", "đi'w"),
+        ("Vieetj'll ", "Việt'll "),
+        // English stems are still restored as a whole
+        ("we'll ", "we'll "),
+        ("didn't ", "didn't "),
+    ] {
+        let mut e = engine_apostrophe();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // revert_invalid only judges the stem before the clitic
+    let mut e = Engine::new();
+    e.set_apostrophe_in_word(true);
+    e.set_revert_invalid(true);
+    assert_eq!(type_word(&mut e, "ddi's "), "đi's ");
+}
+
+#[test]
+fn apostrophe_leading_still_breaks() {
+    let mut e = engine_apostrophe();
+    assert_eq!(type_word(&mut e, "'vieetj "), "'việt ");
+}
+
+#[test]
+fn apostrophe_trailing_still_breaks() {
+    let mut e = engine_apostrophe();
+    assert_eq!(type_word(&mut e, "vieetj' "), "việt' ");
+    let mut e = engine_apostrophe();
+    // Word before the trailing apostrophe is auto-restored on its own
+    assert_eq!(type_word(&mut e, "user' "), "user' ");
+    let mut e = engine_apostrophe();
+    assert_eq!(type_word(&mut e, "user', "), "user', ");
+}
+
+#[test]
+fn apostrophe_backspace_stays_in_sync() {
+    let mut e = engine_apostrophe();
+    type_word(&mut e, "don'");
+    e.on_key(keys::DELETE, false, false);
+    assert_eq!(e.get_buffer_string(), "don");
+}
@@ -3,7 +3,7 @@
 //! Tests for automatic capitalization after sentence-ending punctuation.
 //! Feature: Tự viết hoa đầu câu
 //!
-//! Triggers: . ! ? Enter
+//! Triggers: . ! ? Enter (configurable, plus :)
 //! Default: OFF
 //!
 //! NOTE: These tests use Vietnamese patterns because the engine transforms
@@ -442,3 +442,98 @@ fn delete_past_buffer_keeps_pending() {
     let ch = char::from_u32(r.chars[0]).unwrap();
     assert_eq!(ch, 'C', "After deleting to period, should capitalize");
 }
+
+// ============================================================
+// RESULT FLAG
+// ============================================================
+
+#[test]
+fn auto_capitalized_flag_set_on_forced_uppercase() {
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+
+    // ". b" → "B": flag set on the forced letter
+    e.on_key(keys::DOT, false, false);
+    e.on_key(keys::SPACE, false, false);
+    let r = e.on_key(keys::B, false, false);
+    assert!(
+        r.auto_capitalized(),
+        "'b' after '. ' should be auto-capitalized"
+    );
+    assert_eq!(r.chars[0], 'B' as u32);
+
+    // Subsequent letters: flag cleared
+    let r = e.on_key(keys::A, false, false);
+    assert!(
+        !r.auto_capitalized(),
+        "Next letter should not carry the flag"
+    );
+}
+
+#[test]
+fn auto_capitalized_flag_not_set_when_user_typed_caps() {
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+
+    e.on_key(keys::DOT, false, false);
+    e.on_key(keys::SPACE, false, false);
+    let r = e.on_key(keys::B, true, false);
+    assert!(!r.auto_capitalized(), "User already typed uppercase");
+}
+
+#[test]
+fn cancel_pending_capitalize_keeps_buffer() {
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+
+    // ". " arms capitalize; cancelling leaves the next letter lowercase
+    assert_eq!(type_word(&mut e, "ok. "), "ok. ");
+    e.cancel_pending_capitalize();
+    assert_eq!(type_word(&mut e, "ban"), "ban");
+
+    // Deleting the auto-capitalized letter after cancel doesn't re-arm it
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+    type_word(&mut e, "ok. b");
+    e.cancel_pending_capitalize();
+    assert_eq!(type_word(&mut e, "<c"), "c");
+
+    // Composition in progress survives the cancel
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+    type_word(&mut e, "ok. vie");
+    e.cancel_pending_capitalize();
+    assert_eq!(e.get_buffer_string(), "Vie");
+}
+
+// ============================================================
+// CONFIGURABLE TRIGGERS
+// ============================================================
+
+#[test]
+fn capitalize_triggers_configurable() {
+    use gonhanh_core::engine::{
+        CAPITALIZE_COLON, CAPITALIZE_DEFAULT, CAPITALIZE_ENTER, CAPITALIZE_EXCLAIM,
+    };
+
+    // Exclude '!' from the defaults
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+    e.set_capitalize_triggers(CAPITALIZE_DEFAULT & !CAPITALIZE_EXCLAIM);
+    assert_eq!(type_word(&mut e, "oi! ban. ddi"), "oi! ban. Đi");
+
+    // Colon only
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+    e.set_capitalize_triggers(CAPITALIZE_COLON);
+    assert_eq!(type_word(&mut e, "ok. ban: ddi"), "ok. ban: Đi");
+
+    // Enter only
+    let mut e = Engine::new();
+    e.set_auto_capitalize(true);
+    e.set_capitalize_triggers(CAPITALIZE_ENTER);
+    assert_eq!(type_word(&mut e, "ok. ban"), "ok. ban");
+    e.on_key(keys::RETURN, false, false);
+    let r = e.on_key(keys::B, false, false);
+    assert!(r.auto_capitalized());
+}
@@ -0,0 +1,65 @@
+//! Backtick code span: transforms off inside `code`, shortcuts still work
+
+mod common;
+use common::type_word;
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::{shortcut::Shortcut, Engine};
+
+fn engine_code_span() -> Engine {
+    let mut e = Engine::new();
+    e.set_code_span(true);
+    e
+}
+
+#[test]
+fn code_span_disabled_by_default() {
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "`vieetj"), "`việt");
+}
+
+#[test]
+fn code_span_suppresses_transforms() {
+    let mut e = engine_code_span();
+    assert_eq!(type_word(&mut e, "`vieetj"), "`vieetj");
+}
+
+#[test]
+fn code_span_closing_backtick_resumes_transforms() {
+    let mut e = engine_code_span();
+    assert_eq!(
+        type_word(&mut e, "`ddos` vieetj"),
+        "`ddos` việt",
+        "Transforms resume after closing backtick"
+    );
+}
+
+#[test]
+fn code_span_reset_on_newline() {
+    let mut e = engine_code_span();
+    type_word(&mut e, "`abc");
+    e.on_key(keys::RETURN, false, false);
+    assert_eq!(type_word(&mut e, "vieetj"), "việt");
+}
+
+#[test]
+fn code_span_reset_on_esc() {
+    let mut e = engine_code_span();
+    type_word(&mut e, "`abc");
+    e.on_key(keys::ESC, false, false);
+    assert_eq!(type_word(&mut e, "vieetj"), "việt");
+}
+
+#[test]
+fn code_span_shortcuts_still_work() {
+    let mut e = engine_code_span();
+    e.shortcuts_mut().add(Shortcut::new("btw", "by the way"));
+    assert_eq!(type_word(&mut e, "`btw "), "`by the way ");
+}
+
+#[test]
+fn code_span_setting_off_exits_span() {
+    let mut e = engine_code_span();
+    type_word(&mut e, "`");
+    e.set_code_span(false);
+    assert_eq!(type_word(&mut e, "vieetj"), "việt");
+}
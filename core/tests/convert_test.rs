@@ -0,0 +1,74 @@
+//! Conversion Tests - Vietnamese text back to Telex/VNI keystrokes and ASCII
+
+mod common;
+use common::{telex, vni};
+use gonhanh_core::utils::{to_ascii_folded, to_telex_ascii, to_vni_ascii};
+
+// ============================================================
+// TEST DATA: (vietnamese, telex, vni, folded)
+// ============================================================
+
+const WORDS: &[(&str, &str, &str, &str)] = &[
+    ("việt", "vieejt", "vie65t", "viet"),
+    ("nam", "nam", "nam", "nam"),
+    ("đường", "dduwowfng", "d9u7o72ng", "duong"),
+    ("người", "nguwowfi", "ngu7o72i", "nguoi"),
+    ("khoẻ", "khoer", "khoe3", "khoe"),
+    ("quý", "quys", "quy1", "quy"),
+    ("ăn", "awn", "a8n", "an"),
+    ("trường", "truwowfng", "tru7o72ng", "truong"),
+    ("Đà", "DDaf", "D9a2", "Da"),
+    ("TIẾNG", "TIEESNG", "TIE61NG", "TIENG"),
+];
+
+// ============================================================
+// TEST FUNCTIONS
+// ============================================================
+
+#[test]
+fn telex_ascii() {
+    for (word, telex_keys, _, _) in WORDS {
+        assert_eq!(to_telex_ascii(word), *telex_keys, "[Telex] '{}'", word);
+    }
+}
+
+#[test]
+fn vni_ascii() {
+    for (word, _, vni_keys, _) in WORDS {
+        assert_eq!(to_vni_ascii(word), *vni_keys, "[VNI] '{}'", word);
+    }
+}
+
+#[test]
+fn ascii_folded() {
+    for (word, _, _, folded) in WORDS {
+        assert_eq!(to_ascii_folded(word), *folded, "[Fold] '{}'", word);
+    }
+    assert_eq!(
+        to_ascii_folded("Thành phố Hồ Chí Minh!"),
+        "Thanh pho Ho Chi Minh!"
+    );
+}
+
+#[test]
+fn non_vietnamese_passes_through() {
+    assert_eq!(to_telex_ascii("a-b 1.5"), "a-b 1.5");
+    assert_eq!(to_vni_ascii("(đồng)"), "(d9o62ng)");
+}
+
+#[test]
+fn keystrokes_round_trip() {
+    let telex_cases: Vec<(String, &str)> = WORDS
+        .iter()
+        .map(|(word, _, _, _)| (to_telex_ascii(word), *word))
+        .collect();
+    let telex_refs: Vec<(&str, &str)> = telex_cases.iter().map(|(k, w)| (k.as_str(), *w)).collect();
+    telex(&telex_refs);
+
+    let vni_cases: Vec<(String, &str)> = WORDS
+        .iter()
+        .map(|(word, _, _, _)| (to_vni_ascii(word), *word))
+        .collect();
+    let vni_refs: Vec<(&str, &str)> = vni_cases.iter().map(|(k, w)| (k.as_str(), *w)).collect();
+    vni(&vni_refs);
+}
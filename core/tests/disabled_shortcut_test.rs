@@ -150,3 +150,22 @@ fn test_shortcut_with_number_disabled_mode() {
         .collect();
     assert_eq!(output, "formula one ", "Should output 'formula one '");
 }
+
+#[test]
+fn test_instant_shortcut_disabled_mode() {
+    let mut e = Engine::new();
+    e.set_enabled(false);
+    e.shortcuts_mut().add(Shortcut::new("teh", "the").instant());
+
+    e.on_key(keys::T, false, false);
+    e.on_key(keys::E, false, false);
+    let r = e.on_key(keys::H, false, false);
+
+    assert_eq!(r.action, 1, "Instant shortcut should fire on last letter");
+    assert!(r.key_consumed());
+    assert_eq!(r.backspace, 2, "Only 'te' is on screen");
+    let output: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(output, "the");
+}
@@ -1,7 +1,7 @@
 //! Engine Tests - Syllable parsing, validation, and transformation
 
 mod common;
-use common::{telex, vni};
+use common::{telex, telex_traditional, vni};
 use gonhanh_core::engine::Engine;
 
 // ============================================================
@@ -309,6 +309,52 @@ fn mark_with_final_consonant() {
     ]);
 }
 
+#[test]
+fn mark_on_y_nucleus() {
+    // y as sole vowel or after glide u: mark lands on y
+    telex(&[
+        ("tyr", "tỷ"),
+        ("myx", "mỹ"),
+        ("kyx", "kỹ"),
+        ("quys", "quý"),
+        ("quyx", "quỹ"),
+        ("qusy", "quý"),
+        ("thuyr", "thuỷ"),
+    ]);
+    vni(&[
+        ("ty3", "tỷ"),
+        ("my4", "mỹ"),
+        ("ky4", "kỹ"),
+        ("quy1", "quý"),
+        ("qu1y", "quý"),
+    ]);
+    telex_traditional(&[("thuyr", "thủy"), ("tuys", "túy")]);
+}
+
+#[test]
+fn mark_with_gi_initial() {
+    // gi + vowel: 'i' belongs to the initial, mark goes on the nucleus
+    // gi alone: 'i' is the nucleus and takes the mark
+    telex(&[
+        ("gieengs", "giếng"),
+        ("gieesng", "giếng"),
+        ("gieenf", "giền"),
+        ("giacs", "giác"),
+        ("giasc", "giác"),
+        ("gif", "gì"),
+        ("gir", "gỉ"),
+        ("giuwax", "giữa"),
+        ("giuowngf", "giường"),
+    ]);
+    vni(&[
+        ("gie6ng1", "giếng"),
+        ("gie61ng", "giếng"),
+        ("gie6n2", "giền"),
+        ("gia1c", "giác"),
+        ("gi2", "gì"),
+    ]);
+}
+
 // ============================================================
 // STROKE TRANSFORMATION (d → đ)
 // ============================================================
@@ -369,6 +415,39 @@ fn stroke_in_word() {
     ]);
 }
 
+#[test]
+fn stroke_all_caps_acronym() {
+    // Leading "DD" strokes with no vowel after it and stays uppercase
+    telex(&[
+        ("DDHSP", "ĐHSP"),
+        ("DDHQG", "ĐHQG"),
+        ("DDHSP ", "ĐHSP "),
+        ("DDHQG.", "ĐHQG."),
+        ("Ddhsp", "Đhsp"),
+        ("DDDHSP", "DDHSP"), // third D reverts the stroke
+    ]);
+    vni(&[("D9HSP", "ĐHSP"), ("D9HQG ", "ĐHQG ")]);
+}
+
+#[test]
+fn stroke_with_mark_vni() {
+    // Stroke digit before, between or after the mark/tone digits: đ is kept
+    vni(&[
+        ("d9o1", "đó"),
+        ("do91", "đó"),
+        ("do19", "đó"),
+        ("d9uoc75", "được"),
+        ("duoc9", "đuoc"),
+        ("duoc759", "được"),
+        ("du7o7c59", "được"),
+        ("d9uong72", "đường"),
+        ("duo9ng72", "đường"),
+        ("du7o7ng29", "đường"),
+        ("d9u7o7ng2", "đường"),
+        ("de6n19", "đến"),
+    ]);
+}
+
 // ============================================================
 // REVERT BEHAVIOR TESTS
 // ============================================================
@@ -401,12 +480,43 @@ fn revert_stroke_double_key() {
     telex(&[("ddd", "dd")]);
 }
 
+#[test]
+fn revert_stroke_double_key_vni() {
+    // VNI mirrors Telex: d99 → d9, and once reverted '9' stays literal (no oscillation)
+    vni(&[("d99", "d9"), ("d999", "d99"), ("d9999", "d999")]);
+}
+
+#[test]
+fn revert_stroke_second_d_vni() {
+    // d9d9: second '9' reverts the existing đ instead of stroking the second d
+    // Equivalent of Telex "ddd" → "dd"; further '9's are literal (stroke_reverted)
+    vni(&[("d9d9", "dd"), ("d9d99", "dd9")]);
+}
+
 #[test]
 fn triple_same_key() {
-    // aaaa → aâ
+    // aaaa → aaa: once reverted, 'a' stays literal while it keeps repeating
     let mut e = Engine::new();
     let result = common::type_word(&mut e, "aaaa");
-    assert_eq!(result, "aâ");
+    assert_eq!(result, "aaa");
+}
+
+#[test]
+fn repeated_modifier_no_oscillation() {
+    // After a revert the same key is literal for the rest of the run
+    telex(&[
+        ("aaaaa", "aaaa"),
+        ("eeeee", "eeee"),
+        ("tooooo", "toooo"),
+        ("wwwww", "wwww"),
+        ("uwwww", "uwww"),
+        ("asssss", "assss"),
+        ("ddddd", "dddd"),
+    ]);
+    vni(&[("a6666", "a666"), ("a1111", "a111")]);
+
+    // Backspace ends the run: the key transforms again
+    telex(&[("aaa<a", "â")]);
 }
 
 // ============================================================
@@ -567,6 +677,22 @@ fn delayed_circumflex_vowel_trigger() {
     ]);
 }
 
+#[test]
+fn circumflex_retype_keeps_mark() {
+    // Retyping the vowel after a mark switches to the circumflex vowel
+    // and keeps the mark (tó + o → tố), in both open and closed syllables
+    telex(&[
+        ("toso", "tố"),     // tó + o → tố
+        ("tofo", "tồ"),     // tò + o → tồ
+        ("tojo", "tộ"),     // tọ + o → tộ
+        ("aja", "ậ"),       // ạ + a → ậ
+        ("eje", "ệ"),       // ẹ + e → ệ
+        ("casma", "cấm"),   // cám + a → cấm
+        ("hosngo", "hống"), // hóng + o → hống
+        ("mejte", "mệt"),   // mẹt + e → mệt
+    ]);
+}
+
 #[test]
 fn delayed_circumflex_extending_consonant() {
     // Consonants that can extend (n→ng/nh, c→ch) allow immediate circumflex
@@ -691,3 +817,1022 @@ fn delayed_circumflex_no_false_positives() {
         result2
     );
 }
+
+// ============================================================
+// RAW INPUT DEBUG ACCESSORS
+// ============================================================
+
+#[test]
+fn raw_input_string_shows_keystrokes() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "Vieetj");
+    assert_eq!(e.get_buffer_string(), "Việt");
+    assert_eq!(e.raw_input_string(), "Vieetj");
+}
+
+#[test]
+fn raw_input_string_vni() {
+    let mut e = Engine::new();
+    e.set_method(1);
+    common::type_word(&mut e, "d9u7o7ng2");
+    assert_eq!(e.raw_input_string(), "d9u7o7ng2");
+}
+
+#[test]
+fn set_raw_and_recompose_rebuilds_buffer() {
+    let mut e = Engine::new();
+    e.set_raw_and_recompose("dduowngf");
+    assert_eq!(e.get_buffer_string(), "đường");
+    assert_eq!(e.raw_input_string(), "dduowngf");
+
+    // Replaces any previous word
+    e.set_raw_and_recompose("Vieetj");
+    assert_eq!(e.get_buffer_string(), "Việt");
+}
+
+#[test]
+fn set_raw_and_recompose_round_trip() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "nguoiwf");
+    let raw = e.raw_input_string();
+    let buffer = e.get_buffer_string();
+
+    let mut replay = Engine::new();
+    replay.set_raw_and_recompose(&raw);
+    assert_eq!(replay.get_buffer_string(), buffer);
+}
+
+#[test]
+fn shortcut_prefix_inspect_and_clear() {
+    let mut e = Engine::new();
+    e.shortcuts_mut()
+        .add(gonhanh_core::engine::shortcut::Shortcut::immediate(
+            "->", "→",
+        ));
+    assert_eq!(e.shortcut_prefix_str(), "");
+    common::type_word(&mut e, "-");
+    assert_eq!(e.shortcut_prefix_str(), "-");
+
+    // Cleared prefix: '>' no longer completes "->"
+    e.clear_shortcut_prefix();
+    assert_eq!(e.shortcut_prefix_str(), "");
+    assert_eq!(common::type_word(&mut e, ">"), ">");
+}
+
+// ============================================================
+// HỎI / NGÃ SWAP
+// ============================================================
+
+#[test]
+fn swap_hoi_nga_toggles_mark() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "mir");
+    let r = e.swap_hoi_nga();
+    assert_eq!(r.action, 1);
+    assert_eq!(r.backspace, 1);
+    assert_eq!(e.get_buffer_string(), "mĩ");
+
+    // Swap back
+    e.swap_hoi_nga();
+    assert_eq!(e.get_buffer_string(), "mỉ");
+}
+
+#[test]
+fn swap_hoi_nga_mid_buffer() {
+    // Mark on ê followed by a final: rebuild starts at the marked vowel
+    let mut e = Engine::new();
+    common::type_word(&mut e, "nguyeenx");
+    assert_eq!(e.get_buffer_string(), "nguyễn");
+    let r = e.swap_hoi_nga();
+    assert_eq!(r.backspace, 2, "Rebuild from ễ: delete 'ễn'");
+    assert_eq!(e.get_buffer_string(), "nguyển");
+}
+
+#[test]
+fn swap_hoi_nga_noop_without_mark() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "mas");
+    let r = e.swap_hoi_nga();
+    assert_eq!(r.action, 0);
+    assert_eq!(e.get_buffer_string(), "má");
+
+    let mut empty = Engine::new();
+    assert_eq!(empty.swap_hoi_nga().action, 0);
+}
+
+#[test]
+fn swap_hoi_nga_updates_raw_input() {
+    let mut e = Engine::new();
+    e.set_method(1);
+    common::type_word(&mut e, "ma3");
+    e.swap_hoi_nga();
+    assert_eq!(e.get_buffer_string(), "mã");
+    assert_eq!(e.raw_input_string(), "ma4");
+}
+
+// ============================================================
+// FORCE RAW WORD (one-shot English)
+// ============================================================
+
+#[test]
+fn force_raw_before_word() {
+    let mut e = Engine::new();
+    let r = e.force_raw_current_word();
+    assert_eq!(r.action, 0);
+    assert_eq!(common::type_word(&mut e, "text"), "text");
+    assert_eq!(e.get_buffer_string(), "text");
+}
+
+#[test]
+fn force_raw_mid_word_restores() {
+    let mut e = Engine::new();
+    assert_eq!(common::type_word(&mut e, "tex"), "tẽ");
+    let r = e.force_raw_current_word();
+    assert_eq!(r.action, 2, "Action::Restore");
+    assert_eq!(r.backspace, 2);
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(out, "tex");
+    // Rest of the word stays raw too
+    assert_eq!(common::type_word(&mut e, "ts"), "ts");
+    assert_eq!(e.get_buffer_string(), "texts");
+}
+
+#[test]
+fn force_raw_resets_on_next_word() {
+    let mut e = Engine::new();
+    e.force_raw_current_word();
+    assert_eq!(common::type_word(&mut e, "mas vieetj"), "mas việt");
+}
+
+// ============================================================
+// SPLIT AT (commit a prefix, keep composing the rest)
+// ============================================================
+
+#[test]
+fn split_at_keeps_remainder_composing() {
+    let mut e = Engine::new();
+    e.restore_word("việtnam");
+    let r = e.split_at(4);
+    assert_eq!(r.action, 1);
+    assert_eq!(r.backspace, 3);
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(out, "nam");
+    assert_eq!(e.get_buffer_string(), "nam");
+    assert_eq!(e.raw_input_string(), "nam");
+
+    // The mark lands on the new word, not on "việt"
+    assert_eq!(common::type_word(&mut e, "f"), "àm");
+    assert_eq!(e.get_buffer_string(), "nàm");
+}
+
+#[test]
+fn split_at_keeps_remainder_diacritics() {
+    let mut e = Engine::new();
+    e.restore_word("ngườiviệt");
+    e.split_at(5);
+    assert_eq!(e.get_buffer_string(), "việt");
+}
+
+#[test]
+fn split_at_keeps_remainder_keystrokes() {
+    // VNI: tone and mark digits of "ệt" stay with the remainder
+    let mut e = Engine::new();
+    e.set_method(1);
+    e.set_esc_restore(true);
+    common::type_word(&mut e, "vie6t5");
+    e.split_at(2);
+    assert_eq!(e.get_buffer_string(), "ệt");
+    assert_eq!(e.raw_input_string(), "e6t5");
+    assert_eq!(common::type_word(&mut e, "\x1b"), "e6t5");
+
+    // Telex "vieetj" at 3: 'j' marks the committed "việ", so the rest is rebuilt
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieetj");
+    e.split_at(3);
+    assert_eq!(e.get_buffer_string(), "t");
+    assert_eq!(e.raw_input_string(), "t");
+}
+
+#[test]
+fn split_at_commits_prefix_to_history() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieetjnam");
+    e.split_at(4);
+    // Committing the rest stacks it on top of "việt": backspacing the space
+    // reopens the rest, not the committed prefix
+    common::type_word(&mut e, " ");
+    common::type_word(&mut e, "<");
+    assert_eq!(e.get_buffer_string(), "nam");
+}
+
+#[test]
+fn split_at_out_of_range() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieetj");
+    assert_eq!(e.split_at(0).action, 0);
+    assert_eq!(e.split_at(4).action, 0);
+    assert_eq!(e.split_at(10).action, 0);
+    assert_eq!(e.get_buffer_string(), "việt");
+}
+
+// ============================================================
+// IDLE COMMIT (host-driven tick)
+// ============================================================
+
+#[test]
+fn tick_disabled_by_default() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieet");
+    assert_eq!(e.tick(10_000).action, 0);
+    assert_eq!(e.get_buffer_string(), "viêt");
+}
+
+#[test]
+fn tick_commits_after_idle_threshold() {
+    let mut e = Engine::new();
+    e.set_idle_commit_ms(1000);
+    common::type_word(&mut e, "vieet");
+
+    e.tick(400);
+    e.tick(400);
+    assert_eq!(
+        e.get_buffer_string(),
+        "viêt",
+        "Below threshold: keep composing"
+    );
+
+    e.tick(400);
+    assert_eq!(
+        e.get_buffer_string(),
+        "",
+        "Threshold reached: word committed"
+    );
+
+    // Next mark key starts a fresh word
+    assert_eq!(common::type_word(&mut e, "j"), "j");
+}
+
+#[test]
+fn tick_resets_on_key() {
+    let mut e = Engine::new();
+    e.set_idle_commit_ms(1000);
+    common::type_word(&mut e, "vie");
+    e.tick(900);
+    common::type_word(&mut e, "e");
+    e.tick(900);
+    assert_eq!(e.get_buffer_string(), "viê", "Key resets idle timer");
+}
+
+#[test]
+fn tick_commit_auto_restores_english() {
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    e.set_idle_commit_ms(500);
+    common::type_word(&mut e, "user");
+    assert_eq!(e.get_buffer_string(), "uẻ");
+    let r = e.tick(500);
+    assert_eq!(r.action, 2, "Action::Restore");
+    let restored: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(restored, "user");
+    assert_eq!(r.backspace, 2, "Replace 'uẻ'");
+}
+
+// ============================================================
+// REPEAT FILTER (host timestamps)
+// ============================================================
+
+#[test]
+fn repeat_filter_drops_held_key() {
+    use gonhanh_core::data::keys;
+
+    let mut e = Engine::new();
+    e.set_repeat_filter_ms(50);
+    e.on_key_timed(keys::B, false, false, false, 0);
+    e.on_key_timed(keys::A, false, false, false, 100);
+    // Held 'a': repeats 30ms apart are dropped, no circumflex
+    let r = e.on_key_timed(keys::A, false, false, false, 130);
+    assert!(r.key_consumed());
+    assert_eq!(r.count, 0);
+    e.on_key_timed(keys::A, false, false, false, 160);
+    assert_eq!(e.get_buffer_string(), "ba");
+
+    // A deliberate double tap past the threshold still applies
+    e.on_key_timed(keys::A, false, false, false, 400);
+    assert_eq!(e.get_buffer_string(), "bâ");
+}
+
+#[test]
+fn repeat_filter_keeps_other_keys() {
+    use gonhanh_core::data::keys;
+
+    let mut e = Engine::new();
+    e.set_repeat_filter_ms(50);
+    common::type_word(&mut e, "abc");
+    // Held backspace is never filtered
+    e.on_key_timed(keys::DELETE, false, false, false, 0);
+    e.on_key_timed(keys::DELETE, false, false, false, 30);
+    assert_eq!(e.get_buffer_string(), "a");
+
+    // Off by default; on_key is unaffected either way
+    let mut e = Engine::new();
+    e.on_key_timed(keys::A, false, false, false, 0);
+    e.on_key_timed(keys::A, false, false, false, 10);
+    assert_eq!(e.get_buffer_string(), "â");
+}
+
+#[test]
+fn mark_revert_gap_ignores_double_tap() {
+    use gonhanh_core::data::keys;
+
+    let mut e = Engine::new();
+    e.set_mark_revert_gap_ms(150);
+    e.on_key_timed(keys::C, false, false, false, 0);
+    e.on_key_timed(keys::A, false, false, false, 100);
+    e.on_key_timed(keys::S, false, false, false, 200);
+    // Accidental double tap: consumed, mark kept
+    let r = e.on_key_timed(keys::S, false, false, false, 260);
+    assert!(r.key_consumed());
+    assert_eq!(e.get_buffer_string(), "cá");
+
+    // A deliberate re-press after the gap reverts
+    e.on_key_timed(keys::S, false, false, false, 600);
+    assert_eq!(e.get_buffer_string(), "cas");
+
+    // Off by default
+    let mut e = Engine::new();
+    for (i, key) in [keys::C, keys::A, keys::S, keys::S].into_iter().enumerate() {
+        e.on_key_timed(key, false, false, false, i as u32 * 10);
+    }
+    assert_eq!(e.get_buffer_string(), "cas");
+}
+
+// ============================================================
+// DIFF OUTPUT: minimize backspaces
+// ============================================================
+
+/// Type keys and return the result of the last one
+fn last_result(e: &mut Engine, input: &str) -> gonhanh_core::engine::Result {
+    let (init, last) = input.split_at(input.len() - 1);
+    common::type_word(e, init);
+    let key = gonhanh_core::utils::char_to_key(last.chars().next().unwrap());
+    e.on_key(key, false, false)
+}
+
+#[test]
+fn diff_output_esc_restore_fewer_backspaces() {
+    let mut plain = Engine::new();
+    plain.set_esc_restore(true);
+    let r = last_result(&mut plain, "vieejt\x1b");
+    assert_eq!(r.backspace, 4, "Default: delete whole 'việt'");
+
+    let mut diff = Engine::new();
+    diff.set_esc_restore(true);
+    diff.set_diff_output(true);
+    let r = last_result(&mut diff, "vieejt\x1b");
+    assert_eq!(r.backspace, 2, "Diff: keep 'vi', delete 'ệt'");
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(out, "eejt");
+}
+
+#[test]
+fn diff_output_auto_restore_on_space() {
+    let mut plain = Engine::new();
+    plain.set_english_auto_restore(true);
+    let r = last_result(&mut plain, "user ");
+    assert_eq!(r.backspace, 2);
+
+    let mut diff = Engine::new();
+    diff.set_english_auto_restore(true);
+    diff.set_diff_output(true);
+    let r = last_result(&mut diff, "user ");
+    assert_eq!(r.backspace, 1, "Keep 'u', replace 'ẻ'");
+}
+
+#[test]
+fn diff_output_same_screen_result() {
+    // Diff mode must never change what ends up on screen
+    for input in ["vieetj ", "dduowngf ", "user ", "ddd", "tiengs", "muaan"] {
+        let mut plain = Engine::new();
+        plain.set_english_auto_restore(true);
+        let mut diff = Engine::new();
+        diff.set_english_auto_restore(true);
+        diff.set_diff_output(true);
+        assert_eq!(
+            common::type_word(&mut plain, input),
+            common::type_word(&mut diff, input),
+            "input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn full_replace_resends_whole_word() {
+    let mut e = Engine::new();
+    e.set_full_replace(true);
+    let r = last_result(&mut e, "vieetj");
+    assert_eq!(r.backspace, 4, "Delete the whole 'viêt'");
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(out, "việt");
+}
+
+#[test]
+fn full_replace_same_screen_result() {
+    // Full replace must never change what ends up on screen
+    for input in [
+        "vieetj ",
+        "dduowngf ",
+        "user ",
+        "ddd",
+        "tiengs",
+        "muaan",
+        "vieejt\x1b",
+    ] {
+        let mut plain = Engine::new();
+        plain.set_english_auto_restore(true);
+        plain.set_esc_restore(true);
+        let mut full = Engine::new();
+        full.set_english_auto_restore(true);
+        full.set_esc_restore(true);
+        full.set_full_replace(true);
+        assert_eq!(
+            common::type_word(&mut plain, input),
+            common::type_word(&mut full, input),
+            "input: {}",
+            input
+        );
+    }
+}
+
+// ============================================================
+// TONE INDEX: which output char carries the new diacritic
+// ============================================================
+
+use gonhanh_core::engine::NO_TONE_INDEX;
+
+#[test]
+fn tone_index_points_at_marked_vowel() {
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "as");
+    assert_eq!(r.tone_index, 0, "'á' is the only output char");
+
+    // "ngườ" + 'i' + 'f': output "ười" from 'ư', mark sits on 'ờ'
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "nguoifw");
+    assert_eq!(r.tone_index, 1);
+}
+
+#[test]
+fn tone_index_for_tone_without_mark() {
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "huongw");
+    assert_eq!(r.tone_index, 0, "'ư' in output 'ương' got the horn");
+}
+
+#[test]
+fn tone_index_none_for_plain_keys() {
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "ba");
+    assert_eq!(r.tone_index, NO_TONE_INDEX);
+
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "vieejt ");
+    assert_eq!(r.tone_index, NO_TONE_INDEX);
+}
+
+// ============================================================
+// REJECTED FLAG: modifier keys that fail validation
+// ============================================================
+
+use gonhanh_core::engine::FLAG_REJECTED;
+
+#[test]
+fn rejected_flag_for_invalid_modifier() {
+    // "kư" is not Vietnamese: 'w' falls through as a plain letter
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "kw");
+    assert!(r.rejected());
+    assert_eq!(r.flags & FLAG_REJECTED, FLAG_REJECTED);
+}
+
+#[test]
+fn rejected_flag_not_set_for_plain_letters() {
+    // Ordinary letters, applied modifiers and modifiers with no target vowel
+    for input in ["k", "ba", "bas", "nhw", "ks", "text"] {
+        let mut e = Engine::new();
+        let r = last_result(&mut e, input);
+        assert!(!r.rejected(), "'{}' should not be rejected", input);
+    }
+}
+
+// ============================================================
+// RESTORE ACTION: restores are distinguishable from transforms
+// ============================================================
+
+#[test]
+fn restore_action_on_esc_and_auto_restore() {
+    use gonhanh_core::engine::Action;
+
+    // ESC restore
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    let r = last_result(&mut e, "tex\x1b");
+    assert_eq!(r.action, Action::Restore as u8);
+
+    // Auto-restore on space and on break keys
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    assert_eq!(last_result(&mut e, "user ").action, Action::Restore as u8);
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    assert_eq!(last_result(&mut e, "user,").action, Action::Restore as u8);
+
+    // Transforms stay Send
+    let mut e = Engine::new();
+    assert_eq!(last_result(&mut e, "as").action, Action::Send as u8);
+}
+
+// ============================================================
+// LAST EDIT: recover the most recent result's edit
+// ============================================================
+
+#[test]
+fn last_edit_tracks_latest_result() {
+    use gonhanh_core::data::keys;
+
+    let mut e = Engine::new();
+    assert_eq!(e.last_edit(), (0, vec![]));
+
+    e.on_key(keys::V, false, false);
+    e.on_key(keys::I, false, false);
+    e.on_key(keys::E, false, false);
+    e.on_key(keys::E, false, false);
+    assert_eq!(e.last_edit(), (1, vec!['ê']));
+
+    e.on_key(keys::T, false, false);
+    assert_eq!(e.last_edit(), (0, vec![]), "plain letter sends nothing");
+
+    e.on_key(keys::J, false, false);
+    assert_eq!(e.last_edit(), (2, vec!['ệ', 't']));
+}
+
+// ============================================================
+// APPLY TONE TO: modify an already-displayed char
+// ============================================================
+
+#[test]
+fn apply_tone_to_char() {
+    use gonhanh_core::data::keys;
+
+    // Telex
+    assert_eq!(Engine::apply_tone_to('a', keys::S, 0), Some('á'));
+    assert_eq!(Engine::apply_tone_to('ô', keys::J, 0), Some('ộ'));
+    assert_eq!(Engine::apply_tone_to('ó', keys::O, 0), Some('ố'));
+    assert_eq!(Engine::apply_tone_to('U', keys::W, 0), Some('Ư'));
+    assert_eq!(Engine::apply_tone_to('a', keys::W, 0), Some('ă'));
+    assert_eq!(Engine::apply_tone_to('d', keys::D, 0), Some('đ'));
+    assert_eq!(Engine::apply_tone_to('ấ', keys::Z, 0), Some('â'));
+
+    // Same diacritic again removes it
+    assert_eq!(Engine::apply_tone_to('á', keys::S, 0), Some('a'));
+    assert_eq!(Engine::apply_tone_to('ơ', keys::W, 0), Some('o'));
+
+    // VNI
+    assert_eq!(Engine::apply_tone_to('e', keys::N6, 1), Some('ê'));
+    assert_eq!(Engine::apply_tone_to('ư', keys::N2, 1), Some('ừ'));
+    assert_eq!(Engine::apply_tone_to('a', keys::N8, 1), Some('ă'));
+    assert_eq!(Engine::apply_tone_to('D', keys::N9, 1), Some('Đ'));
+
+    // Not applicable
+    assert_eq!(Engine::apply_tone_to('i', keys::O, 0), None);
+    assert_eq!(Engine::apply_tone_to('b', keys::S, 0), None);
+    assert_eq!(Engine::apply_tone_to('a', keys::T, 0), None);
+    assert_eq!(Engine::apply_tone_to('e', keys::Z, 0), None);
+    assert_eq!(Engine::apply_tone_to('!', keys::S, 0), None);
+}
+
+// ============================================================
+// STRICT FINALS: invalid final restores raw while typing
+// ============================================================
+
+#[test]
+fn strict_finals_restore_immediately() {
+    use gonhanh_core::utils::type_word;
+
+    for (input, expected) in [
+        ("vieetb", "vieetb"),   // việt + b: "tb" is not a final
+        ("ansl", "ansl"),       // án + l
+        ("osch", "osch"),       // óc + h: ch only after a, ê, i, y
+        ("awnh", "awnh"),       // ăn + h
+        ("vieetbs", "vieetbs"), // word stays raw after the restore
+        // Valid finals are untouched
+        ("asch", "ách"),
+        ("eesch", "ếch"),
+        ("isnh", "ính"),
+        ("ddoongf", "đồng"),
+        ("dduwowcj", "được"),
+    ] {
+        let mut e = Engine::new();
+        e.set_strict_finals(true);
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Default: checked at word boundary only
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "ansl"), "ánl");
+}
+
+// ============================================================
+// MODIFIER KEYS: key roles of the active method
+// ============================================================
+
+#[test]
+fn modifier_keys_per_method() {
+    use gonhanh_core::data::keys;
+
+    let mut e = Engine::new();
+    let m = e.modifier_keys();
+    assert_eq!(m.marks, [keys::S, keys::F, keys::R, keys::X, keys::J]);
+    assert_eq!(m.circumflex, [keys::A, keys::E, keys::O]);
+    assert_eq!((m.horn, m.breve), (keys::W, keys::W));
+    assert_eq!((m.stroke, m.remove), (keys::D, keys::Z));
+
+    e.set_method(1);
+    let m = e.modifier_keys();
+    assert_eq!(m.marks, [keys::N1, keys::N2, keys::N3, keys::N4, keys::N5]);
+    assert_eq!(m.circumflex, [keys::N6, keys::UNKNOWN, keys::UNKNOWN]);
+    assert_eq!((m.horn, m.breve), (keys::N7, keys::N8));
+    assert_eq!((m.stroke, m.remove), (keys::N9, keys::N0));
+
+    // Codes follow the active layout (Windows VK)
+    e.set_method(0);
+    e.set_layout(keys::LAYOUT_WINDOWS);
+    assert_eq!(e.modifier_keys().stroke, 0x44);
+}
+
+// ============================================================
+// BIDI ISOLATION: committed words wrapped in LRI/PDI
+// ============================================================
+
+#[test]
+fn bidi_isolation_wraps_committed_words() {
+    use gonhanh_core::engine::Action;
+    use gonhanh_core::utils::type_word;
+
+    // Words after right-to-left text cross a script boundary and get wrapped
+    let mut e = Engine::new();
+    e.set_bidi_isolation(true);
+    e.set_cursor_context("سلام ", "");
+    assert_eq!(
+        type_word(&mut e, "vieetj nam "),
+        "\u{2066}việt\u{2069} \u{2066}nam\u{2069} "
+    );
+
+    // Latin-only text has no boundary to isolate
+    let mut e = Engine::new();
+    e.set_bidi_isolation(true);
+    assert_eq!(type_word(&mut e, "vieetj nam "), "việt nam ");
+    e.set_cursor_context("hello ", "");
+    assert_eq!(type_word(&mut e, "vieetj "), "việt ");
+
+    // RTL chars typed through on_char switch it on too
+    let mut e = Engine::new();
+    e.set_bidi_isolation(true);
+    for ch in "שלום ".chars() {
+        e.on_char(ch, false, false, false);
+    }
+    assert_eq!(type_word(&mut e, "vieetj "), "\u{2066}việt\u{2069} ");
+
+    // Auto-restored words are wrapped as restored, keeping the Restore action
+    let mut e = Engine::new();
+    e.set_bidi_isolation(true);
+    e.set_english_auto_restore(true);
+    e.set_cursor_context("سلام ", "");
+    let r = last_result(&mut e, "user ");
+    assert_eq!(r.action, Action::Restore as u8);
+    let out: String = r.chars[..r.count as usize]
+        .iter()
+        .filter_map(|&c| char::from_u32(c))
+        .collect();
+    assert_eq!(out, "\u{2066}user\u{2069} ");
+
+    // Off by default
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "vieetj "), "việt ");
+}
+
+// ============================================================
+// EXTERNAL DELETE: host deleted chars without sending DELETE
+// ============================================================
+
+#[test]
+fn notify_external_delete_resyncs_buffer() {
+    use gonhanh_core::data::keys;
+    use gonhanh_core::utils::type_word;
+
+    let mut e = Engine::new();
+    type_word(&mut e, "toans");
+    assert_eq!(e.get_buffer_string(), "toán");
+
+    // Screen now shows "toá"; the next key composes against it
+    e.notify_external_delete(1);
+    assert_eq!(e.get_buffer_string(), "toá");
+    e.on_key(keys::T, false, false);
+    assert_eq!(e.get_buffer_string(), "toát");
+
+    // Clamped to the tracked word
+    e.notify_external_delete(10);
+    assert_eq!(e.get_buffer_string(), "");
+    assert_eq!(e.raw_input_len(), 0);
+}
+
+// ============================================================
+// VIETNAMESE CONFIDENCE: 0-100 score for the current word
+// ============================================================
+
+#[test]
+fn vietnamese_confidence_score() {
+    use gonhanh_core::utils::type_word;
+
+    for (input, expected) in [
+        ("", 0),
+        ("vieetj", 100),
+        ("ban", 60),
+        ("tesst", 30), // mark typed then reverted
+        ("search", 0),
+    ] {
+        let mut e = Engine::new();
+        type_word(&mut e, input);
+        assert_eq!(e.vietnamese_confidence(), expected, "input: {}", input);
+    }
+}
+
+// ============================================================
+// KEY SUPPRESSION: one place for the "eat this key" decision
+// ============================================================
+
+#[test]
+fn should_suppress_key_cases() {
+    let mut e = Engine::new();
+    assert!(!last_result(&mut e, "a").should_suppress_key());
+    assert!(last_result(&mut e, "s").should_suppress_key());
+
+    // Auto-restore on a break key: suppressed, the host types "," after "vieetk"
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    let r = last_result(&mut e, "vieetk,");
+    assert!(r.should_suppress_key());
+    assert!(!r.key_consumed());
+}
+
+// ============================================================
+// DEFERRED EMIT: one edit per word, on commit
+// ============================================================
+
+#[test]
+fn deferred_emit_single_edit_per_word() {
+    use gonhanh_core::engine::Action;
+    use gonhanh_core::utils::char_to_key;
+
+    let mut e = Engine::new();
+    e.set_deferred_emit(true);
+    let mut edits = Vec::new();
+    for c in "vieetj ".chars() {
+        let r = e.on_key(char_to_key(c), false, false);
+        if r.action != Action::None as u8 {
+            edits.push(r);
+        }
+    }
+    assert_eq!(edits.len(), 1, "Only the space emits");
+    let out: String = (0..edits[0].count as usize)
+        .filter_map(|i| char::from_u32(edits[0].chars[i]))
+        .collect();
+    assert_eq!(edits[0].backspace, 6, "Delete the raw 'vieetj'");
+    assert_eq!(out, "việt ");
+}
+
+#[test]
+fn deferred_emit_same_screen_result() {
+    for input in [
+        "vieetj ",
+        "dduowngf, ddi!",
+        "user ",
+        "tesst ",
+        "Vieetj Nam.",
+        "vieejt\x1b",
+    ] {
+        let mut plain = Engine::new();
+        plain.set_english_auto_restore(true);
+        plain.set_esc_restore(true);
+        let mut deferred = Engine::new();
+        deferred.set_english_auto_restore(true);
+        deferred.set_esc_restore(true);
+        deferred.set_deferred_emit(true);
+        assert_eq!(
+            common::type_word(&mut deferred, input),
+            common::type_word(&mut plain, input),
+            "input: {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn deferred_emit_backspace_edits_typed_keys() {
+    let mut e = Engine::new();
+    e.set_deferred_emit(true);
+    // Screen shows "vieetj", backspace removes 'j' then 't'
+    assert_eq!(common::type_word(&mut e, "vieetj<<j "), "việ ");
+}
+
+#[test]
+fn deferred_emit_idle_commit_replaces_typed_keys() {
+    use gonhanh_core::engine::Action;
+    use gonhanh_core::utils::char_to_key;
+
+    let mut e = Engine::new();
+    e.set_deferred_emit(true);
+    e.set_idle_commit_ms(100);
+    for c in "vieetj".chars() {
+        e.on_key(char_to_key(c), false, false);
+    }
+    let r = e.tick(500);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 6, "Delete the raw 'vieetj'");
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!(out, "việt");
+    // The word is committed: the next space is typed as-is
+    assert_eq!(e.on_key(char_to_key(' '), false, false).action, 0);
+
+    // Auto-restored words come back raw: nothing to replace
+    let mut e = Engine::new();
+    e.set_deferred_emit(true);
+    e.set_english_auto_restore(true);
+    e.set_idle_commit_ms(100);
+    for c in "user".chars() {
+        e.on_key(char_to_key(c), false, false);
+    }
+    assert_eq!(e.tick(500).action, 0);
+}
+
+// ============================================================
+// OUTPUT FILTER: host post-processing of Send results
+// ============================================================
+
+#[test]
+fn output_filter_rewrites_send_chars() {
+    fn no_dot_below(chars: &mut Vec<char>) {
+        *chars = chars
+            .iter()
+            .map(|&c| if c == 'ệ' { 'ê' } else { c })
+            .collect();
+    }
+
+    let mut e = Engine::new();
+    e.set_output_filter(Some(no_dot_below));
+    assert_eq!(common::type_word(&mut e, "vieetj "), "viêt ");
+
+    // Restores are raw keystrokes, not filtered
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    e.set_output_filter(Some(|chars: &mut Vec<char>| chars.clear()));
+    assert_eq!(common::type_word(&mut e, "vieejt\x1b"), "vieejt");
+
+    // Removed with None
+    e.set_output_filter(None);
+    assert_eq!(common::type_word(&mut e, "vieetj"), "việt");
+}
+
+// ============================================================
+// NFD OUTPUT: backspace counts decomposed codepoints
+// ============================================================
+
+#[test]
+fn nfd_output_backspace_counts_codepoints() {
+    // "việt" → "viết": the edit replaces "ệt" with "ết"
+    let mut nfc = Engine::new();
+    let r = last_result(&mut nfc, "vieetjs");
+    assert_eq!(r.backspace, 2);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, vec!['ế' as u32, 't' as u32]);
+
+    let mut nfd = Engine::new();
+    nfd.set_nfd_output(true);
+    let r = last_result(&mut nfd, "vieetjs");
+    // "ệ" on screen is e + dot below + circumflex, plus 't'
+    assert_eq!(r.backspace, 4);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, vec!['e' as u32, 0x0302, 0x0301, 't' as u32]);
+    assert_eq!(nfd.get_buffer_string(), "viết");
+}
+
+#[test]
+fn nfd_output_plain_letters_unchanged() {
+    let mut e = Engine::new();
+    e.set_nfd_output(true);
+    // "ee" → "ê": one plain 'e' on screen
+    let r = last_result(&mut e, "vie");
+    assert_eq!(r.action, 0);
+    let r = last_result(&mut e, "e");
+    assert_eq!(r.backspace, 1);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, vec!['e' as u32, 0x0302]);
+}
+
+#[test]
+fn nfd_output_word_editing_apis() {
+    // "mỉ" on screen is m + i + hook above: swapping to ngã replaces 2 codepoints
+    let mut e = Engine::new();
+    e.set_nfd_output(true);
+    common::type_word(&mut e, "mir");
+    let r = e.swap_hoi_nga();
+    assert_eq!(r.backspace, 2);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, vec!['i' as u32, 0x0303]);
+
+    // Forcing raw deletes every decomposed codepoint of "tẽt"
+    let mut e = Engine::new();
+    e.set_nfd_output(true);
+    common::type_word(&mut e, "text");
+    let r = e.force_raw_current_word();
+    assert_eq!(r.backspace, 4);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, "text".chars().map(|c| c as u32).collect::<Vec<_>>());
+
+    // The re-anchored remainder of a split is resent decomposed
+    let mut e = Engine::new();
+    e.set_nfd_output(true);
+    common::type_word(&mut e, "vieetj");
+    let r = e.split_at(2);
+    assert_eq!(r.backspace, 4);
+    let sent: Vec<u32> = r.chars[..r.count as usize].to_vec();
+    assert_eq!(sent, vec!['e' as u32, 0x0323, 0x0302, 't' as u32]);
+}
+
+// ============================================================
+// CURRENT TONE/MARK: diacritic of the word's tone-bearing vowel
+// ============================================================
+
+#[test]
+fn current_tone_mark_reads_diacritic_vowel() {
+    use gonhanh_core::data::chars::{mark, tone};
+
+    let cases: [(&str, (u8, u8)); 5] = [
+        ("", (tone::NONE, mark::NONE)),
+        ("ban", (tone::NONE, mark::NONE)),
+        ("vieet", (tone::CIRCUMFLEX, mark::NONE)),
+        ("nguwowif", (tone::HORN, mark::HUYEN)),
+        ("hoaf", (tone::NONE, mark::HUYEN)),
+    ];
+    for (input, expected) in cases {
+        let mut e = Engine::new();
+        common::type_word(&mut e, input);
+        assert_eq!(e.current_tone_mark(), expected, "{input}");
+    }
+}
+
+// ============================================================
+// COMPOSITION RESET FLAG: word dropped by ctrl, break or disable
+// ============================================================
+
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::FLAG_COMPOSITION_RESET;
+
+#[test]
+fn composition_reset_flag_on_dropped_word() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieetj");
+    let r = e.on_key(keys::C, false, true); // Ctrl+C
+    assert!(r.composition_reset());
+    assert_eq!(r.flags & FLAG_COMPOSITION_RESET, FLAG_COMPOSITION_RESET);
+
+    let mut e = Engine::new();
+    let r = last_result(&mut e, "vieetj,");
+    assert!(r.composition_reset(), "break key ends the word");
+    // One-shot: the next key starts clean
+    assert!(!e.on_key(keys::A, false, false).composition_reset());
+}
+
+#[test]
+fn composition_reset_flag_not_set_without_word() {
+    let mut e = Engine::new();
+    assert!(!e.on_key(keys::C, false, true).composition_reset());
+    assert!(!e.on_key(keys::COMMA, false, false).composition_reset());
+    // Space commits the word (backspace can reopen it), typing continues it
+    for input in ["vieetj ", "vieetj"] {
+        let mut e = Engine::new();
+        assert!(!last_result(&mut e, input).composition_reset(), "{input}");
+    }
+}
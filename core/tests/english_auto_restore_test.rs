@@ -29,6 +29,8 @@
 
 mod common;
 use common::telex_auto_restore;
+use gonhanh_core::engine::Engine;
+use gonhanh_core::utils::type_word;
 
 // =============================================================================
 // PATTERN 1: MODIFIER FOLLOWED BY CONSONANT
@@ -918,3 +920,29 @@ fn standalone_vowel_circumflex_with_tone() {
         ("aja ", "ậ "), // ậ - (contextual)
     ]);
 }
+
+// =============================================================================
+// MINIMUM RESTORE LENGTH
+// Words shorter than `set_min_restore_len` keep their Vietnamese form
+// =============================================================================
+
+#[test]
+fn min_restore_len_keeps_short_words() {
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    e.set_min_restore_len(4);
+    for (input, expected) in [
+        ("sax ", "sã "),    // 3 keys: below the minimum
+        ("sims ", "sims "), // 4 keys: restores
+        ("user ", "user "),
+        ("text ", "text "),
+    ] {
+        e.clear_all();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // 0 restores any length again
+    e.set_min_restore_len(0);
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "sax "), "sax ");
+}
@@ -0,0 +1,67 @@
+//! Hyphen in word: "việt-trường" and "well-known" stay one word across the hyphen
+
+mod common;
+use common::type_word;
+use gonhanh_core::engine::shortcut::Shortcut;
+use gonhanh_core::engine::Engine;
+
+fn engine_hyphen() -> Engine {
+    let mut e = Engine::new();
+    e.set_hyphen_in_word(true);
+    e.set_english_auto_restore(true);
+    e
+}
+
+#[test]
+fn hyphen_breaks_word_by_default() {
+    let mut e = Engine::new();
+    // "well" is committed as "ưell" by the hyphen
+    assert_eq!(type_word(&mut e, "well-known "), "ưell-known ");
+}
+
+#[test]
+fn hyphen_composes_each_part() {
+    for (input, expected) in [
+        ("vieetj-truwowngf ", "việt-trường "),
+        ("Vieetj-Nam ", "Việt-Nam "),
+        ("ddeso-vieetj ", "đéo-việt "),
+        ("2023-2024 ", "2023-2024 "),
+    ] {
+        let mut e = engine_hyphen();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn hyphen_restores_english_parts_at_word_end() {
+    let mut e = engine_hyphen();
+    // Still shown as typed while the word is open
+    assert_eq!(type_word(&mut e, "well-kn"), "ưell-kn");
+
+    for (input, expected) in [
+        ("well-known ", "well-known "),
+        ("vieetj-text ", "việt-text "),
+        ("text-vieetj ", "text-việt "),
+        ("well-known,", "well-known,"),
+    ] {
+        let mut e = engine_hyphen();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn hyphen_at_edges_still_breaks() {
+    let mut e = engine_hyphen();
+    assert_eq!(type_word(&mut e, "-vieetj "), "-việt ");
+
+    // Trailing hyphen: the word before it is finished
+    let mut e = engine_hyphen();
+    assert_eq!(type_word(&mut e, "well- "), "well- ");
+}
+
+#[test]
+fn hyphen_keeps_prefix_shortcuts() {
+    let mut e = engine_hyphen();
+    e.shortcuts_mut().add(Shortcut::immediate("->", "→"));
+    assert_eq!(type_word(&mut e, "abc->"), "abc→");
+}
@@ -71,6 +71,21 @@ fn ctrl_clears_buffer() {
     assert_passthrough(&mut e, keys::S);
 }
 
+#[test]
+fn ctrl_ignore_mode_keeps_buffer() {
+    let mut e = Engine::new();
+    e.set_ctrl_behavior(1);
+
+    type_word(&mut e, "vieet");
+    assert_action(&mut e, keys::LEFT, false, true, Action::None);
+    assert_eq!(e.get_buffer_string(), "viêt");
+
+    // Composition continues after the chord
+    let result = type_word(&mut e, "j");
+    assert_eq!(result, "ệt", "mark applies to the kept word");
+    assert_eq!(e.get_buffer_string(), "việt");
+}
+
 // ============================================================
 // METHOD SWITCHING: Telex <-> VNI
 // ============================================================
@@ -263,6 +278,32 @@ fn multiple_backspace_clears_all() {
     assert_passthrough(&mut e, keys::S);
 }
 
+#[test]
+fn smart_backspace_peels_diacritics() {
+    let cases = [
+        ("ees<", "ê"),
+        ("ees<<", "e"),
+        ("ees<<<", ""),
+        ("vieejt<<", "viê"),
+        ("vieejt<<<", "vie"),
+        ("ddaf<<", "đ"),
+        ("ddaf<<<", "d"),
+        ("uwj<<", "u"),
+        ("ees<f", "ề"), // Keep composing after a peel
+    ];
+    for (input, expected) in cases {
+        let mut e = Engine::new();
+        e.set_smart_backspace(true);
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn smart_backspace_off_deletes_whole_char() {
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "ees<"), "");
+}
+
 // ============================================================
 // CONSONANT-ONLY WORDS
 // ============================================================
@@ -343,9 +384,9 @@ fn double_mark_reverts() {
 #[test]
 fn triple_same_key_behavior() {
     let mut e = Engine::new();
-    // a → a, aa → â, aaa → aa, aaaa → aâ
+    // a → a, aa → â, aaa → aa, aaaa → aaa (no re-apply after revert)
     let result = type_word(&mut e, "aaaa");
-    assert_eq!(result, "aâ");
+    assert_eq!(result, "aaa");
 }
 
 // ============================================================
@@ -816,6 +857,21 @@ fn shortcut_tphcm_raw_result() {
     assert_eq!(r.count, 22, "count should be 22 chars");
 }
 
+#[test]
+fn shortcut_title_case_follows_trigger_case() {
+    let mut e = Engine::new();
+
+    // Add title-case shortcut: "hn" → "hà nội"
+    e.shortcuts_mut()
+        .add(Shortcut::new("hn", "hà nội").title_case());
+
+    assert_eq!(type_word(&mut e, "hn "), "hà nội ");
+    e.clear();
+    assert_eq!(type_word(&mut e, "Hn "), "Hà Nội ");
+    e.clear();
+    assert_eq!(type_word(&mut e, "HN "), "HÀ NỘI ");
+}
+
 #[test]
 fn shortcut_does_not_trigger_without_space() {
     let mut e = Engine::new();
@@ -1049,6 +1105,318 @@ fn shortcut_not_triggered_by_letter() {
     );
 }
 
+#[test]
+fn shortcut_instant_fires_mid_typing() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("teh", "the").instant());
+
+    // Fires on the last trigger letter, no space needed
+    let r = e.on_key(keys::T, false, false);
+    assert_eq!(r.action, Action::None as u8);
+    e.on_key(keys::E, false, false);
+    let r = e.on_key(keys::H, false, false);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 2);
+    e.clear();
+
+    assert_eq!(type_word(&mut e, "teh cat "), "the cat ");
+    assert_eq!(type_word(&mut e, "Teh "), "The ");
+}
+
+#[test]
+fn shortcut_instant_is_opt_in() {
+    let mut e = Engine::new();
+    // Word-boundary shortcut: "teh" inside "tehran" must not fire mid-word
+    e.shortcuts_mut().add(Shortcut::new("teh", "the"));
+    assert_eq!(type_word(&mut e, "tehran "), "tehran ");
+    assert_eq!(type_word(&mut e, "teh "), "the ");
+}
+
+#[test]
+fn shortcut_instant_vietnamese_trigger() {
+    let mut e = Engine::new();
+    // Trigger matches the composed word, so "đc" is typed as "ddc"
+    e.shortcuts_mut().add(Shortcut::new("đc", "được").instant());
+    assert_eq!(type_word(&mut e, "ddc"), "được");
+}
+
+#[test]
+fn shortcut_multi_word_trigger() {
+    let mut e = Engine::new();
+    e.shortcuts_mut()
+        .add(Shortcut::new("on the fly", "ngay lập tức"));
+
+    // Whole phrase replaced at once, backspacing across word boundaries
+    assert_eq!(type_word(&mut e, "on the fly "), "ngay lập tức ");
+    e.clear_all();
+    type_word(&mut e, "on the fly");
+    let r = e.on_key(keys::SPACE, false, false);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 10, "whole phrase is on screen");
+    e.clear_all();
+
+    // Case follows the first word
+    assert_eq!(type_word(&mut e, "On the fly "), "Ngay lập tức ");
+    e.clear_all();
+}
+
+#[test]
+fn shortcut_multi_word_needs_single_spaces() {
+    let mut e = Engine::new();
+    e.shortcuts_mut()
+        .add(Shortcut::new("on the fly", "ngay lập tức"));
+
+    // Extra space or punctuation between words breaks the phrase
+    assert_eq!(type_word(&mut e, "on  the fly "), "on  the fly ");
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "on, the fly "), "on, the fly ");
+    e.clear_all();
+
+    // Phrase can start mid-sentence
+    assert_eq!(
+        type_word(&mut e, "do it on the fly "),
+        "do it ngay lập tức "
+    );
+}
+
+#[test]
+fn shortcut_multi_word_longest_first() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("the fly", "con ruồi"));
+    e.shortcuts_mut()
+        .add(Shortcut::new("on the fly", "ngay lập tức"));
+
+    assert_eq!(type_word(&mut e, "on the fly "), "ngay lập tức ");
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "catch the fly "), "catch con ruồi ");
+}
+
+#[test]
+fn shortcut_terminators_default_space_only() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+    assert_eq!(type_word(&mut e, "vn,"), "vn,");
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "vn "), "Việt Nam ");
+}
+
+#[test]
+fn shortcut_terminators_custom() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+    e.shortcuts_mut().add(Shortcut::new("fn", "function"));
+    e.shortcuts_mut().set_terminators(&[' ', ',', '\n']);
+
+    // Comma expands; the host still types the comma itself
+    assert_eq!(type_word(&mut e, "vn,"), "Việt Nam,");
+    // '(' is not a terminator: "fn(" stays as typed
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "fn("), "fn(");
+
+    // Enter
+    e.clear_all();
+    type_word(&mut e, "vn");
+    let r = e.on_key(keys::RETURN, false, false);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 2);
+    assert!(
+        !r.key_consumed(),
+        "Enter passes through after the expansion"
+    );
+
+    // Dropping space from the set disables expansion on space
+    e.clear_all();
+    e.shortcuts_mut().set_terminators(&['\n']);
+    assert_eq!(type_word(&mut e, "vn "), "vn ");
+}
+
+#[test]
+fn shortcut_accent_insensitive() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("viet", "Việt Nam"));
+    assert_eq!(type_word(&mut e, "vieetj "), "việt ");
+
+    e.clear_all();
+    e.shortcuts_mut().set_accent_insensitive(true);
+    assert_eq!(type_word(&mut e, "vieetj "), "Việt Nam ");
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "viet "), "Việt Nam ");
+}
+
+#[test]
+fn shortcut_pending_flag() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().clear();
+    e.shortcuts_mut().add(Shortcut::new("hcm", "Hồ Chí Minh"));
+
+    assert!(e.on_key(keys::H, false, false).shortcut_pending());
+    assert!(e.on_key(keys::C, false, false).shortcut_pending());
+    // Complete trigger waits for space: no longer a proper prefix
+    assert!(!e.on_key(keys::M, false, false).shortcut_pending());
+
+    e.clear();
+    e.on_key(keys::H, false, false);
+    assert!(!e.on_key(keys::X, false, false).shortcut_pending());
+}
+
+#[test]
+fn shortcut_computed_replacement() {
+    use gonhanh_core::engine::shortcut::Shortcut;
+
+    fn number_words(typed: &str) -> String {
+        let words = ["không", "một", "hai", "ba"];
+        let n: usize = typed[1..].parse().unwrap_or(0);
+        format!("{} trăm", words.get(n / 100).unwrap_or(&"nhiều"))
+    }
+
+    let mut e = Engine::new();
+    e.shortcuts_mut()
+        .add(Shortcut::with_fn("s100", number_words));
+    e.shortcuts_mut()
+        .add(Shortcut::with_fn("s300", number_words));
+    assert_eq!(type_word(&mut e, "s100 "), "một trăm ");
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "s300 "), "ba trăm ");
+    e.clear_all();
+    // Not a registered trigger
+    assert_eq!(type_word(&mut e, "s200 "), "s200 ");
+}
+
+#[test]
+fn shortcut_typed_past_trigger() {
+    let add_vn = |e: &mut Engine| e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+
+    let mut e = Engine::new();
+    add_vn(&mut e);
+    assert_eq!(type_word(&mut e, "vn "), "Việt Nam ");
+    e.clear();
+    assert_eq!(type_word(&mut e, "vnx "), "vnx ");
+
+    // The key that goes past the complete trigger carries the cancelled flag
+    e.clear();
+    e.on_key(keys::V, false, false);
+    assert!(!e.on_key(keys::N, false, false).shortcut_cancelled());
+    assert!(e.on_key(keys::X, false, false).shortcut_cancelled());
+    assert!(!e.on_key(keys::Y, false, false).shortcut_cancelled());
+
+    // Default: backspacing back to the trigger expands again
+    e.clear();
+    assert_eq!(type_word(&mut e, "vnx< "), "Việt Nam ");
+
+    // Exact boundary: once declined, the word stays as typed
+    let mut e = Engine::new();
+    add_vn(&mut e);
+    e.set_shortcut_require_exact_boundary(true);
+    assert_eq!(type_word(&mut e, "vnx< "), "vn ");
+    assert_eq!(type_word(&mut e, "vn "), "Việt Nam ");
+}
+
+#[test]
+fn shortcut_overflow_flag_via_on_char() {
+    use gonhanh_core::engine::{OVERFLOW_ERROR, OVERFLOW_SPLIT};
+
+    let long = "x".repeat(100);
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("xx", &long));
+    e.set_overflow_mode(OVERFLOW_ERROR);
+    e.on_char('x', false, false, false);
+    e.on_char('x', false, false, false);
+    assert!(e.on_char(' ', false, false, false).overflowed());
+    // The flag belongs to that key only
+    assert!(!e.on_key(keys::A, false, false).overflowed());
+
+    // A split queue doesn't survive into the next key
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("xx", &long));
+    e.set_overflow_mode(OVERFLOW_SPLIT);
+    for ch in "xx ".chars() {
+        e.on_char(ch, false, false, false);
+    }
+    e.on_char('a', false, false, false);
+    assert!(e.next_result().is_none());
+}
+
+#[test]
+fn shortcut_overflow_modes() {
+    use gonhanh_core::engine::{OVERFLOW_ERROR, OVERFLOW_SPLIT};
+
+    let long: String = "Cộng hòa Xã hội Chủ nghĩa Việt Nam, ".repeat(3);
+    let expected = format!("{} ", long);
+    let type_long = |e: &mut Engine| {
+        e.shortcuts_mut().add(Shortcut::new("ch", &long));
+        for key in [keys::C, keys::H] {
+            e.on_key(key, false, false);
+        }
+        e.on_key(keys::SPACE, false, false)
+    };
+
+    // Default: truncated to one result
+    let mut e = Engine::new();
+    let r = type_long(&mut e);
+    assert_eq!(r.count as usize, 64);
+    assert!(e.next_result().is_none());
+
+    // Split: the rest is queued in order
+    let mut e = Engine::new();
+    e.set_overflow_mode(OVERFLOW_SPLIT);
+    let r = type_long(&mut e);
+    assert_eq!(r.backspace, 2);
+    let mut out: String = r.chars[..r.count as usize]
+        .iter()
+        .filter_map(|&c| char::from_u32(c))
+        .collect();
+    while let Some(r) = e.next_result() {
+        assert_eq!(r.backspace, 0);
+        out.extend(
+            r.chars[..r.count as usize]
+                .iter()
+                .filter_map(|&c| char::from_u32(c)),
+        );
+    }
+    assert_eq!(out, expected);
+
+    // Error: not expanded, the word is committed as typed
+    let mut e = Engine::new();
+    e.set_overflow_mode(OVERFLOW_ERROR);
+    let r = type_long(&mut e);
+    assert_eq!(r.action, Action::None as u8);
+    assert!(r.overflowed());
+}
+
+#[test]
+fn shortcut_long_words_skip_matching() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+    e.shortcuts_mut().add(Shortcut::new("hcm", "Hồ Chí Minh"));
+
+    // Words longer than any trigger are rejected before matching
+    // (timing: `cargo bench --bench engine_bench`)
+    let input = "nghieengs truowngf nguowif khuyeenr vn ".repeat(20);
+    let expected = "nghiếng trường người khuyển Việt Nam ".repeat(20);
+    assert_eq!(type_word(&mut e, &input), expected);
+}
+
+#[test]
+fn plain_letters_fast_path() {
+    let mut e = Engine::new();
+
+    // Letters with no modifier role skip the modifier scans, mixed with marked
+    // words so fast and slow paths interleave (timing: `engine_bench`)
+    let input = "chung tinh lung thuc tinhs ngwng bich chuyeenj ".repeat(20);
+    let expected = "chung tinh lung thuc tính ngưng bich chuyện ".repeat(20);
+    assert_eq!(type_word(&mut e, &input), expected);
+}
+
+#[test]
+fn long_run_on_words_validation() {
+    let mut e = Engine::new();
+
+    // 30+ letter words can't be one syllable: they stay as typed
+    // (timing lives in `benches/engine_bench.rs`)
+    let input = "supercalifragilisticexpialidocious antidisestablishmentarianism ".repeat(20);
+    assert_eq!(type_word(&mut e, &input), input);
+}
+
 // Issue: "search" should not become "seảch" in Telex
 // "ea" is not a valid Vietnamese vowel combination
 #[test]
@@ -1547,6 +1915,133 @@ fn skip_w_shortcut_multiple_words() {
     assert_eq!(result3, "ư");
 }
 
+/// Custom initial set for standalone 'w': only listed consonants give Cư
+#[test]
+fn w_valid_initials_custom_set() {
+    let mut e = Engine::new();
+    e.set_w_valid_initials(&[keys::N, keys::T, keys::F]);
+    for (input, expected) in [
+        ("nw", "nư"),
+        ("thw", "thư"), // matched on the first letter of the initial
+        ("fw", "fư"),   // not valid Vietnamese, but explicitly allowed
+        ("bw", "bw"),
+        ("khw", "khw"),
+        ("nwf", "nừ"),
+    ] {
+        e.clear();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Back to validation-driven default
+    e.reset_w_valid_initials();
+    for (input, expected) in [("bw", "bư"), ("fw", "fw")] {
+        e.clear();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn double_o_literal_initials() {
+    let mut e = Engine::new();
+    e.set_double_o_literal_initials(&[keys::C, keys::Z]);
+    for (input, expected) in [
+        ("coo", "coo"),
+        ("cool", "cool"),
+        ("zoo", "zoo"),
+        ("choo", "choo"), // matched on the first letter of the initial
+        ("too", "tô"),    // not listed
+        ("cos", "có"),    // single o is unaffected
+    ] {
+        e.clear();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Default: circumflex after every initial
+    e.set_double_o_literal_initials(&[]);
+    e.clear();
+    assert_eq!(type_word(&mut e, "coo"), "cô");
+}
+
+#[test]
+fn iy_preference_on_commit() {
+    let mut e = Engine::new();
+    e.set_iy_preference(1);
+    for (input, expected) in [
+        ("lis ", "lý "),
+        ("Kix,", "Kỹ,"),
+        ("hi ", "hy "),
+        ("ly ", "ly "),    // already preferred
+        ("bi ", "bi "),    // no "by" variant
+        ("chis ", "chí "), // digraph initial
+        ("quys ", "quý "),
+        ("lisch ", "lích "), // final consonant
+    ] {
+        e.clear_all();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    e.set_iy_preference(2);
+    for (input, expected) in [("lys ", "lí "), ("MYX ", "MĨ "), ("kyf", "kỳ")] {
+        e.clear_all();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Default: as typed
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "lis lys "), "lí lý ");
+}
+
+#[test]
+fn simple_mode_minimal_telex() {
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    e.set_defer_breve(true);
+    e.set_simple_mode(true);
+    for (input, expected) in [
+        ("did", "did"),    // no short-pattern stroke
+        ("ddi", "đi"),     // adjacent stroke still works
+        ("muasn", "múan"), // mark stays where it was placed
+        ("osa", "óa"),
+        ("aw", "ă"),      // no breve deferral
+        ("user ", "úẻ "), // no English auto-restore
+        ("vieetj", "việt"),
+        ("dduowngf", "đường"),
+    ] {
+        e.clear_all();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Off again: reposition and short-pattern stroke are back
+    e.set_simple_mode(false);
+    for (input, expected) in [("did", "đi"), ("muasn", "muán")] {
+        e.clear_all();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn no_echo_passes_keys_literally() {
+    let mut e = Engine::new();
+    e.shortcuts_mut().add(Shortcut::new("vn", "Việt Nam"));
+    e.set_no_echo(true);
+    for input in ["vieetj", "dduowngf", "vn ", "aas"] {
+        e.clear_all();
+        for ch in input.chars() {
+            let key = gonhanh_core::utils::char_to_key(ch);
+            let r = e.on_key(key, false, false);
+            assert_eq!(r.action, 0, "input: {} at '{}'", input, ch);
+        }
+    }
+    // The literal word is still tracked
+    e.clear_all();
+    type_word(&mut e, "vieetj");
+    assert_eq!(e.get_buffer_string(), "vieetj");
+
+    e.set_no_echo(false);
+    e.clear_all();
+    assert_eq!(type_word(&mut e, "vieetj"), "việt");
+}
+
 // ============================================================
 // BACKSPACE-AFTER-SPACE: Issue #32
 // ============================================================
@@ -1773,6 +2268,47 @@ fn backspace_after_space_esc_clears() {
     assert_eq!(r.action, Action::None as u8, "ESC should clear history");
 }
 
+/// ESC behavior modes: 0=restore, 1=commit and keep, 2=clear only
+#[test]
+fn esc_behavior_modes() {
+    // Restore (default): raw keystrokes come back when esc_restore is on
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    assert_eq!(type_word(&mut e, "vieejt\x1b"), "vieejt");
+
+    // Commit: Vietnamese kept, next key starts a new word
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    e.set_esc_behavior(1);
+    let mut screen = type_word(&mut e, "vieejt\x1b");
+    screen.push_str(&type_word(&mut e, "s"));
+    assert_eq!(screen, "việts");
+
+    // Clear only: no restore even with esc_restore on
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    e.set_esc_behavior(2);
+    assert_eq!(type_word(&mut e, "vieejt\x1b"), "việt");
+}
+
+/// ESC commit mode keeps history, clear mode drops it
+#[test]
+fn esc_behavior_history() {
+    let mut e = Engine::new();
+    e.set_esc_behavior(1);
+    type_word(&mut e, "du ");
+    e.on_key(keys::ESC, false, false);
+    let r = e.on_key(keys::DELETE, false, false);
+    assert_eq!(r.action, Action::Send as u8, "Commit should keep history");
+
+    let mut e = Engine::new();
+    e.set_esc_behavior(2);
+    type_word(&mut e, "du ");
+    e.on_key(keys::ESC, false, false);
+    let r = e.on_key(keys::DELETE, false, false);
+    assert_eq!(r.action, Action::None as u8, "Clear should drop history");
+}
+
 /// Dot punctuation clears history
 #[test]
 fn backspace_after_space_dot_clears() {
@@ -2235,6 +2771,24 @@ fn backspace_after_space_uppercase_then_lowercase() {
     );
 }
 
+/// Disabled: backspace just deletes the space, the word stays committed
+#[test]
+fn backspace_after_space_disabled() {
+    let mut e = Engine::new();
+    e.set_backspace_restore(false);
+    // "j" starts a new word instead of marking "du"
+    assert_eq!(type_word(&mut e, "du <j"), "duj");
+    e.clear_all();
+    type_word(&mut e, "du ");
+    let r = e.on_key(keys::DELETE, false, false);
+    assert_eq!(
+        r.action,
+        Action::None as u8,
+        "Host deletes the space itself"
+    );
+    assert_eq!(e.get_buffer_string(), "");
+}
+
 // ============================================================
 // RESTORE_WORD: Buffer restoration from Vietnamese string
 // ============================================================
@@ -2412,6 +2966,145 @@ fn restore_word_change_mark_then_extend() {
     assert_eq!(result, "cháo", "Should change mark and extend word");
 }
 
+/// Oversized input (FFI-reachable) is capped at buffer capacity
+#[test]
+fn restore_word_oversized_input() {
+    use gonhanh_core::engine::buffer::MAX;
+
+    let mut e = Engine::new();
+    let long: String = "việt".repeat(25); // 100 chars
+    e.restore_word(&long);
+    assert_eq!(e.get_buffer_string().chars().count(), MAX);
+    assert_eq!(e.raw_input_string().chars().count(), MAX);
+
+    // Still usable after the capped restore
+    e.clear();
+    assert_eq!(type_word(&mut e, "vieetj"), "việt");
+}
+
+/// restore_with_raw: ESC gives back the keys that typed the word, not a guess
+#[test]
+fn restore_with_raw_esc_restores_given_keys() {
+    let mut e = Engine::new();
+    e.set_esc_restore(true);
+    e.restore_word("Việt");
+    assert_eq!(
+        e.raw_input_string(),
+        "Viet",
+        "restore_word only has the letters"
+    );
+
+    e.restore_with_raw(
+        "Việt",
+        &[keys::V, keys::I, keys::E, keys::E, keys::T, keys::J],
+    );
+    assert_eq!(e.raw_input_string(), "Vieetj");
+    let r = e.on_key(keys::ESC, false, false);
+    let out: String = (0..r.count as usize)
+        .filter_map(|i| char::from_u32(r.chars[i]))
+        .collect();
+    assert_eq!((r.backspace, out.as_str()), (4, "Vieetj"));
+
+    // All-caps word: unmatched modifier keys are uppercase too
+    e.restore_with_raw("ĐI", &[keys::D, keys::D, keys::I]);
+    assert_eq!(e.raw_input_string(), "DDI");
+
+    // Editing still works after the restore
+    let mut e = Engine::new();
+    e.restore_with_raw(
+        "việt",
+        &[keys::V, keys::I, keys::E, keys::E, keys::T, keys::J],
+    );
+    assert_eq!(e.get_buffer_string(), "việt");
+    e.on_key(keys::S, false, false);
+    assert_eq!(e.get_buffer_string(), "viết");
+}
+
+// ============================================================
+// CURSOR CONTEXT: host-reported text around the cursor
+// ============================================================
+
+/// Helper: simulate typing at a cursor reported by the host.
+/// Returns the text left of the cursor after typing `input` ('<' = backspace).
+fn type_at_cursor(e: &mut Engine, before: &str, after: &str, input: &str) -> String {
+    use gonhanh_core::utils::char_to_key;
+
+    let mut screen: String = before.into();
+    e.set_cursor_context(before, after);
+
+    for c in input.chars() {
+        let key = if c == '<' {
+            keys::DELETE
+        } else {
+            char_to_key(c)
+        };
+        let r = e.on_key(key, c.is_uppercase(), false);
+        if r.action == Action::Send as u8 {
+            for _ in 0..r.backspace {
+                screen.pop();
+            }
+            for i in 0..r.count as usize {
+                if let Some(ch) = char::from_u32(r.chars[i]) {
+                    screen.push(ch);
+                }
+            }
+        } else if key == keys::DELETE {
+            screen.pop();
+        } else {
+            screen.push(c);
+        }
+    }
+    screen
+}
+
+#[test]
+fn cursor_context_restores_word_left_of_cursor() {
+    let mut e = Engine::new();
+    let result = type_at_cursor(&mut e, "xin chào việt", " nam", "s");
+    assert_eq!(result, "xin chào viết");
+}
+
+#[test]
+fn cursor_context_inside_word_does_not_compose() {
+    let mut e = Engine::new();
+    // Cursor between "vi" and "ệt": 's' must not rewrite the word
+    let result = type_at_cursor(&mut e, "vi", "ệt", "s");
+    assert_eq!(result, "vis");
+}
+
+#[test]
+fn cursor_context_backspace_into_previous_word() {
+    let mut e = Engine::new();
+    // Cursor after "chà " - deleting the space restores "chà" for editing
+    let result = type_at_cursor(&mut e, "chà ", "", "<s");
+    assert_eq!(result, "chá");
+}
+
+#[test]
+fn cursor_context_after_punctuation() {
+    let mut e = Engine::new();
+    // Only the word after the last non-letter is restored
+    let result = type_at_cursor(&mut e, "(hà", ")", "s");
+    assert_eq!(result, "(há");
+    let result = type_at_cursor(&mut e, "(ha", ")", "f");
+    assert_eq!(result, "(hà");
+}
+
+#[test]
+fn notify_space_keeps_backspace_after_space() {
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "chaf"), "chà");
+    // Host inserts the space itself (e.g., autocomplete): nothing is sent
+    e.notify_space();
+    // Backspace deletes that space and restores "chà" for editing
+    let r = e.on_key(keys::DELETE, false, false);
+    assert_eq!((r.action, r.backspace, r.count), (Action::Send as u8, 1, 0));
+    let r = e.on_key(keys::S, false, false);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 1);
+    assert_eq!(char::from_u32(r.chars[0]), Some('á'));
+}
+
 // ============================================================
 // OIW VS OWI BUG FIX TEST
 // ============================================================
@@ -3310,3 +4003,85 @@ fn test_ua_open_vs_closed_syllable() {
     let r2 = type_word(&mut e, "muasn ");
     assert_eq!(r2, "muán ", "ua closed syllable: tone on a");
 }
+
+// ============================================================
+// SYMBOL SHORTCUTS: "(dong)" → "₫", "(do)" → "°"
+// ============================================================
+
+#[test]
+fn symbol_shortcuts_disabled_by_default() {
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "(dong)"), "(dong)");
+}
+
+#[test]
+fn symbol_shortcut_dong_sign() {
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    assert_eq!(type_word(&mut e, "(dong)"), "₫");
+}
+
+#[test]
+fn symbol_shortcut_after_number() {
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    assert_eq!(type_word(&mut e, "500(dong)"), "500₫");
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    assert_eq!(type_word(&mut e, "30(do)"), "30°");
+}
+
+#[test]
+fn symbol_shortcut_after_word() {
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    assert_eq!(type_word(&mut e, "nhieetj (do)"), "nhiệt °");
+}
+
+#[test]
+fn symbol_shortcuts_toggle_off() {
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    e.set_symbol_shortcuts(false);
+    assert_eq!(type_word(&mut e, "(do)"), "(do)");
+}
+
+#[test]
+fn symbol_shortcut_when_disabled() {
+    let mut e = Engine::new();
+    e.set_symbol_shortcuts(true);
+    e.set_enabled(false);
+    assert_eq!(type_word(&mut e, "(dong)"), "₫");
+}
+
+// ============================================================
+// REVERT INVALID: structurally invalid Vietnamese → raw keystrokes
+// ============================================================
+
+#[test]
+fn revert_invalid_disabled_by_default() {
+    let mut e = Engine::new();
+    assert_eq!(type_word(&mut e, "awesome "), "ắeome ");
+}
+
+#[test]
+fn revert_invalid_restores_raw_on_space() {
+    let mut e = Engine::new();
+    e.set_revert_invalid(true);
+    assert_eq!(type_word(&mut e, "awesome "), "awesome ");
+}
+
+#[test]
+fn revert_invalid_restores_raw_on_break() {
+    let mut e = Engine::new();
+    e.set_revert_invalid(true);
+    assert_eq!(type_word(&mut e, "awesome,"), "awesome,");
+}
+
+#[test]
+fn revert_invalid_keeps_valid_vietnamese() {
+    let mut e = Engine::new();
+    e.set_revert_invalid(true);
+    // Valid structure stays, even if it looks English ("text" → "tẽt")
+    assert_eq!(type_word(&mut e, "nuwowcs vieetj text "), "nước việt tẽt ");
+}
@@ -0,0 +1,62 @@
+//! Tests for `keyboard-types` interop (cargo feature `keyboard-types`)
+#![cfg(feature = "keyboard-types")]
+
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::Engine;
+use keyboard_types::Code;
+
+#[test]
+fn maps_letters_numbers_and_specials() {
+    assert_eq!(keys::from_key_code(Code::KeyA), Some(keys::A));
+    assert_eq!(keys::from_key_code(Code::KeyW), Some(keys::W));
+    assert_eq!(keys::from_key_code(Code::Digit9), Some(keys::N9));
+    assert_eq!(keys::from_key_code(Code::Space), Some(keys::SPACE));
+    assert_eq!(keys::from_key_code(Code::Backspace), Some(keys::DELETE));
+    assert_eq!(keys::from_key_code(Code::Escape), Some(keys::ESC));
+    assert_eq!(keys::from_key_code(Code::NumpadEnter), Some(keys::ENTER));
+}
+
+#[test]
+fn unmapped_codes_return_none() {
+    assert_eq!(keys::from_key_code(Code::F1), None);
+    assert_eq!(keys::from_key_code(Code::ShiftLeft), None);
+}
+
+#[test]
+fn portable_codes_drive_engine() {
+    let mut e = Engine::new();
+    for code in [
+        Code::KeyV,
+        Code::KeyI,
+        Code::KeyE,
+        Code::KeyE,
+        Code::KeyT,
+        Code::KeyJ,
+    ] {
+        let key = keys::from_key_code(code).unwrap();
+        e.on_key(key, false, false);
+    }
+    assert_eq!(e.get_buffer_string(), "việt");
+}
+
+#[test]
+fn portable_codes_ignore_the_active_layout() {
+    let word = [
+        Code::KeyV,
+        Code::KeyI,
+        Code::KeyE,
+        Code::KeyE,
+        Code::KeyT,
+        Code::KeyJ,
+    ];
+    for layout in [keys::LAYOUT_MACOS, keys::LAYOUT_WINDOWS, keys::LAYOUT_LINUX] {
+        let mut e = Engine::new();
+        e.set_layout(layout);
+        for code in word {
+            e.on_key_code(code, false, false, false);
+        }
+        assert_eq!(e.get_buffer_string(), "việt", "layout {}", layout);
+        e.on_key_code(Code::Backspace, false, false, false);
+        assert_eq!(e.get_buffer_string(), "việ", "layout {}", layout);
+    }
+}
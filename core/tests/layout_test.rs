@@ -0,0 +1,209 @@
+//! Platform keycode layout tests
+//!
+//! The same logical keys typed through any layout must produce identical output.
+
+use gonhanh_core::data::keys::{self, KeyCode};
+use gonhanh_core::engine::Engine;
+use gonhanh_core::utils::char_to_key;
+
+const LAYOUTS: [u8; 3] = [keys::LAYOUT_MACOS, keys::LAYOUT_WINDOWS, keys::LAYOUT_LINUX];
+
+/// Type `input` through `layout` and return the buffer string
+fn type_with_layout(method: u8, layout: u8, input: &str) -> String {
+    let mut e = Engine::new();
+    e.set_method(method);
+    e.set_layout(layout);
+    for c in input.chars() {
+        let logical = KeyCode::from_platform(keys::LAYOUT_MACOS, char_to_key(c)).unwrap();
+        let code = logical.to_platform(layout).unwrap();
+        e.on_key(code, c.is_uppercase(), false);
+    }
+    e.get_buffer_string()
+}
+
+#[test]
+fn same_keys_same_output_across_layouts() {
+    let cases = [
+        (0, "vieetj", "việt"),
+        (0, "dduowngf", "đường"),
+        (0, "Nam", "Nam"),
+        (1, "vie6t5", "việt"),
+        (1, "d9u7o7ng2", "đường"),
+    ];
+    for (method, input, expected) in cases {
+        for layout in LAYOUTS {
+            assert_eq!(
+                type_with_layout(method, layout, input),
+                expected,
+                "method={} layout={} input={}",
+                method,
+                layout,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn delete_key_translated_per_layout() {
+    for layout in LAYOUTS {
+        let mut e = Engine::new();
+        e.set_layout(layout);
+        for key in [KeyCode::A, KeyCode::S, KeyCode::Delete] {
+            e.on_key(key.to_platform(layout).unwrap(), false, false);
+        }
+        assert_eq!(e.get_buffer_string(), "", "layout={}", layout);
+    }
+}
+
+#[test]
+fn platform_codes_round_trip() {
+    for layout in LAYOUTS {
+        for key in [KeyCode::A, KeyCode::W, KeyCode::N9, KeyCode::Space] {
+            let code = key.to_platform(layout).unwrap();
+            assert_eq!(KeyCode::from_platform(layout, code), Some(key));
+        }
+    }
+}
+
+#[test]
+fn unmapped_codes_become_unknown() {
+    // Windows VK_F1 (0x70) must not alias macOS keycode 112
+    assert_eq!(keys::translate(keys::LAYOUT_WINDOWS, 0x70), keys::UNKNOWN);
+    // macOS codes pass through unchanged
+    assert_eq!(keys::translate(keys::LAYOUT_MACOS, keys::A), keys::A);
+    // Windows 'A' (0x41) maps to internal A
+    assert_eq!(keys::translate(keys::LAYOUT_WINDOWS, 0x41), keys::A);
+    // Linux evdev KEY_A (30) maps to internal A
+    assert_eq!(keys::translate(keys::LAYOUT_LINUX, 30), keys::A);
+}
+
+// ============================================================
+// CHARACTER INPUT: hosts without a keycode layer
+// ============================================================
+
+/// Type `input` as characters and return the buffer string
+fn type_chars(method: u8, input: &str) -> String {
+    let mut e = Engine::new();
+    e.set_method(method);
+    for c in input.chars() {
+        e.on_char(c, false, false, false);
+    }
+    e.get_buffer_string()
+}
+
+#[test]
+fn from_char_maps_to_internal_keys() {
+    assert_eq!(keys::from_char('a'), Some(keys::A));
+    assert_eq!(keys::from_char('A'), Some(keys::A));
+    assert_eq!(keys::from_char('7'), Some(keys::N7));
+    assert_eq!(keys::from_char('@'), Some(keys::N2));
+    assert_eq!(keys::from_char('"'), Some(keys::QUOTE));
+    assert_eq!(keys::from_char(' '), Some(keys::SPACE));
+    assert_eq!(keys::from_char('\x08'), Some(keys::DELETE));
+    assert_eq!(keys::from_char('ệ'), None);
+}
+
+#[test]
+fn on_char_composes_like_keycodes() {
+    assert_eq!(type_chars(0, "vieetj"), "việt");
+    assert_eq!(type_chars(0, "Dduowngf"), "Đường");
+    assert_eq!(type_chars(1, "vie6t5"), "việt");
+}
+
+#[test]
+fn on_char_shifted_symbol_is_not_vni_mark() {
+    // '@' is Shift+2: must not apply huyền in VNI
+    let mut e = Engine::new();
+    e.set_method(1);
+    e.on_char('a', false, false, false);
+    let r = e.on_char('@', false, false, false);
+    assert_eq!(r.action, 0);
+}
+
+#[test]
+fn on_char_ignores_layout() {
+    let mut e = Engine::new();
+    e.set_layout(keys::LAYOUT_WINDOWS);
+    for c in "as".chars() {
+        e.on_char(c, false, false, false);
+    }
+    assert_eq!(e.get_buffer_string(), "á");
+}
+
+// ============================================================
+// VNI NUMPAD: keypad digits as tone/mark keys
+// ============================================================
+
+/// Type `input` in VNI with digits sent as keypad keys, return the buffer string
+fn type_vni_numpad(layout: u8, numpad: bool, input: &str) -> String {
+    let mut e = Engine::new();
+    e.set_method(1);
+    e.set_layout(layout);
+    e.set_vni_numpad(numpad);
+    for c in input.chars() {
+        let logical = match c.to_digit(10) {
+            Some(d) => [
+                KeyCode::Kp0,
+                KeyCode::Kp1,
+                KeyCode::Kp2,
+                KeyCode::Kp3,
+                KeyCode::Kp4,
+                KeyCode::Kp5,
+                KeyCode::Kp6,
+                KeyCode::Kp7,
+                KeyCode::Kp8,
+                KeyCode::Kp9,
+            ][d as usize],
+            None => KeyCode::from_platform(keys::LAYOUT_MACOS, char_to_key(c)).unwrap(),
+        };
+        e.on_key(logical.to_platform(layout).unwrap(), false, false);
+    }
+    e.get_buffer_string()
+}
+
+#[test]
+fn vni_numpad_digits_match_top_row() {
+    for (input, expected) in [
+        ("vie6t5", "việt"),
+        ("d9u7o7ng2", "đường"),
+        ("a8", "ă"),
+        ("to1i0", "toi"),
+    ] {
+        for layout in LAYOUTS {
+            assert_eq!(
+                type_vni_numpad(layout, true, input),
+                expected,
+                "layout={} input={}",
+                layout,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn vni_numpad_off_keeps_keypad_numeric() {
+    for layout in LAYOUTS {
+        let typed = type_vni_numpad(layout, false, "vie6t5");
+        assert!(!typed.contains('ệ'), "layout={} got {}", layout, typed);
+    }
+    // Telex ignores the option
+    let mut e = Engine::new();
+    e.set_vni_numpad(true);
+    for key in [keys::A, keys::KP1] {
+        e.on_key(key, false, false);
+    }
+    assert_eq!(e.get_buffer_string(), "a");
+}
+
+#[test]
+fn numpad_codes_round_trip() {
+    for layout in LAYOUTS {
+        let code = KeyCode::Kp5.to_platform(layout).unwrap();
+        assert_eq!(KeyCode::from_platform(layout, code), Some(KeyCode::Kp5));
+    }
+    assert_eq!(keys::translate(keys::LAYOUT_WINDOWS, 0x65), keys::KP5);
+    assert_eq!(keys::numpad_to_digit(keys::KP5), Some(keys::N5));
+    assert_eq!(keys::numpad_to_digit(keys::N5), None);
+}
@@ -0,0 +1,80 @@
+//! Protected words - never get Vietnamese transforms
+
+mod common;
+use common::type_word;
+use gonhanh_core::engine::Engine;
+
+fn engine_with(words: &[&str]) -> Engine {
+    let mut e = Engine::new();
+    for w in words {
+        e.add_protected_word(w);
+    }
+    e
+}
+
+#[test]
+fn protected_word_stays_raw() {
+    for (input, expected) in [
+        ("docs ", "docs "),
+        ("rust ", "rust "),
+        ("sass ", "sass "),
+        ("ddos ", "ddos "),
+    ] {
+        let mut e = engine_with(&["docs", "Rust", "sass", "DDoS"]);
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn without_protection_word_is_transformed() {
+    for (input, expected) in [
+        ("docs", "dóc"),
+        ("rust", "rút"),
+        ("sass", "sas"),
+        ("ddos", "đó"),
+    ] {
+        let mut e = Engine::new();
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn protected_word_case_insensitive() {
+    let mut e = engine_with(&["rust"]);
+    assert_eq!(type_word(&mut e, "Rust "), "Rust ");
+}
+
+#[test]
+fn diverging_word_resumes_transforms() {
+    // "do" is a prefix of "docs" (kept raw), "doi" diverges → normal processing
+    let mut e = engine_with(&["docs"]);
+    assert_eq!(type_word(&mut e, "dois"), "dói");
+}
+
+#[test]
+fn diverging_word_recomposes_raw_prefix() {
+    // "dd" is kept raw as a prefix of "ddos"; 'i' diverges and "dd" becomes "đ"
+    for (input, expected) in [
+        ("ddi", "đi"),
+        ("ddi ", "đi "),
+        ("ddieeuf", "điều"),
+        ("Ddi", "Đi"),
+        ("ddos ", "ddos "),
+    ] {
+        let mut e = engine_with(&["DDoS"]);
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn other_words_unaffected() {
+    let mut e = engine_with(&["docs"]);
+    assert_eq!(type_word(&mut e, "vieetj "), "việt ");
+}
+
+#[test]
+fn clear_protected_words() {
+    let mut e = engine_with(&["rust"]);
+    e.clear_protected_words();
+    assert_eq!(type_word(&mut e, "rust"), "rút");
+}
@@ -0,0 +1,125 @@
+//! T9 Keypad Tests
+//!
+//! Method 4: digits 2-9 cycle through their letter group, the selected
+//! letter is typed as Telex once another key (or the timeout) commits it.
+
+use gonhanh_core::data::keys;
+use gonhanh_core::engine::{Action, Engine, Result};
+use gonhanh_core::utils::char_to_key;
+
+/// Apply a result to the screen the way a host would
+fn apply(screen: &mut String, r: &Result, typed: Option<char>) {
+    if r.action != Action::None as u8 {
+        for _ in 0..r.backspace {
+            screen.pop();
+        }
+        for i in 0..r.count as usize {
+            screen.push(char::from_u32(r.chars[i]).unwrap());
+        }
+    } else if let Some(c) = typed {
+        screen.push(c);
+    }
+}
+
+/// Type keypad input: digits as keys, '.' = timeout (tick), '<' = backspace
+fn keypad(e: &mut Engine, input: &str) -> String {
+    let mut screen = String::new();
+    for c in input.chars() {
+        match c {
+            '.' => {
+                let r = e.tick(1000);
+                apply(&mut screen, &r, None);
+            }
+            '<' => {
+                let r = e.on_key(keys::DELETE, false, false);
+                if r.action != Action::None as u8 {
+                    apply(&mut screen, &r, None);
+                } else {
+                    screen.pop();
+                }
+            }
+            _ => {
+                let r = e.on_key(char_to_key(c), c.is_uppercase(), false);
+                apply(&mut screen, &r, Some(c));
+            }
+        }
+    }
+    screen
+}
+
+fn t9() -> Engine {
+    let mut e = Engine::new();
+    e.set_method(4);
+    e
+}
+
+#[test]
+fn method_id() {
+    let e = t9();
+    assert_eq!(e.method(), 4);
+}
+
+#[test]
+fn cycling_replaces_shown_letter() {
+    let mut e = t9();
+    assert_eq!(keypad(&mut e, "2"), "a");
+    assert_eq!(keypad(&mut e, "2"), "b");
+    assert_eq!(keypad(&mut e, "2"), "c");
+    // Wraps around
+    assert_eq!(keypad(&mut e, "2"), "a");
+}
+
+#[test]
+fn different_digit_commits() {
+    let mut e = t9();
+    // c-a-t: 222 · 2 · 8
+    assert_eq!(keypad(&mut e, "222.2.8 "), "cat ");
+    // Different digits commit without waiting
+    let mut e = t9();
+    assert_eq!(keypad(&mut e, "6668 "), "ot ");
+}
+
+#[test]
+fn commit_applies_telex() {
+    // d d a a u → "đâu"
+    let mut e = t9();
+    assert_eq!(keypad(&mut e, "3.3.2.2.88 "), "đâu ");
+    // v i e e t j → "việt"
+    let mut e = t9();
+    assert_eq!(keypad(&mut e, "888444.33.338.5 "), "việt ");
+}
+
+#[test]
+fn timeout_commits() {
+    let mut e = t9();
+    keypad(&mut e, "3.3");
+    // Second 'd' shown raw, tick commits it as the stroke
+    let r = e.tick(1000);
+    assert_eq!(r.action, Action::Send as u8);
+    assert_eq!(r.backspace, 2);
+    assert_eq!(r.chars[0], 'đ' as u32);
+
+    // Shorter timeout
+    let mut e = t9();
+    e.set_t9_timeout_ms(300);
+    keypad(&mut e, "2");
+    assert_eq!(e.tick(200).action, Action::None as u8);
+    keypad(&mut e, "2");
+    assert_eq!(e.tick(200).action, Action::None as u8);
+    e.tick(100);
+    assert_eq!(e.get_buffer_string(), "b");
+}
+
+#[test]
+fn backspace_drops_pending_letter() {
+    let mut e = t9();
+    assert_eq!(keypad(&mut e, "2.22<8 "), "at ");
+}
+
+#[test]
+fn other_methods_keep_digits() {
+    let mut e = Engine::new();
+    e.set_method(4);
+    e.set_method(1);
+    assert_eq!(keypad(&mut e, "a1"), "á");
+}
@@ -0,0 +1,39 @@
+//! Tests for the decision trace (cargo feature `trace`)
+#![cfg(feature = "trace")]
+
+mod common;
+use gonhanh_core::engine::Engine;
+
+#[test]
+fn trace_records_fired_transforms() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "vieetj");
+    let trace = e.take_trace();
+    assert!(trace.iter().any(|t| t.starts_with("key ")));
+    assert!(trace.iter().any(|t| t.starts_with("try_tone fired")));
+    assert!(trace.iter().any(|t| t.starts_with("try_mark fired")));
+    // Taking clears the trace
+    assert!(e.take_trace().is_empty());
+}
+
+#[test]
+fn trace_records_revert_and_reposition() {
+    let mut e = Engine::new();
+    common::type_word(&mut e, "ass");
+    assert!(e.take_trace().iter().any(|t| t == "revert mark"));
+
+    e.clear();
+    common::type_word(&mut e, "muasn");
+    assert!(e
+        .take_trace()
+        .iter()
+        .any(|t| t.starts_with("reposition mark")));
+}
+
+#[test]
+fn trace_records_auto_restore() {
+    let mut e = Engine::new();
+    e.set_english_auto_restore(true);
+    common::type_word(&mut e, "user ");
+    assert!(e.take_trace().iter().any(|t| t == "auto-restore on space"));
+}
@@ -2,6 +2,8 @@
 
 mod common;
 use common::{telex, telex_auto_restore, telex_traditional, vni, vni_traditional};
+use gonhanh_core::engine::Engine;
+use gonhanh_core::utils::type_word;
 
 // ============================================================
 // BACKSPACE & CORRECTIONS
@@ -470,6 +472,42 @@ const VNI_CAPS: &[(&str, &str)] = &[
     ("TRU7O7NG2", "TRƯỜNG"),
 ];
 
+// ALL CAPS: every key uppercase, including modifier keys (CapsLock on)
+const TELEX_ALL_CAPS: &[(&str, &str)] = &[
+    ("HOAF", "HOÀ"),
+    ("VIEEJT", "VIỆT"),    // mark before final
+    ("DDUOWNGF", "ĐƯỜNG"), // uo + w → ươ
+    ("TIEENGS", "TIẾNG"),
+    ("QUYEENF", "QUYỀN"),
+    ("GIAF", "GIÀ"),
+    ("DDAWKS", "ĐẮK"), // breve + k final
+    ("TRAWM", "TRĂM"),
+    ("CHUWA", "CHƯA"),
+    ("W", "Ư"),
+    ("AAS", "Ấ"),
+    ("OWF", "Ờ"),
+    ("DDOONGF", "ĐỒNG"),
+    ("MUOONS", "MUỐN"),
+    ("VIEETJ NAM", "VIỆT NAM"),
+    // Reverts keep uppercase
+    ("ASS", "AS"),
+    ("AAA", "AA"),
+    ("OWW", "OW"),
+    ("WW", "W"),
+    ("DDD", "DD"),   // stroke revert keeps uppercase
+    ("DADD", "DAD"), // short-pattern stroke revert keeps uppercase
+];
+
+const VNI_ALL_CAPS: &[(&str, &str)] = &[
+    ("HOA2", "HOÀ"),
+    ("VIE6T5", "VIỆT"),
+    ("D9A8K1", "ĐẮK"),
+    ("NGU7O7I2", "NGƯỜI"),
+    ("A11", "A1"),
+    ("A66", "A6"),
+    ("D99", "D9"),
+];
+
 // ============================================================
 // GREETINGS
 // ============================================================
@@ -987,6 +1025,28 @@ const TELEX_SWITCH_DIACRITICS: &[(&str, &str)] = &[
     ("uoow", "ươ"), // uô + w → ươ (switch to horn)
 ];
 
+// ============================================================
+// VNI REMOVE - '0' strips diacritics anywhere in the word
+// ============================================================
+
+const VNI_REMOVE: &[(&str, &str)] = &[
+    // Mark removed first, then vowel tone
+    ("a10", "a"),
+    ("a60", "a"),
+    ("viet650", "viêt"),  // mark on ê removed, circumflex kept
+    ("viet6500", "viet"), // second 0 removes circumflex
+    ("vie6t50", "viêt"),  // 0 after final consonant
+    ("tu1o6n0", "tuôn"),  // mark removed even when tone is on another vowel
+    ("u7o7i10", "ươi"),   // ươ compound keeps horn
+    ("muo6i10", "muôi"),  // 0 after trailing vowel
+    ("ba10n", "ban"),     // typing continues after removal
+    ("VIE6T50", "VIÊT"),  // case preserved
+    // Nothing removable → literal '0'
+    ("a0", "a0"),
+    ("a100", "a0"),
+    ("vie6t5 0", "việt 0"), // new word, nothing to remove
+];
+
 // ============================================================
 // TEST FUNCTIONS
 // ============================================================
@@ -1006,6 +1066,97 @@ fn telex_typos() {
     telex(TELEX_TYPOS);
 }
 
+// Final consonant typed after the tone: the mark moves to the vowel the final
+// calls for ("hoà" + 'n' → "hoàn", "khỏa" + "ng" → "khoảng" in classic style)
+#[test]
+fn late_final_repositions_tone() {
+    telex(&[
+        ("hoajn", "hoạn"),
+        ("hoanj", "hoạn"),
+        ("hoafn", "hoàn"),
+        ("khoarng", "khoảng"),
+        ("khoangr", "khoảng"),
+        ("nguyeexn", "nguyễn"),
+        ("nguyeenx", "nguyễn"),
+        ("huyfnh", "huỳnh"),
+    ]);
+    telex_traditional(&[
+        ("hoajn", "hoạn"),
+        ("hoafn", "hoàn"),
+        ("khoarng", "khoảng"),
+        ("khoafng", "khoàng"),
+        ("nguyeexn", "nguyễn"),
+        ("huyfnh", "huỳnh"),
+    ]);
+    vni(&[
+        ("hoa5n", "hoạn"),
+        ("khoa3ng", "khoảng"),
+        ("nguye64n", "nguyễn"),
+    ]);
+    vni_traditional(&[
+        ("hoa5n", "hoạn"),
+        ("khoa3ng", "khoảng"),
+        ("huy2nh", "huỳnh"),
+    ]);
+}
+
+// Open diphthongs ia/ya/ua/ưa: the tone goes on the first vowel whether it is
+// typed before or after the second one; after "qu"/"gi" the 'a' is the nucleus
+#[test]
+fn open_diphthong_tone_order() {
+    let cases: &[(&str, &str)] = &[
+        ("mias", "mía"),
+        ("misa", "mía"),
+        ("khuya", "khuya"),
+        ("khuyaf", "khuỳa"),
+        ("khufya", "khuỳa"),
+        ("cuar", "của"),
+        ("cura", "của"),
+        ("muaf", "mùa"),
+        ("mufa", "mùa"),
+        ("chuaf", "chùa"),
+        ("chufa", "chùa"),
+        ("cuwar", "cửa"),
+        ("mufwa", "mừa"),
+        ("quaf", "quà"),
+        ("qufa", "quà"),
+        ("giaf", "già"),
+        ("gifa", "già"),
+    ];
+    telex(cases);
+    telex_traditional(cases);
+}
+
+// "qu" initial: the tone lands on the vowel after 'u' whatever the typing order,
+// including a tone typed on 'u' before the "qu" initial is complete ("qur" + "a")
+#[test]
+fn qu_initial_tone_order() {
+    telex(&[
+        ("quar", "quả"),
+        ("qura", "quả"),
+        ("quys", "quý"),
+        ("qusy", "quý"),
+        ("quaxng", "quãng"),
+        ("quangx", "quãng"),
+        ("quxang", "quãng"),
+        ("quyeenr", "quyển"),
+        ("quyenre", "quyển"),
+        ("quryeen", "quyển"),
+        ("quyreen", "quyển"),
+    ]);
+    vni(&[
+        ("qua3", "quả"),
+        ("qu3a", "quả"),
+        ("quy1", "quý"),
+        ("qu1y", "quý"),
+        ("quang4", "quãng"),
+        ("qu4ang", "quãng"),
+        ("quye6n3", "quyển"),
+        ("quyen36", "quyển"),
+        ("qu3ye6n", "quyển"),
+    ]);
+}
+
 #[test]
 fn telex_rapid_typing() {
     telex(TELEX_RAPID);
@@ -1026,6 +1177,62 @@ fn vni_capitalization() {
     vni(VNI_CAPS);
 }
 
+#[test]
+fn telex_all_caps() {
+    telex(TELEX_ALL_CAPS);
+}
+
+#[test]
+fn vni_all_caps() {
+    vni(VNI_ALL_CAPS);
+}
+
+// Stroke revert: the literal 'd' takes the case of the reverting keystroke,
+// the first 'd' keeps its own ("ddd" → "dd" with each case preserved)
+#[test]
+fn stroke_revert_keeps_typed_case() {
+    telex(&[
+        ("DDd", "Dd"),
+        ("Ddd", "Dd"),
+        ("DDD", "DD"),
+        ("ddD", "dD"),
+        ("DDafd", "Đàd"),
+    ]);
+}
+
+/// CapsLock on: host reports caps=true for every key, typed chars are lowercase
+#[test]
+fn caps_lock_composes_uppercase() {
+    use gonhanh_core::engine::{Action, Engine};
+    use gonhanh_core::utils::char_to_key;
+
+    for (input, expected) in [
+        ("vieetj", "VIỆT"),
+        ("dduwowngf", "ĐƯỜNG"),
+        ("hoaf", "HOÀ"),
+        ("ddawks", "ĐẮK"),
+    ] {
+        let mut e = Engine::new();
+        let mut screen = String::new();
+        for c in input.chars() {
+            let r = e.on_key(char_to_key(c), true, false);
+            if r.action == Action::Send as u8 {
+                for _ in 0..r.backspace {
+                    screen.pop();
+                }
+                screen.extend(
+                    r.chars[..r.count as usize]
+                        .iter()
+                        .filter_map(|&c| char::from_u32(c)),
+                );
+            } else {
+                screen.push(c.to_ascii_uppercase());
+            }
+        }
+        assert_eq!(screen, expected, "CapsLock input: {}", input);
+    }
+}
+
 #[test]
 fn telex_greetings() {
     telex(TELEX_GREETINGS);
@@ -1146,6 +1353,11 @@ fn vni_switch_diacritics() {
     vni(VNI_SWITCH_DIACRITICS);
 }
 
+#[test]
+fn vni_remove_diacritics() {
+    vni(VNI_REMOVE);
+}
+
 // ============================================================
 // NON-ADJACENT STROKE - Issue #51
 // ============================================================
@@ -1287,6 +1499,10 @@ const TELEX_VALID_BREVE: &[(&str, &str)] = &[
     ("lawks", "lắk"),             // lắk - lowercase
     ("Ddawks Lawks", "Đắk Lắk"),  // Đắk Lắk - full province name
     ("Ddawks Noong", "Đắk Nông"), // Đắk Nông province (oo = ô)
+    ("Ddawsk", "Đắk"),            // Đắk - mark before final k
+    ("Lawsk", "Lắk"),             // Lắk - mark before final k
+    // Buôn Ma Thuột - capital of Đắk Lắk
+    ("Buoon Ma Thuootj", "Buôn Ma Thuột"),
     // Kr initial for ethnic minority words (Krông Búk district)
     ("Kroong", "Krông"),          // Krông - Kr initial + ô
     ("Busk", "Búk"),              // Búk - B + ú + k
@@ -1310,6 +1526,8 @@ const VNI_VALID_BREVE: &[(&str, &str)] = &[
     ("la81k", "lắk"),             // lắk - lowercase
     ("D9a81k La81k", "Đắk Lắk"),  // Đắk Lắk - full province name
     ("D9a81k No6ng", "Đắk Nông"), // Đắk Nông province (o6 = ô)
+    // Buôn Ma Thuột - capital of Đắk Lắk
+    ("Buo6n Ma Thuo6t5", "Buôn Ma Thuột"),
 ];
 
 // ============================================================
@@ -1556,12 +1774,50 @@ fn telex_breve_edge_cases() {
     telex(TELEX_BREVE_EDGE_CASES);
 }
 
+// Issue #44: Deferred breve - open syllable keeps "aw" until a final is typed
+#[test]
+fn deferred_breve_setting() {
+    for (method, input, deferred, immediate) in [
+        (0, "aw", "aw", "ă"),
+        (0, "raw", "raw", "ră"),
+        (0, "trawm", "trăm", "trăm"),
+        (0, "trawms", "trắm", "trắm"),
+        (1, "tra8", "tra8", "tră"),
+        (1, "tra8m", "trăm", "trăm"),
+    ] {
+        let mut e = Engine::new();
+        e.set_method(method);
+        assert_eq!(type_word(&mut e, input), immediate, "default: {}", input);
+
+        let mut e = Engine::new();
+        e.set_method(method);
+        e.set_defer_breve(true);
+        assert_eq!(type_word(&mut e, input), deferred, "deferred: {}", input);
+    }
+}
+
 // Issue #133: Horn placement - "uơ" vs "ươ" patterns
 #[test]
 fn telex_horn_placement() {
     telex(TELEX_HORN_PLACEMENT);
 }
 
+// Eager uo horn: both vowels get horn right away, even without a final
+#[test]
+fn telex_horn_placement_eager() {
+    for (input, expected) in [
+        ("huow", "hươ"),
+        ("khuow", "khươ"),
+        ("duowc", "dươc"),
+        ("duowcj", "dược"),
+        ("muowif", "mười"),
+    ] {
+        let mut e = Engine::new();
+        e.set_eager_uo_horn(true);
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+}
+
 // Issue #312: Same vowel after marked vowel
 #[test]
 fn telex_same_vowel_after_mark() {
@@ -1681,3 +1937,41 @@ fn telex_traditional_tone_placement() {
 fn vni_traditional_tone_placement() {
     vni_traditional(VNI_TRADITIONAL_TONE);
 }
+
+// ============================================================
+// OA/OE STYLE - independent of uy placement
+// ============================================================
+
+#[test]
+fn oa_oe_style_independent_of_uy() {
+    // Traditional oa/oe, modern uy
+    let mut e = Engine::new();
+    e.set_oa_oe_style(0);
+    for (input, expected) in [
+        ("hoaf ", "hòa "),
+        ("khoer ", "khỏe "),
+        ("thuys ", "thuý "),
+        ("toanf ", "toàn "),
+    ] {
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // Modern oa/oe, traditional uy
+    let mut e = Engine::new();
+    e.set_modern_tone(false);
+    e.set_oa_oe_style(1);
+    for (input, expected) in [
+        ("hoaf ", "hoà "),
+        ("khoer ", "khoẻ "),
+        ("thuys ", "thúy "),
+        ("toanf ", "toàn "),
+    ] {
+        assert_eq!(type_word(&mut e, input), expected, "input: {}", input);
+    }
+
+    // set_modern_tone still controls both
+    let mut e = Engine::new();
+    e.set_oa_oe_style(0);
+    e.set_modern_tone(true);
+    assert_eq!(type_word(&mut e, "hoaf "), "hoà ");
+}
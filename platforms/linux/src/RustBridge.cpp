@@ -27,7 +27,8 @@ std::pair<int, std::string> RustBridge::processKey(
 
     std::pair<int, std::string> output = {0, ""};
 
-    if (result->action == static_cast<uint8_t>(ImeAction::Send)) {
+    if (result->action == static_cast<uint8_t>(ImeAction::Send) ||
+        result->action == static_cast<uint8_t>(ImeAction::Restore)) {
         output.first = result->backspace;
 
         // Convert UTF-32 chars to UTF-8 string
@@ -423,7 +423,7 @@ private class TextInjector {
 // MARK: - FFI (Rust Bridge)
 
 /// FFI result struct - must match Rust `Result` struct layout exactly
-/// Size: 64 UInt32 chars (256 bytes) + 4 bytes = 260 bytes
+/// Size: 64 UInt32 chars (256 bytes) + 5 bytes, padded to 264 bytes
 /// Max replacement: 63 UTF-32 codepoints (Vietnamese diacritics = 1 each)
 private struct ImeResult {
     // 64 UInt32 values for UTF-32 codepoints (matches core/src/engine/buffer.rs MAX)
@@ -441,6 +441,7 @@ private struct ImeResult {
     var backspace: UInt8
     var count: UInt8
     var flags: UInt8  // bit 0: key_consumed
+    var toneIndex: UInt8  // index in chars of the new diacritic (0xFF = none)
 }
 
 private let FLAG_KEY_CONSUMED: UInt8 = 0x01  // Key was consumed by shortcut, don't pass through
@@ -488,7 +489,8 @@ class RustBridge {
         defer { ime_free(ptr) }
 
         let r = ptr.pointee
-        guard r.action == 1 else { return nil }
+        // 1 = Send, 2 = Restore (raw keystrokes); both replace text the same way
+        guard r.action == 1 || r.action == 2 else { return nil }
 
         let chars = withUnsafePointer(to: r.chars) { p in
             p.withMemoryRebound(to: UInt32.self, capacity: 64) { bound in